@@ -0,0 +1,1228 @@
+#[cfg(test)]
+mod tests;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use tracing::{debug, info, warn};
+
+#[cfg(feature = "decorations")]
+use std::path::Path;
+
+use image::DynamicImage;
+#[cfg(not(feature = "dmabuf"))]
+use libimgvwr::renderer::frame::FrameBuffer;
+#[cfg(any(feature = "gpu-vulkan", feature = "gpu-gles"))]
+use libimgvwr::renderer::gpu::GpuContext;
+use libimgvwr::{
+    keybinds::{
+        Action, KeyCombo, Modifiers, keysym_digit, keysym_is_modifier,
+        mouse::{MouseCombo, MouseInput},
+    },
+    loader,
+    navigator::Navigator,
+    renderer::{ImageId, Source, mip::PendingMips},
+    viewport::{Edge, FitMode, ViewportState},
+    wayland::{InputEvent, WaylandContext, pointer::ScrollKind, touch::TouchGesture},
+};
+
+use crate::settings::AppSettings;
+
+/// Source of the [`ImageId::image`] of every loaded image.
+static NEXT_IMAGE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A fresh id for a newly loaded image.
+fn next_image_id() -> u64 {
+    NEXT_IMAGE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Holds either a static single image or an animated sequence of frames.
+enum ImageHolder {
+    Static {
+        image: Arc<DynamicImage>,
+        id: u64,
+        /// Zoom-out levels, built in the background after loading.
+        mips: PendingMips,
+    },
+    #[cfg(any(
+        feature = "gif",
+        feature = "avif-anim",
+        feature = "jxl-anim",
+        feature = "webp-anim",
+        feature = "apng"
+    ))]
+    Animated {
+        frames: Vec<(DynamicImage, std::time::Duration)>,
+        current: usize,
+        next_at: Instant,
+        /// Id shared by every frame; [`ImageId::frame`] tells them apart.
+        id: u64,
+    },
+}
+
+impl ImageHolder {
+    /// A still image, with its mip pyramid built in linear light if
+    /// `linear_light`.
+    fn from_image(image: DynamicImage, linear_light: bool) -> Self {
+        let image = Arc::new(image);
+        Self::Static {
+            mips: PendingMips::spawn(Arc::clone(&image), linear_light),
+            image,
+            id: next_image_id(),
+        }
+    }
+
+    fn current(&self) -> &DynamicImage {
+        match self {
+            Self::Static { image, .. } => image,
+            #[cfg(any(
+                feature = "gif",
+                feature = "avif-anim",
+                feature = "jxl-anim",
+                feature = "webp-anim",
+                feature = "apng"
+            ))]
+            Self::Animated {
+                frames, current, ..
+            } => &frames[*current].0,
+        }
+    }
+
+    /// [`current`](Self::current) with its mip pyramid, once built.
+    fn source(&self) -> Source<'_> {
+        match self {
+            Self::Static { image, id, mips } => Source {
+                image,
+                mips: mips.get(),
+                id: Some(ImageId {
+                    image: *id,
+                    frame: 0,
+                }),
+            },
+            #[cfg(any(
+                feature = "gif",
+                feature = "avif-anim",
+                feature = "jxl-anim",
+                feature = "webp-anim",
+                feature = "apng"
+            ))]
+            Self::Animated { current, id, .. } => Source {
+                id: Some(ImageId {
+                    image: *id,
+                    frame: *current,
+                }),
+                ..self.current().into()
+            },
+        }
+    }
+
+    /// Advance animation by one frame if its display time has elapsed.
+    /// Returns `true` if the frame changed and a redraw is needed.
+    fn tick(&mut self) -> bool {
+        #[cfg(any(
+            feature = "gif",
+            feature = "avif-anim",
+            feature = "jxl-anim",
+            feature = "webp-anim",
+            feature = "apng"
+        ))]
+        if let Self::Animated {
+            frames,
+            current,
+            next_at,
+            ..
+        } = self
+        {
+            let now = Instant::now();
+            if now >= *next_at {
+                *current = (*current + 1) % frames.len();
+                let delay = frames[*current].1;
+                *next_at = now + delay;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// `true` once after the mip pyramid of a still image completes.
+    fn take_mips_ready(&mut self) -> bool {
+        match self {
+            Self::Static { mips, .. } => mips.take_ready(),
+            #[cfg(any(
+                feature = "gif",
+                feature = "avif-anim",
+                feature = "jxl-anim",
+                feature = "webp-anim",
+                feature = "apng"
+            ))]
+            Self::Animated { .. } => false,
+        }
+    }
+
+    /// How long the event loop may wait for input: the frame cadence while
+    /// animating or while the mip pyramid is built, otherwise indefinitely
+    /// (negative).
+    fn poll_timeout_ms(&self) -> i32 {
+        match self {
+            Self::Static { mips, .. } if mips.is_building() => MIPS_POLL_MS,
+            Self::Static { .. } => -1,
+            #[cfg(any(
+                feature = "gif",
+                feature = "avif-anim",
+                feature = "jxl-anim",
+                feature = "webp-anim",
+                feature = "apng"
+            ))]
+            Self::Animated { .. } => 16,
+        }
+    }
+}
+
+/// How often the event loop checks whether a mip pyramid is complete.
+const MIPS_POLL_MS: i32 = 16;
+
+/// How long the event loop may wait for input: until the next animation
+/// frame or `refine_at`, whichever comes first (negative: indefinitely).
+fn poll_timeout_ms(image: &ImageHolder, refine_at: Option<Instant>) -> i32 {
+    let animation = image.poll_timeout_ms();
+    let Some(at) = refine_at else {
+        return animation;
+    };
+    // Round up so as not to wake just before `at`.
+    let remaining = at
+        .saturating_duration_since(Instant::now())
+        .as_micros()
+        .div_ceil(1000);
+    let remaining = i32::try_from(remaining).unwrap_or(i32::MAX);
+    if animation < 0 {
+        remaining
+    } else {
+        remaining.min(animation)
+    }
+}
+
+#[derive(Default)]
+struct EventOutcome {
+    dirty: bool,
+    /// The image was zoomed or panned; frames use the interactive filter
+    /// until input settles.
+    interactive: bool,
+    quit: bool,
+    navigated: bool,
+    /// The active fit mode must be reapplied (mode change or rotation).
+    refit: bool,
+    /// Ask the compositor to enter or leave fullscreen.
+    toggle_fullscreen: bool,
+    /// Ask the compositor to maximize or restore the window.
+    toggle_maximize: bool,
+}
+
+impl EventOutcome {
+    /// Combine the outcomes of two handlers run for the same event.
+    fn merge(self, other: EventOutcome) -> EventOutcome {
+        EventOutcome {
+            dirty: self.dirty || other.dirty,
+            interactive: self.interactive || other.interactive,
+            quit: self.quit || other.quit,
+            navigated: self.navigated || other.navigated,
+            refit: self.refit || other.refit,
+            toggle_fullscreen: self.toggle_fullscreen || other.toggle_fullscreen,
+            toggle_maximize: self.toggle_maximize || other.toggle_maximize,
+        }
+    }
+}
+
+/// The active [`FitMode`] and whether it still governs the current scale.
+///
+/// Manual zooming releases the fit; window resizes only reapply the mode
+/// while it is still `active`. Navigation and fit actions always reapply it.
+struct FitState {
+    mode: FitMode,
+    active: bool,
+}
+
+/// Input state that spans several events.
+#[derive(Default)]
+struct InputState {
+    /// Digits typed so far for `Action::GoTo`.
+    count: Option<usize>,
+    /// Mouse button currently dragging the image (`Action::PanDrag`).
+    drag_button: Option<u32>,
+    /// Scroll accumulated towards the next wheel notch for discrete actions,
+    /// `(horizontal, vertical)`.
+    scroll: (f32, f32),
+    /// Quarter turns (clockwise positive) applied by the current pinch.
+    pinch_turns: i32,
+}
+
+#[cfg(feature = "decorations")]
+fn make_title(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("imgvwr");
+    format!("{name} — imgvwr")
+}
+
+fn apply_fit(
+    fit: &mut FitState,
+    img: &DynamicImage,
+    window: (u32, u32),
+    settings: &AppSettings,
+    viewport: &mut ViewportState,
+) {
+    viewport.fit(
+        fit.mode,
+        (img.width(), img.height()),
+        window,
+        settings.min_scale,
+        settings.max_scale,
+    );
+    fit.active = true;
+}
+
+fn load_image(
+    path: &std::path::Path,
+    linear_light: bool,
+) -> Result<ImageHolder, loader::LoadError> {
+    let _ext = path
+        .extension()
+        .and_then(|e: &std::ffi::OsStr| e.to_str())
+        .map(str::to_ascii_lowercase);
+
+    #[cfg(feature = "gif")]
+    if _ext.as_deref() == Some("gif") {
+        let anim = loader::load_gif_frames(path)?;
+        return Ok(anim_frames_to_holder(anim, linear_light));
+    }
+
+    #[cfg(feature = "jxl-anim")]
+    if _ext.as_deref() == Some("jxl")
+        && let Ok(anim) = loader::load_jxl_anim_frames(path)
+    {
+        return Ok(anim_frames_to_holder(anim, linear_light));
+    }
+
+    #[cfg(feature = "avif-anim")]
+    if _ext.as_deref() == Some("avif")
+        && let Ok(anim) = loader::load_avif_anim_frames(path)
+    {
+        return Ok(anim_frames_to_holder(anim, linear_light));
+    }
+
+    #[cfg(feature = "webp-anim")]
+    if _ext.as_deref() == Some("webp")
+        && let Ok(anim) = loader::load_webp_anim_frames(path)
+    {
+        return Ok(anim_frames_to_holder(anim, linear_light));
+    }
+
+    #[cfg(feature = "apng")]
+    if _ext.as_deref() == Some("png")
+        && let Ok(anim) = loader::load_apng_frames(path)
+    {
+        return Ok(anim_frames_to_holder(anim, linear_light));
+    }
+
+    loader::load(path).map(|image| ImageHolder::from_image(image, linear_light))
+}
+
+#[cfg(any(
+    feature = "gif",
+    feature = "avif-anim",
+    feature = "jxl-anim",
+    feature = "webp-anim",
+    feature = "apng"
+))]
+fn anim_frames_to_holder(anim: loader::AnimFrames, linear_light: bool) -> ImageHolder {
+    if anim.frames.len() > 1 {
+        let next_at = Instant::now() + anim.frames[0].1;
+        ImageHolder::Animated {
+            id: next_image_id(),
+            frames: anim.frames,
+            current: 0,
+            next_at,
+        }
+    } else {
+        let img = anim.frames.into_iter().next().map(|(img, _)| img);
+        let img = img.unwrap_or_else(|| image::DynamicImage::new_rgba8(1, 1));
+        ImageHolder::from_image(img, linear_light)
+    }
+}
+
+fn navigate_to(
+    path: PathBuf,
+    image: &mut ImageHolder,
+    viewport: &mut ViewportState,
+    linear_light: bool,
+) -> bool {
+    match load_image(&path, linear_light) {
+        Ok(img) => {
+            *image = img;
+            viewport.reset();
+            info!(path = %path.display(), "navigated to image");
+            true
+        }
+        Err(e) => {
+            warn!(path = %path.display(), error = %e, "failed to load image");
+            false
+        }
+    }
+}
+
+/// Move the navigator with `step` and load the image it lands on. A `step`
+/// returning `None` leaves the current image in place.
+fn on_navigate(
+    navigator: &mut Navigator,
+    image: &mut ImageHolder,
+    viewport: &mut ViewportState,
+    linear_light: bool,
+    step: impl FnOnce(&mut Navigator) -> Option<&std::path::Path>,
+) -> EventOutcome {
+    let Some(path) = step(navigator).map(|p| p.to_path_buf()) else {
+        return EventOutcome::default();
+    };
+    let success = navigate_to(path, image, viewport, linear_light);
+    EventOutcome {
+        dirty: success,
+        navigated: success,
+        ..Default::default()
+    }
+}
+
+/// Jump to the 1-based `count` typed before the go-to key.
+fn on_go_to(
+    count: Option<usize>,
+    navigator: &mut Navigator,
+    image: &mut ImageHolder,
+    viewport: &mut ViewportState,
+    linear_light: bool,
+) -> EventOutcome {
+    let Some(index) = count.and_then(|n| n.checked_sub(1)) else {
+        debug!("go to without an index prefix");
+        return EventOutcome::default();
+    };
+    on_navigate(navigator, image, viewport, linear_light, |n| {
+        let path = n.go_to(index);
+        if path.is_none() {
+            warn!(index = index + 1, "image index out of range");
+        }
+        path
+    })
+}
+
+fn on_rotate_left(fit: &FitState, viewport: &mut ViewportState) -> EventOutcome {
+    viewport.rotate_left();
+    debug!(rotation = viewport.rotation, "rotated left");
+    EventOutcome {
+        dirty: true,
+        refit: fit.active,
+        ..Default::default()
+    }
+}
+
+fn on_rotate_right(fit: &FitState, viewport: &mut ViewportState) -> EventOutcome {
+    viewport.rotate_right();
+    debug!(rotation = viewport.rotation, "rotated right");
+    EventOutcome {
+        dirty: true,
+        refit: fit.active,
+        ..Default::default()
+    }
+}
+
+fn on_set_fit_mode(mode: FitMode, fit: &mut FitState) -> EventOutcome {
+    fit.mode = mode;
+    info!(fit_mode = ?mode, "fit mode changed");
+    EventOutcome {
+        dirty: true,
+        refit: true,
+        ..Default::default()
+    }
+}
+
+fn on_flip_horizontal(viewport: &mut ViewportState) -> EventOutcome {
+    viewport.flip_horizontal();
+    debug!(flip_h = viewport.flip_h, "flipped horizontally");
+    EventOutcome {
+        dirty: true,
+        ..Default::default()
+    }
+}
+
+fn on_flip_vertical(viewport: &mut ViewportState) -> EventOutcome {
+    viewport.flip_vertical();
+    debug!(flip_v = viewport.flip_v, "flipped vertically");
+    EventOutcome {
+        dirty: true,
+        ..Default::default()
+    }
+}
+
+fn on_delete_file(
+    navigator: &mut Navigator,
+    image: &mut ImageHolder,
+    viewport: &mut ViewportState,
+    linear_light: bool,
+) -> EventOutcome {
+    let path = navigator.current().to_path_buf();
+    match std::fs::remove_file(&path) {
+        Ok(()) => {
+            info!(path = %path.display(), "deleted file");
+            match navigator.remove_current() {
+                Some(next) => {
+                    let next = next.to_path_buf();
+                    let success = navigate_to(next, image, viewport, linear_light);
+                    EventOutcome {
+                        dirty: success,
+                        navigated: success,
+                        ..Default::default()
+                    }
+                }
+                None => EventOutcome {
+                    quit: true,
+                    ..Default::default()
+                },
+            }
+        }
+        Err(e) => {
+            warn!(path = %path.display(), error = %e, "failed to delete file");
+            EventOutcome::default()
+        }
+    }
+}
+
+/// Zoom by `delta` keeping the image point under `anchor` (buffer pixels)
+/// fixed, leaving fit mode.
+fn on_zoom(
+    delta: f32,
+    anchor: (f32, f32),
+    window: (u32, u32),
+    settings: &AppSettings,
+    fit: &mut FitState,
+    viewport: &mut ViewportState,
+) -> EventOutcome {
+    fit.active = false;
+    viewport.zoom_by_at(
+        delta,
+        settings.min_scale,
+        settings.max_scale,
+        anchor,
+        window,
+    );
+    debug!(scale = viewport.scale, "zoomed");
+    EventOutcome {
+        dirty: true,
+        interactive: true,
+        ..Default::default()
+    }
+}
+
+fn on_zoom_fit() -> EventOutcome {
+    EventOutcome {
+        dirty: true,
+        refit: true,
+        ..Default::default()
+    }
+}
+
+/// Show `edge` of the image, leaving fit mode like a pan.
+fn on_jump_to_edge(
+    edge: Edge,
+    img: &DynamicImage,
+    window: (u32, u32),
+    fit: &mut FitState,
+    viewport: &mut ViewportState,
+) -> EventOutcome {
+    fit.active = false;
+    viewport.align_edge(edge, (img.width(), img.height()), window);
+    EventOutcome {
+        dirty: true,
+        ..Default::default()
+    }
+}
+
+/// Pan by `(dx, dy)`, leaving fit mode so that a later resize keeps the
+/// offset.
+fn on_pointer_motion(
+    dx: f32,
+    dy: f32,
+    fit: &mut FitState,
+    viewport: &mut ViewportState,
+) -> EventOutcome {
+    fit.active = false;
+    viewport.pan(dx, dy);
+    EventOutcome {
+        dirty: true,
+        interactive: true,
+        ..Default::default()
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn on_action(
+    action: Action,
+    count: Option<usize>,
+    settings: &AppSettings,
+    navigator: &mut Navigator,
+    image: &mut ImageHolder,
+    fit: &mut FitState,
+    viewport: &mut ViewportState,
+    window: (u32, u32),
+    scale: f32,
+) -> EventOutcome {
+    // `pan_step` is in surface pixels; the viewport works in buffer pixels.
+    let step = settings.pan_step * scale;
+    let skip = settings.skip_step as isize;
+    let linear_light = settings.linear_light;
+    let centre = (window.0 as f32 / 2.0, window.1 as f32 / 2.0);
+    match action {
+        Action::Quit => EventOutcome {
+            quit: true,
+            ..Default::default()
+        },
+        Action::Prev => on_navigate(navigator, image, viewport, linear_light, |n| Some(n.prev())),
+        Action::Next => on_navigate(navigator, image, viewport, linear_light, |n| Some(n.next())),
+        Action::First => on_navigate(navigator, image, viewport, linear_light, |n| {
+            Some(n.first())
+        }),
+        Action::Last => on_navigate(navigator, image, viewport, linear_light, |n| Some(n.last())),
+        Action::SkipForward => on_navigate(navigator, image, viewport, linear_light, |n| {
+            Some(n.skip(skip))
+        }),
+        Action::SkipBackward => on_navigate(navigator, image, viewport, linear_light, |n| {
+            Some(n.skip(-skip))
+        }),
+        Action::Random => on_navigate(navigator, image, viewport, linear_light, |n| {
+            Some(n.random())
+        }),
+        Action::GoTo => on_go_to(count, navigator, image, viewport, linear_light),
+        Action::RotateLeft => on_rotate_left(fit, viewport),
+        Action::RotateRight => on_rotate_right(fit, viewport),
+        Action::FlipHorizontal => on_flip_horizontal(viewport),
+        Action::FlipVertical => on_flip_vertical(viewport),
+        Action::CycleFitMode => on_set_fit_mode(fit.mode.next(), fit),
+        Action::SetFitMode(mode) => on_set_fit_mode(mode, fit),
+        Action::ZoomIn => on_zoom(settings.scale_step, centre, window, settings, fit, viewport),
+        Action::ZoomOut => on_zoom(
+            -settings.scale_step,
+            centre,
+            window,
+            settings,
+            fit,
+            viewport,
+        ),
+        Action::ZoomActual => on_zoom(
+            1.0 / viewport.scale - 1.0,
+            centre,
+            window,
+            settings,
+            fit,
+            viewport,
+        ),
+        Action::ZoomFit => on_zoom_fit(),
+        Action::PanLeft => on_pointer_motion(step, 0.0, fit, viewport),
+        Action::PanRight => on_pointer_motion(-step, 0.0, fit, viewport),
+        Action::PanUp => on_pointer_motion(0.0, step, fit, viewport),
+        Action::PanDown => on_pointer_motion(0.0, -step, fit, viewport),
+        Action::JumpToEdge(edge) => on_jump_to_edge(edge, image.current(), window, fit, viewport),
+        Action::ToggleFullscreen => EventOutcome {
+            toggle_fullscreen: true,
+            ..Default::default()
+        },
+        Action::ToggleMaximize => EventOutcome {
+            toggle_maximize: true,
+            ..Default::default()
+        },
+        // Only meaningful on a mouse button; see `on_pointer_button`.
+        Action::PanDrag => EventOutcome::default(),
+        Action::DeleteFile => on_delete_file(navigator, image, viewport, linear_light),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn on_key(
+    combo: KeyCombo,
+    repeat: bool,
+    settings: &AppSettings,
+    navigator: &mut Navigator,
+    image: &mut ImageHolder,
+    fit: &mut FitState,
+    viewport: &mut ViewportState,
+    input: &mut InputState,
+    window: (u32, u32),
+    scale: f32,
+) -> EventOutcome {
+    if let Some(action) = settings.keybind_map.lookup(combo) {
+        if repeat && !action.is_repeatable() {
+            return EventOutcome::default();
+        }
+        let count = input.count.take();
+        on_action(
+            action, count, settings, navigator, image, fit, viewport, window, scale,
+        )
+    } else if let Some(digit) = keysym_digit(combo.sym).filter(|_| combo.mods.is_empty() && !repeat)
+    {
+        // Unbound digits build the index prefix consumed by `Action::GoTo`.
+        let n = input
+            .count
+            .unwrap_or(0)
+            .saturating_mul(10)
+            .saturating_add(digit as usize);
+        input.count = Some(n);
+        EventOutcome::default()
+    } else {
+        // Any other key abandons the prefix; modifiers may precede a binding.
+        if !repeat && !keysym_is_modifier(combo.sym) {
+            input.count = None;
+        }
+        EventOutcome::default()
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn on_pointer_button(
+    button: u32,
+    pressed: bool,
+    mods: Modifiers,
+    double: bool,
+    settings: &AppSettings,
+    navigator: &mut Navigator,
+    image: &mut ImageHolder,
+    fit: &mut FitState,
+    viewport: &mut ViewportState,
+    input: &mut InputState,
+    window: (u32, u32),
+    scale: f32,
+) -> EventOutcome {
+    if !pressed {
+        if input.drag_button == Some(button) {
+            input.drag_button = None;
+        }
+        return EventOutcome::default();
+    }
+    let lookup = |input| settings.mousebind_map.lookup(MouseCombo { input, mods });
+    let action = double
+        .then(|| lookup(MouseInput::DoubleClick(button)))
+        .flatten()
+        .or_else(|| lookup(MouseInput::Button(button)));
+    match action {
+        Some(Action::PanDrag) => {
+            input.drag_button = Some(button);
+            EventOutcome::default()
+        }
+        Some(action) => on_action(
+            action, None, settings, navigator, image, fit, viewport, window, scale,
+        ),
+        None => EventOutcome::default(),
+    }
+}
+
+/// Touchpad travel, in surface pixels, that counts as one wheel notch when a
+/// modifier routes touchpad scrolling through the wheel bindings (e.g.
+/// `Ctrl` + scroll). Scaled by the output scale before use.
+const SMOOTH_NOTCH_PX: f32 = 15.0;
+
+/// Touchpad scrolling without modifiers pans the image. Wheels, and touchpad
+/// scrolling with a modifier held, go through the wheel bindings per axis.
+#[allow(clippy::too_many_arguments)]
+fn on_scroll(
+    delta: (f32, f32),
+    kind: ScrollKind,
+    cursor: (f32, f32),
+    mods: Modifiers,
+    settings: &AppSettings,
+    navigator: &mut Navigator,
+    image: &mut ImageHolder,
+    fit: &mut FitState,
+    viewport: &mut ViewportState,
+    input: &mut InputState,
+    window: (u32, u32),
+    scale: f32,
+) -> EventOutcome {
+    let (dx, dy) = match kind {
+        ScrollKind::Smooth if mods.is_empty() => {
+            return on_pointer_motion(-delta.0, -delta.1, fit, viewport);
+        }
+        ScrollKind::Smooth => {
+            let notch = SMOOTH_NOTCH_PX * scale;
+            (delta.0 / notch, delta.1 / notch)
+        }
+        ScrollKind::Wheel => delta,
+    };
+    let mut wheel = |notches, wheel, accumulated: &mut f32| {
+        on_wheel(
+            notches,
+            MouseCombo { input: wheel, mods },
+            accumulated,
+            cursor,
+            settings,
+            navigator,
+            image,
+            fit,
+            viewport,
+            window,
+            scale,
+        )
+    };
+    let (scroll_x, scroll_y) = &mut input.scroll;
+    let vertical = match dy {
+        dy if dy < 0.0 => wheel(dy, MouseInput::WheelUp, scroll_y),
+        dy if dy > 0.0 => wheel(dy, MouseInput::WheelDown, scroll_y),
+        _ => EventOutcome::default(),
+    };
+    let horizontal = match dx {
+        dx if dx < 0.0 => wheel(dx, MouseInput::WheelLeft, scroll_x),
+        dx if dx > 0.0 => wheel(dx, MouseInput::WheelRight, scroll_x),
+        _ => EventOutcome::default(),
+    };
+    vertical.merge(horizontal)
+}
+
+/// Wheel zoom is proportional to the scroll amount and anchored at the
+/// cursor; any other bound action fires once per accumulated wheel notch.
+/// `accumulated` carries partial notches for this axis between events.
+#[allow(clippy::too_many_arguments)]
+fn on_wheel(
+    notches: f32,
+    combo: MouseCombo,
+    accumulated: &mut f32,
+    cursor: (f32, f32),
+    settings: &AppSettings,
+    navigator: &mut Navigator,
+    image: &mut ImageHolder,
+    fit: &mut FitState,
+    viewport: &mut ViewportState,
+    window: (u32, u32),
+    scale: f32,
+) -> EventOutcome {
+    let Some(action) = settings.mousebind_map.lookup(combo) else {
+        return EventOutcome::default();
+    };
+    let step = notches.abs() * settings.scale_step;
+    match action {
+        Action::ZoomIn => on_zoom(step, cursor, window, settings, fit, viewport),
+        Action::ZoomOut => on_zoom(-step, cursor, window, settings, fit, viewport),
+        action => {
+            if accumulated.signum() != notches.signum() {
+                *accumulated = 0.0;
+            }
+            *accumulated += notches;
+            // A fast spin can report several notches in one frame.
+            let mut outcome = EventOutcome::default();
+            while accumulated.abs() >= 1.0 {
+                *accumulated -= accumulated.signum();
+                outcome = outcome.merge(on_action(
+                    action, None, settings, navigator, image, fit, viewport, window, scale,
+                ));
+            }
+            outcome
+        }
+    }
+}
+
+/// Minimum horizontal travel, in surface pixels, for a three-finger swipe
+/// to switch images. Scaled by the output scale before use.
+const SWIPE_MIN_PX: f32 = 100.0;
+
+/// Pinch zooms around the gesture centre; twisting the fingers rotates in
+/// 90° steps, snapping at 45° past the last step.
+#[allow(clippy::too_many_arguments)]
+fn on_pinch(
+    scale: f32,
+    rotation: f32,
+    centre: (f32, f32),
+    settings: &AppSettings,
+    fit: &mut FitState,
+    viewport: &mut ViewportState,
+    input: &mut InputState,
+    window: (u32, u32),
+) -> EventOutcome {
+    let mut outcome = EventOutcome::default();
+    if scale != 1.0 {
+        outcome = on_zoom(scale - 1.0, centre, window, settings, fit, viewport);
+    }
+    let turns = (rotation / 90.0).round() as i32;
+    while input.pinch_turns < turns {
+        input.pinch_turns += 1;
+        outcome = outcome.merge(on_rotate_right(fit, viewport));
+    }
+    while input.pinch_turns > turns {
+        input.pinch_turns -= 1;
+        outcome = outcome.merge(on_rotate_left(fit, viewport));
+    }
+    outcome
+}
+
+/// A horizontal three-finger swipe shows the next (leftwards) or previous
+/// (rightwards) image; other swipes are left to the compositor.
+#[allow(clippy::too_many_arguments)]
+fn on_swipe(
+    fingers: u32,
+    dx: f32,
+    dy: f32,
+    navigator: &mut Navigator,
+    image: &mut ImageHolder,
+    viewport: &mut ViewportState,
+    linear_light: bool,
+    scale: f32,
+) -> EventOutcome {
+    if fingers != 3 || dx.abs() < SWIPE_MIN_PX * scale || dx.abs() < dy.abs() {
+        return EventOutcome::default();
+    }
+    if dx < 0.0 {
+        on_navigate(navigator, image, viewport, linear_light, |n| Some(n.next()))
+    } else {
+        on_navigate(navigator, image, viewport, linear_light, |n| Some(n.prev()))
+    }
+}
+
+/// Fraction of the window width at each side where a tap navigates.
+const EDGE_TAP_FRACTION: f32 = 0.2;
+
+/// One finger pans and two fingers pinch-zoom. Taps near the left or right
+/// edge show the previous or next image, a double tap elsewhere toggles
+/// between the fit mode and 100 %, and a flick navigates like a touchpad
+/// swipe.
+fn on_touch(
+    gesture: TouchGesture,
+    settings: &AppSettings,
+    navigator: &mut Navigator,
+    image: &mut ImageHolder,
+    fit: &mut FitState,
+    viewport: &mut ViewportState,
+    window: (u32, u32),
+) -> EventOutcome {
+    let linear_light = settings.linear_light;
+    match gesture {
+        TouchGesture::Pan { dx, dy } => on_pointer_motion(dx, dy, fit, viewport),
+        TouchGesture::Pinch {
+            scale,
+            centre,
+            dx,
+            dy,
+        } => on_pointer_motion(dx, dy, fit, viewport).merge(on_zoom(
+            scale - 1.0,
+            centre,
+            window,
+            settings,
+            fit,
+            viewport,
+        )),
+        TouchGesture::Tap { pos, double } => {
+            let edge = window.0 as f32 * EDGE_TAP_FRACTION;
+            if pos.0 < edge {
+                on_navigate(navigator, image, viewport, linear_light, |n| Some(n.prev()))
+            } else if pos.0 > window.0 as f32 - edge {
+                on_navigate(navigator, image, viewport, linear_light, |n| Some(n.next()))
+            } else if double && fit.active {
+                on_zoom(
+                    1.0 / viewport.scale - 1.0,
+                    pos,
+                    window,
+                    settings,
+                    fit,
+                    viewport,
+                )
+            } else if double {
+                on_zoom_fit()
+            } else {
+                EventOutcome::default()
+            }
+        }
+        TouchGesture::Swipe { dx, .. } if dx < 0.0 => {
+            on_navigate(navigator, image, viewport, linear_light, |n| Some(n.next()))
+        }
+        TouchGesture::Swipe { .. } => {
+            on_navigate(navigator, image, viewport, linear_light, |n| Some(n.prev()))
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn process_event(
+    event: InputEvent,
+    settings: &AppSettings,
+    navigator: &mut Navigator,
+    image: &mut ImageHolder,
+    fit: &mut FitState,
+    viewport: &mut ViewportState,
+    input: &mut InputState,
+    window: (u32, u32),
+    scale: f32,
+) -> EventOutcome {
+    // The go-to prefix only survives digits and the key that consumes it;
+    // pointer, wheel and touch input abandon it.
+    if !matches!(
+        event,
+        InputEvent::Key { .. }
+            | InputEvent::PointerMotion { .. }
+            | InputEvent::PointerLeave
+            | InputEvent::PinchEnd
+    ) {
+        input.count = None;
+    }
+    match event {
+        InputEvent::Key { sym, mods, repeat } => on_key(
+            KeyCombo { sym, mods },
+            repeat,
+            settings,
+            navigator,
+            image,
+            fit,
+            viewport,
+            input,
+            window,
+            scale,
+        ),
+        InputEvent::Scroll {
+            delta,
+            kind,
+            cursor,
+            mods,
+        } => on_scroll(
+            delta, kind, cursor, mods, settings, navigator, image, fit, viewport, input, window,
+            scale,
+        ),
+        InputEvent::PointerLeave => {
+            input.drag_button = None;
+            EventOutcome::default()
+        }
+        InputEvent::PointerMotion { dx, dy } => match input.drag_button {
+            Some(_) => on_pointer_motion(dx, dy, fit, viewport),
+            None => EventOutcome::default(),
+        },
+        InputEvent::PointerButton {
+            button,
+            pressed,
+            mods,
+            double,
+        } => on_pointer_button(
+            button, pressed, mods, double, settings, navigator, image, fit, viewport, input,
+            window, scale,
+        ),
+        InputEvent::Pinch {
+            scale,
+            rotation,
+            centre,
+        } => on_pinch(
+            scale, rotation, centre, settings, fit, viewport, input, window,
+        ),
+        InputEvent::PinchEnd => {
+            input.pinch_turns = 0;
+            EventOutcome::default()
+        }
+        InputEvent::Swipe { fingers, dx, dy } => on_swipe(
+            fingers,
+            dx,
+            dy,
+            navigator,
+            image,
+            viewport,
+            settings.linear_light,
+            scale,
+        ),
+        InputEvent::Touch(gesture) => {
+            on_touch(gesture, settings, navigator, image, fit, viewport, window)
+        }
+    }
+}
+
+pub fn run(settings: AppSettings) -> Result<(), Box<dyn std::error::Error>> {
+    if settings.paths.is_empty() {
+        return Err("no image paths given".into());
+    }
+
+    info!(
+        decorations = settings.decorations,
+        fullscreen = settings.fullscreen,
+        antialiasing = settings.antialiasing,
+        linear_light = settings.linear_light,
+        upscale_filter = ?settings.upscale_filter,
+        downscale_filter = ?settings.downscale_filter,
+        fit_mode = ?settings.fit_mode,
+        "imgvwr starting"
+    );
+
+    #[cfg(all(
+        any(feature = "gpu-vulkan", feature = "gpu-gles"),
+        not(feature = "dmabuf")
+    ))]
+    let gpu_ctx = GpuContext::new()?.with_linear_light(settings.linear_light);
+
+    let mut navigator = Navigator::from_path(&settings.paths[0])?;
+    let mut image = load_image(navigator.current(), settings.linear_light)?;
+    info!(path = %navigator.current().display(), "loaded first image");
+
+    let mut viewport = ViewportState::default();
+    let mut wayland =
+        WaylandContext::connect((800, 600), settings.decorations, settings.fullscreen)?;
+
+    // dmabuf: init GPU context after surface is created, using Wayland handles.
+    #[cfg(feature = "dmabuf")]
+    let mut gpu_ctx = {
+        let (w, h) = wayland.state.buffer_size();
+        GpuContext::new_with_surface(wayland.display_ptr(), wayland.surface_ptr(), w, h)?
+            .with_linear_light(settings.linear_light)
+    };
+    #[cfg(feature = "dmabuf")]
+    let mut last_surface_size = wayland.state.buffer_size();
+
+    let mut fit = FitState {
+        mode: settings.fit_mode,
+        active: true,
+    };
+    apply_fit(
+        &mut fit,
+        image.current(),
+        wayland.state.buffer_size(),
+        &settings,
+        &mut viewport,
+    );
+    let mut last_window_size = wayland.state.buffer_size();
+    let mut input = InputState::default();
+    #[cfg(all(
+        not(feature = "dmabuf"),
+        any(feature = "gpu-vulkan", feature = "gpu-gles")
+    ))]
+    let mut frame = FrameBuffer::default();
+    #[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+    let mut frame = FrameBuffer::default()
+        .with_threads(settings.render_threads)
+        .with_linear_light(settings.linear_light);
+
+    #[cfg(feature = "decorations")]
+    if settings.decorations {
+        wayland.set_title(&make_title(navigator.current()));
+    }
+
+    // Frames drawn while panning or zooming use a fast filter until input has
+    // been idle until `refine_at`.
+    let mut refine_at: Option<Instant> = None;
+    let mut drawn_filter = settings.filter_for(viewport.scale, false);
+
+    loop {
+        wayland.dispatch(poll_timeout_ms(&image, refine_at))?;
+
+        let events: Vec<InputEvent> = wayland.state.pending_events.drain(..).collect();
+        let mut dirty = wayland.state.needs_redraw;
+        wayland.state.needs_redraw = false;
+        let mut any_navigated = false;
+        let mut refit = false;
+        let mut interacted = false;
+
+        dirty |= image.tick();
+        // A finished pyramid only changes the frame if the scale uses it.
+        if image.take_mips_ready() {
+            dirty |= image.source().mip_level(&viewport, drawn_filter) > 0;
+        }
+
+        for event in events {
+            let outcome = process_event(
+                event,
+                &settings,
+                &mut navigator,
+                &mut image,
+                &mut fit,
+                &mut viewport,
+                &mut input,
+                wayland.state.buffer_size(),
+                wayland.state.scale_factor() as f32,
+            );
+            dirty |= outcome.dirty;
+            interacted |= outcome.interactive;
+            any_navigated |= outcome.navigated;
+            refit |= outcome.refit;
+            if outcome.quit {
+                wayland.state.closed = true;
+            }
+            let toplevel = wayland.state.toplevel_state;
+            if outcome.toggle_fullscreen {
+                wayland.set_fullscreen(!toplevel.fullscreen);
+            }
+            if outcome.toggle_maximize {
+                wayland.set_maximized(!toplevel.maximized);
+            }
+        }
+
+        if wayland.state.buffer_size() != last_window_size {
+            last_window_size = wayland.state.buffer_size();
+            refit |= fit.active;
+        }
+
+        if any_navigated || refit {
+            apply_fit(
+                &mut fit,
+                image.current(),
+                wayland.state.buffer_size(),
+                &settings,
+                &mut viewport,
+            );
+            dirty = true;
+        }
+
+        #[cfg(feature = "decorations")]
+        if any_navigated && settings.decorations {
+            wayland.set_title(&make_title(navigator.current()));
+        }
+
+        // A new image is drawn at full quality straight away.
+        if any_navigated || settings.refine_delay.is_zero() {
+            refine_at = None;
+        } else if interacted {
+            refine_at = Some(Instant::now() + settings.refine_delay);
+        } else if refine_at.is_some_and(|at| Instant::now() >= at) {
+            refine_at = None;
+        }
+        let effective_filter = settings.filter_for(viewport.scale, refine_at.is_some());
+        // The interactive filter exists to avoid slow resamples; keep the
+        // drawn filter while its resampled pixels can still be reused.
+        #[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+        let effective_filter =
+            if refine_at.is_some() && frame.reuses(image.source(), &viewport, drawn_filter) {
+                drawn_filter
+            } else {
+                effective_filter
+            };
+        dirty |= effective_filter != drawn_filter;
+
+        if dirty {
+            let (w, h) = wayland.state.buffer_size();
+            drawn_filter = effective_filter;
+
+            #[cfg(feature = "dmabuf")]
+            {
+                if (w, h) != last_surface_size {
+                    gpu_ctx.configure_surface(w, h);
+                    last_surface_size = (w, h);
+                }
+                wayland.apply_buffer_scale();
+                gpu_ctx.render_and_present(image.source(), &viewport, w, h, effective_filter)?;
+            }
+
+            #[cfg(not(feature = "dmabuf"))]
+            {
+                let damage = frame.render(
+                    image.source(),
+                    &viewport,
+                    w,
+                    h,
+                    effective_filter,
+                    #[cfg(any(feature = "gpu-vulkan", feature = "gpu-gles"))]
+                    &gpu_ctx,
+                );
+                if !wayland.commit_frame(frame.pixels(), w, h, &damage)? {
+                    frame.invalidate();
+                }
+            }
+        }
+
+        if wayland.state.closed {
+            break;
+        }
+    }
+
+    Ok(())
+}
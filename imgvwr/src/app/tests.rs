@@ -0,0 +1,123 @@
+use clap::Parser;
+use image::DynamicImage;
+use libimgvwr::{
+    keybinds::{
+        Modifiers,
+        mouse::{BTN_LEFT, MouseCombo, MouseInput},
+    },
+    navigator::Navigator,
+    viewport::{FitMode, ViewportState},
+    wayland::InputEvent,
+};
+
+use super::*;
+use crate::{cli::Cli, config::Config};
+
+fn resolve_default() -> AppSettings {
+    AppSettings::resolve(&Cli::parse_from(["imgvwr"]), &Config::default())
+}
+
+/// A navigator over `n` paths that do not exist; stepping onto one moves
+/// the cursor even though the image fails to load.
+fn navigator(n: usize) -> Navigator {
+    Navigator {
+        paths: (0..n)
+            .map(|i| PathBuf::from(format!("missing-{i}.png")))
+            .collect(),
+        current: 0,
+    }
+}
+
+fn blank_image() -> ImageHolder {
+    ImageHolder::from_image(DynamicImage::new_rgba8(1, 1), false)
+}
+
+fn fit() -> FitState {
+    FitState {
+        mode: FitMode::Fit,
+        active: true,
+    }
+}
+
+#[test]
+fn wheel_frame_with_two_notches_navigates_twice() {
+    let settings = resolve_default();
+    let mut navigator = navigator(4);
+    let mut image = blank_image();
+    let mut fit = fit();
+    let mut viewport = ViewportState::default();
+    let mut accumulated = 0.0;
+    on_wheel(
+        2.0,
+        MouseCombo {
+            input: MouseInput::WheelDown,
+            mods: Modifiers::NONE,
+        },
+        &mut accumulated,
+        (0.0, 0.0),
+        &settings,
+        &mut navigator,
+        &mut image,
+        &mut fit,
+        &mut viewport,
+        (100, 100),
+        1.0,
+    );
+    assert_eq!(navigator.current, 2);
+    assert_eq!(accumulated, 0.0);
+}
+
+#[test]
+fn pointer_leave_ends_a_drag() {
+    let settings = resolve_default();
+    let mut navigator = navigator(1);
+    let mut image = blank_image();
+    let mut fit = fit();
+    let mut viewport = ViewportState::default();
+    let mut input = InputState::default();
+    let mut process = |event, viewport: &mut ViewportState| {
+        process_event(
+            event,
+            &settings,
+            &mut navigator,
+            &mut image,
+            &mut fit,
+            viewport,
+            &mut input,
+            (100, 100),
+            1.0,
+        )
+    };
+    let press = InputEvent::PointerButton {
+        button: BTN_LEFT,
+        pressed: true,
+        mods: Modifiers::NONE,
+        double: false,
+    };
+    let motion = || InputEvent::PointerMotion { dx: 5.0, dy: 0.0 };
+    process(press, &mut viewport);
+    assert!(process(motion(), &mut viewport).dirty);
+    // The release never arrives: a compositor grab took the pointer.
+    process(InputEvent::PointerLeave, &mut viewport);
+    let offset = viewport.offset;
+    assert!(!process(motion(), &mut viewport).dirty);
+    assert_eq!(viewport.offset, offset);
+}
+
+#[test]
+fn keyboard_pan_step_is_in_logical_pixels() {
+    let settings = resolve_default();
+    let mut viewport = ViewportState::default();
+    on_action(
+        Action::PanLeft,
+        None,
+        &settings,
+        &mut navigator(1),
+        &mut blank_image(),
+        &mut fit(),
+        &mut viewport,
+        (100, 100),
+        2.0,
+    );
+    assert_eq!(viewport.offset, (2.0 * settings.pan_step, 0.0));
+}
@@ -5,7 +5,7 @@ use std::path::PathBuf;
 
 use clap::Parser;
 
-use crate::config::FilterMethod;
+use crate::config::{FilterMethod, FitMode};
 
 #[derive(Parser, Debug)]
 #[command(about = "Minimal Wayland image viewer")]
@@ -29,6 +29,9 @@ pub struct Cli {
     #[arg(short = 'a', long, num_args = 0..=1, default_missing_value = "true")]
     pub antialiasing: Option<bool>,
 
+    #[arg(short = 'f', long, num_args = 0..=1, default_missing_value = "true")]
+    pub fullscreen: Option<bool>,
+
     #[arg(long)]
     pub min_scale: Option<f32>,
 
@@ -38,9 +41,39 @@ pub struct Cli {
     #[arg(long)]
     pub scale_step: Option<f32>,
 
+    #[arg(long)]
+    pub pan_step: Option<f32>,
+
+    #[arg(long)]
+    pub skip_step: Option<usize>,
+
     #[arg(long)]
     pub filter_method: Option<FilterMethod>,
 
+    #[arg(long, value_name = "METHOD")]
+    pub upscale_filter: Option<FilterMethod>,
+
+    #[arg(long, value_name = "METHOD")]
+    pub downscale_filter: Option<FilterMethod>,
+
+    #[arg(long, value_name = "FACTOR")]
+    pub nearest_threshold: Option<f32>,
+
+    #[arg(long, value_name = "METHOD")]
+    pub interactive_filter: Option<FilterMethod>,
+
+    #[arg(long, value_name = "MS")]
+    pub refine_delay_ms: Option<u64>,
+
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    pub linear_light: Option<bool>,
+
+    #[arg(long)]
+    pub fit_mode: Option<FitMode>,
+
+    #[arg(long, value_name = "N")]
+    pub render_threads: Option<usize>,
+
     #[arg(long)]
     pub log_level: Option<String>,
 }
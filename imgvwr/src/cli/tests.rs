@@ -32,3 +32,71 @@ fn paths_and_config_together() {
     assert_eq!(cli.config.unwrap(), std::path::PathBuf::from("my.toml"));
     assert_eq!(cli.paths, vec![std::path::PathBuf::from("img.png")]);
 }
+
+#[test]
+fn fit_mode_flag_is_parsed() {
+    let cli = Cli::parse_from(["imgvwr", "--fit-mode", "shrink-to-fit"]);
+    assert_eq!(cli.fit_mode, Some(FitMode::ShrinkToFit));
+}
+
+#[test]
+fn filter_method_flag_is_parsed() {
+    for (arg, expected) in [
+        ("sharp-bilinear", FilterMethod::SharpBilinear),
+        ("mitchell", FilterMethod::Mitchell),
+        ("lanczos4", FilterMethod::Lanczos4),
+    ] {
+        let cli = Cli::parse_from(["imgvwr", "--filter-method", arg]);
+        assert_eq!(cli.filter_method, Some(expected));
+    }
+}
+
+#[test]
+fn zoom_filter_flags_are_parsed() {
+    let cli = Cli::parse_from([
+        "imgvwr",
+        "--upscale-filter",
+        "lanczos3",
+        "--downscale-filter",
+        "box",
+        "--nearest-threshold",
+        "8",
+        "--interactive-filter",
+        "nearest",
+        "--refine-delay-ms",
+        "300",
+    ]);
+    assert_eq!(cli.upscale_filter, Some(FilterMethod::Lanczos3));
+    assert_eq!(cli.downscale_filter, Some(FilterMethod::Box));
+    assert_eq!(cli.nearest_threshold, Some(8.0));
+    assert_eq!(cli.interactive_filter, Some(FilterMethod::Nearest));
+    assert_eq!(cli.refine_delay_ms, Some(300));
+}
+
+#[test]
+fn pan_step_flag_is_parsed() {
+    let cli = Cli::parse_from(["imgvwr", "--pan-step", "25"]);
+    assert_eq!(cli.pan_step, Some(25.0));
+}
+
+#[test]
+fn render_threads_flag_is_parsed() {
+    let cli = Cli::parse_from(["imgvwr", "--render-threads", "4"]);
+    assert_eq!(cli.render_threads, Some(4));
+}
+
+#[test]
+fn fullscreen_flag_is_parsed() {
+    let cli = Cli::parse_from(["imgvwr", "--fullscreen"]);
+    assert_eq!(cli.fullscreen, Some(true));
+    let cli = Cli::parse_from(["imgvwr", "-f", "false"]);
+    assert_eq!(cli.fullscreen, Some(false));
+}
+
+#[test]
+fn linear_light_flag_is_parsed() {
+    let cli = Cli::parse_from(["imgvwr", "--linear-light"]);
+    assert_eq!(cli.linear_light, Some(true));
+    let cli = Cli::parse_from(["imgvwr", "--linear-light", "false"]);
+    assert_eq!(cli.linear_light, Some(false));
+}
@@ -1,6 +1,8 @@
 #[cfg(all(test, feature = "config"))]
 mod tests;
 
+use std::collections::BTreeMap;
+
 #[cfg(feature = "config")]
 use std::{env, error::Error, fs::File, io::Read, path::PathBuf};
 
@@ -15,6 +17,8 @@ pub struct Config {
     pub window: Option<Window>,
     pub viewer: Option<Viewer>,
     pub keybindings: Option<Keybindings>,
+    pub binds: Option<Vec<Bind>>,
+    pub mousebindings: Option<Mousebindings>,
     #[cfg_attr(not(feature = "logging"), allow(dead_code))]
     pub logging: Option<Logging>,
 }
@@ -25,6 +29,8 @@ impl Default for Config {
             window: Some(Window::default()),
             viewer: Some(Viewer::default()),
             keybindings: Some(Keybindings::default()),
+            binds: None,
+            mousebindings: Some(Mousebindings::default()),
             logging: None,
         }
     }
@@ -97,10 +103,21 @@ impl Config {
         debug!(
             decorations = w.and_then(|w| w.decorations).unwrap_or(false),
             antialiasing = w.and_then(|w| w.antialiasing).unwrap_or(true),
+            fullscreen = w.and_then(|w| w.fullscreen).unwrap_or(false),
             filter = ?v.and_then(|v| v.filter_method.as_ref()),
+            upscale_filter = ?v.and_then(|v| v.upscale_filter.as_ref()),
+            downscale_filter = ?v.and_then(|v| v.downscale_filter.as_ref()),
+            nearest_threshold = v.and_then(|v| v.nearest_threshold).unwrap_or(4.0),
+            interactive_filter = ?v.and_then(|v| v.interactive_filter.as_ref()),
+            refine_delay_ms = v.and_then(|v| v.refine_delay_ms).unwrap_or(150),
+            linear_light = v.and_then(|v| v.linear_light).unwrap_or(false),
             min_scale = v.and_then(|v| v.min_scale).unwrap_or(0.1),
             max_scale = v.and_then(|v| v.max_scale).unwrap_or(100.0),
             scale_step = v.and_then(|v| v.scale_step).unwrap_or(0.08),
+            pan_step = v.and_then(|v| v.pan_step).unwrap_or(50.0),
+            skip_step = v.and_then(|v| v.skip_step).unwrap_or(10),
+            fit_mode = ?v.and_then(|v| v.fit_mode.as_ref()),
+            render_threads = v.and_then(|v| v.render_threads).unwrap_or(0),
             "effective config"
         );
 
@@ -130,19 +147,71 @@ impl Config {
             window: merge_section(base.window, overlay.window, |b, o| Window {
                 decorations: o.decorations.or(b.decorations),
                 antialiasing: o.antialiasing.or(b.antialiasing),
+                fullscreen: o.fullscreen.or(b.fullscreen),
             }),
             viewer: merge_section(base.viewer, overlay.viewer, |b, o| Viewer {
                 min_scale: o.min_scale.or(b.min_scale),
                 max_scale: o.max_scale.or(b.max_scale),
                 scale_step: o.scale_step.or(b.scale_step),
+                pan_step: o.pan_step.or(b.pan_step),
+                skip_step: o.skip_step.or(b.skip_step),
                 filter_method: o.filter_method.or(b.filter_method),
+                upscale_filter: o.upscale_filter.or(b.upscale_filter),
+                downscale_filter: o.downscale_filter.or(b.downscale_filter),
+                nearest_threshold: o.nearest_threshold.or(b.nearest_threshold),
+                interactive_filter: o.interactive_filter.or(b.interactive_filter),
+                refine_delay_ms: o.refine_delay_ms.or(b.refine_delay_ms),
+                linear_light: o.linear_light.or(b.linear_light),
+                fit_mode: o.fit_mode.or(b.fit_mode),
+                render_threads: o.render_threads.or(b.render_threads),
             }),
             keybindings: merge_section(base.keybindings, overlay.keybindings, |b, o| Keybindings {
                 quit: o.quit.or(b.quit),
+                prev: o.prev.or(b.prev),
+                next: o.next.or(b.next),
+                first: o.first.or(b.first),
+                last: o.last.or(b.last),
+                skip_forward: o.skip_forward.or(b.skip_forward),
+                skip_backward: o.skip_backward.or(b.skip_backward),
+                random: o.random.or(b.random),
+                go_to: o.go_to.or(b.go_to),
                 rotate_left: o.rotate_left.or(b.rotate_left),
                 rotate_right: o.rotate_right.or(b.rotate_right),
+                flip_horizontal: o.flip_horizontal.or(b.flip_horizontal),
+                flip_vertical: o.flip_vertical.or(b.flip_vertical),
+                cycle_fit_mode: o.cycle_fit_mode.or(b.cycle_fit_mode),
+                fit: o.fit.or(b.fit),
+                shrink_to_fit: o.shrink_to_fit.or(b.shrink_to_fit),
+                fill: o.fill.or(b.fill),
+                fit_width: o.fit_width.or(b.fit_width),
+                fit_height: o.fit_height.or(b.fit_height),
+                actual_size: o.actual_size.or(b.actual_size),
+                zoom_in: o.zoom_in.or(b.zoom_in),
+                zoom_out: o.zoom_out.or(b.zoom_out),
+                zoom_actual: o.zoom_actual.or(b.zoom_actual),
+                zoom_fit: o.zoom_fit.or(b.zoom_fit),
+                pan_left: o.pan_left.or(b.pan_left),
+                pan_right: o.pan_right.or(b.pan_right),
+                pan_up: o.pan_up.or(b.pan_up),
+                pan_down: o.pan_down.or(b.pan_down),
+                jump_left: o.jump_left.or(b.jump_left),
+                jump_right: o.jump_right.or(b.jump_right),
+                jump_top: o.jump_top.or(b.jump_top),
+                jump_bottom: o.jump_bottom.or(b.jump_bottom),
+                toggle_fullscreen: o.toggle_fullscreen.or(b.toggle_fullscreen),
+                toggle_maximize: o.toggle_maximize.or(b.toggle_maximize),
                 delete: o.delete.or(b.delete),
             }),
+            // Later files add to the bind list rather than replacing it.
+            binds: merge_section(base.binds, overlay.binds, |mut b, o| {
+                b.extend(o);
+                b
+            }),
+            mousebindings: merge_section(base.mousebindings, overlay.mousebindings, |b, o| {
+                let mut actions = b.0;
+                actions.extend(o.0);
+                Mousebindings(actions)
+            }),
             logging: merge_section(base.logging, overlay.logging, |b, o| Logging {
                 level: o.level.or(b.level),
             }),
@@ -165,6 +234,7 @@ fn merge_section<T, F: FnOnce(T, T) -> T>(base: Option<T>, overlay: Option<T>, f
 pub struct Window {
     pub decorations: Option<bool>,
     pub antialiasing: Option<bool>,
+    pub fullscreen: Option<bool>,
 }
 
 impl Default for Window {
@@ -172,6 +242,7 @@ impl Default for Window {
         Window {
             decorations: Some(false),
             antialiasing: Some(false),
+            fullscreen: Some(false),
         }
     }
 }
@@ -182,7 +253,26 @@ pub struct Viewer {
     pub min_scale: Option<f32>,
     pub max_scale: Option<f32>,
     pub scale_step: Option<f32>,
+    pub pan_step: Option<f32>,
+    pub skip_step: Option<usize>,
     pub filter_method: Option<FilterMethod>,
+    /// Filter for magnified images; `filter_method` when unset.
+    pub upscale_filter: Option<FilterMethod>,
+    /// Filter for shrunk images; `filter_method` when unset.
+    pub downscale_filter: Option<FilterMethod>,
+    /// Zoom factor from which images are drawn with nearest neighbour; `0`
+    /// never switches.
+    pub nearest_threshold: Option<f32>,
+    /// Filter used while panning and zooming.
+    pub interactive_filter: Option<FilterMethod>,
+    /// Idle time before a frame drawn with `interactive_filter` is redrawn
+    /// with the full-quality filter; `0` never uses `interactive_filter`.
+    pub refine_delay_ms: Option<u64>,
+    /// Resample in linear light instead of on sRGB-encoded values.
+    pub linear_light: Option<bool>,
+    pub fit_mode: Option<FitMode>,
+    /// Worker threads for software rendering; `0` uses one per CPU.
+    pub render_threads: Option<usize>,
 }
 
 impl Default for Viewer {
@@ -191,7 +281,17 @@ impl Default for Viewer {
             min_scale: Some(0.1),
             max_scale: Some(100.0),
             scale_step: Some(0.08),
+            pan_step: Some(50.0),
+            skip_step: Some(10),
             filter_method: Some(FilterMethod::Nearest),
+            upscale_filter: None,
+            downscale_filter: None,
+            nearest_threshold: Some(4.0),
+            interactive_filter: Some(FilterMethod::Triangle),
+            refine_delay_ms: Some(150),
+            linear_light: Some(false),
+            fit_mode: Some(FitMode::Fit),
+            render_threads: Some(0),
         }
     }
 }
@@ -203,33 +303,227 @@ impl Default for Viewer {
 pub enum FilterMethod {
     #[default]
     Nearest,
+    Box,
     Triangle,
+    SharpBilinear,
+    Hermite,
     CatmullRom,
+    Mitchell,
     Gaussian,
+    Lanczos2,
     Lanczos3,
+    Lanczos4,
+}
+
+#[derive(Default, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "config", serde(rename_all = "snake_case"))]
+pub enum FitMode {
+    #[default]
+    Fit,
+    ShrinkToFit,
+    Fill,
+    FitWidth,
+    FitHeight,
+    ActualSize,
 }
 
+/// Key bindings per action: a single key spec or a list of them (see
+/// `KeyCombo::parse` for the syntax). `None` or an empty list leaves the action
+/// unbound. Field names double as action names for [`Bind::action`].
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "config", derive(serde::Deserialize, serde::Serialize))]
 #[cfg_attr(not(feature = "keybinds"), allow(dead_code))]
 pub struct Keybindings {
-    pub quit: Option<String>,
-    pub rotate_left: Option<String>,
-    pub rotate_right: Option<String>,
-    pub delete: Option<String>,
+    pub quit: Option<KeySpec>,
+    pub prev: Option<KeySpec>,
+    pub next: Option<KeySpec>,
+    pub first: Option<KeySpec>,
+    pub last: Option<KeySpec>,
+    pub skip_forward: Option<KeySpec>,
+    pub skip_backward: Option<KeySpec>,
+    pub random: Option<KeySpec>,
+    pub go_to: Option<KeySpec>,
+    pub rotate_left: Option<KeySpec>,
+    pub rotate_right: Option<KeySpec>,
+    pub flip_horizontal: Option<KeySpec>,
+    pub flip_vertical: Option<KeySpec>,
+    pub cycle_fit_mode: Option<KeySpec>,
+    pub fit: Option<KeySpec>,
+    pub shrink_to_fit: Option<KeySpec>,
+    pub fill: Option<KeySpec>,
+    pub fit_width: Option<KeySpec>,
+    pub fit_height: Option<KeySpec>,
+    pub actual_size: Option<KeySpec>,
+    pub zoom_in: Option<KeySpec>,
+    pub zoom_out: Option<KeySpec>,
+    pub zoom_actual: Option<KeySpec>,
+    pub zoom_fit: Option<KeySpec>,
+    pub pan_left: Option<KeySpec>,
+    pub pan_right: Option<KeySpec>,
+    pub pan_up: Option<KeySpec>,
+    pub pan_down: Option<KeySpec>,
+    pub jump_left: Option<KeySpec>,
+    pub jump_right: Option<KeySpec>,
+    pub jump_top: Option<KeySpec>,
+    pub jump_bottom: Option<KeySpec>,
+    pub toggle_fullscreen: Option<KeySpec>,
+    pub toggle_maximize: Option<KeySpec>,
+    pub delete: Option<KeySpec>,
+}
+
+impl Keybindings {
+    /// Every `(action name, keys)` pair, in declaration order.
+    pub fn entries(&self) -> [(&'static str, Option<&KeySpec>); 35] {
+        [
+            ("quit", self.quit.as_ref()),
+            ("prev", self.prev.as_ref()),
+            ("next", self.next.as_ref()),
+            ("first", self.first.as_ref()),
+            ("last", self.last.as_ref()),
+            ("skip_forward", self.skip_forward.as_ref()),
+            ("skip_backward", self.skip_backward.as_ref()),
+            ("random", self.random.as_ref()),
+            ("go_to", self.go_to.as_ref()),
+            ("rotate_left", self.rotate_left.as_ref()),
+            ("rotate_right", self.rotate_right.as_ref()),
+            ("flip_horizontal", self.flip_horizontal.as_ref()),
+            ("flip_vertical", self.flip_vertical.as_ref()),
+            ("cycle_fit_mode", self.cycle_fit_mode.as_ref()),
+            ("fit", self.fit.as_ref()),
+            ("shrink_to_fit", self.shrink_to_fit.as_ref()),
+            ("fill", self.fill.as_ref()),
+            ("fit_width", self.fit_width.as_ref()),
+            ("fit_height", self.fit_height.as_ref()),
+            ("actual_size", self.actual_size.as_ref()),
+            ("zoom_in", self.zoom_in.as_ref()),
+            ("zoom_out", self.zoom_out.as_ref()),
+            ("zoom_actual", self.zoom_actual.as_ref()),
+            ("zoom_fit", self.zoom_fit.as_ref()),
+            ("pan_left", self.pan_left.as_ref()),
+            ("pan_right", self.pan_right.as_ref()),
+            ("pan_up", self.pan_up.as_ref()),
+            ("pan_down", self.pan_down.as_ref()),
+            ("jump_left", self.jump_left.as_ref()),
+            ("jump_right", self.jump_right.as_ref()),
+            ("jump_top", self.jump_top.as_ref()),
+            ("jump_bottom", self.jump_bottom.as_ref()),
+            ("toggle_fullscreen", self.toggle_fullscreen.as_ref()),
+            ("toggle_maximize", self.toggle_maximize.as_ref()),
+            ("delete", self.delete.as_ref()),
+        ]
+    }
 }
 
 impl Default for Keybindings {
     fn default() -> Self {
         Keybindings {
-            quit: Some("q".to_string()),
-            rotate_left: Some("[".to_string()),
-            rotate_right: Some("]".to_string()),
-            delete: Some("Delete".to_string()),
+            quit: Some("q".into()),
+            prev: Some("Left".into()),
+            next: Some("Right".into()),
+            first: Some("Home".into()),
+            last: Some("End".into()),
+            skip_forward: Some("Page_Down".into()),
+            skip_backward: Some("Page_Up".into()),
+            random: Some("r".into()),
+            go_to: Some("g".into()),
+            rotate_left: Some("[".into()),
+            rotate_right: Some("]".into()),
+            flip_horizontal: Some("bar".into()),
+            flip_vertical: Some("underscore".into()),
+            cycle_fit_mode: Some("m".into()),
+            fit: None,
+            shrink_to_fit: None,
+            fill: None,
+            fit_width: None,
+            fit_height: None,
+            actual_size: None,
+            zoom_in: Some("plus".into()),
+            zoom_out: Some("minus".into()),
+            zoom_actual: Some("equal".into()),
+            zoom_fit: Some("w".into()),
+            pan_left: Some("h".into()),
+            pan_right: Some("l".into()),
+            pan_up: Some("k".into()),
+            pan_down: Some("j".into()),
+            jump_left: Some("H".into()),
+            jump_right: Some("L".into()),
+            jump_top: Some("K".into()),
+            jump_bottom: Some("J".into()),
+            toggle_fullscreen: Some("f".into()),
+            toggle_maximize: Some("F".into()),
+            delete: Some("Delete".into()),
         }
     }
 }
 
+/// One key spec (`"q"`) or several (`["Right", "l", "space"]`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "config", serde(untagged))]
+#[cfg_attr(not(feature = "keybinds"), allow(dead_code))]
+pub enum KeySpec {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeySpec {
+    pub fn keys(&self) -> &[String] {
+        match self {
+            KeySpec::One(key) => std::slice::from_ref(key),
+            KeySpec::Many(keys) => keys,
+        }
+    }
+}
+
+impl From<&str> for KeySpec {
+    fn from(key: &str) -> Self {
+        KeySpec::One(key.to_string())
+    }
+}
+
+/// Mouse bindings keyed by action name (as in [`Keybindings`]), each a
+/// single mouse spec or a list (see `MouseCombo::parse` for the syntax).
+/// Overlay files replace an action's whole entry; an empty list unbinds it.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "config", serde(transparent))]
+#[cfg_attr(not(feature = "keybinds"), allow(dead_code))]
+pub struct Mousebindings(pub BTreeMap<String, KeySpec>);
+
+impl Default for Mousebindings {
+    fn default() -> Self {
+        let spec = |keys: &[&str]| match keys {
+            [key] => KeySpec::from(*key),
+            keys => KeySpec::Many(keys.iter().map(|k| k.to_string()).collect()),
+        };
+        Mousebindings(BTreeMap::from([
+            ("pan_drag".to_string(), spec(&["left"])),
+            ("zoom_fit".to_string(), spec(&["middle"])),
+            ("toggle_fullscreen".to_string(), spec(&["double_left"])),
+            (
+                "next".to_string(),
+                spec(&["right", "forward", "wheel_down", "wheel_right"]),
+            ),
+            (
+                "prev".to_string(),
+                spec(&["back", "wheel_up", "wheel_left"]),
+            ),
+            ("zoom_in".to_string(), spec(&["Ctrl+wheel_up"])),
+            ("zoom_out".to_string(), spec(&["Ctrl+wheel_down"])),
+        ]))
+    }
+}
+
+/// A `[[binds]]` entry mapping any key spec to any action by name.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(not(feature = "keybinds"), allow(dead_code))]
+pub struct Bind {
+    pub key: String,
+    pub action: String,
+}
+
 /// Logging configuration. The `level` field accepts the same values as the
 /// `RUST_LOG` environment variable (`"error"`, `"warn"`, `"info"`, `"debug"`,
 /// `"trace"`). `RUST_LOG` always overrides this field when set.
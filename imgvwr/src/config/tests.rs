@@ -6,15 +6,33 @@ fn default_has_all_fields_set() {
     let w = cfg.window.unwrap();
     assert_eq!(w.decorations, Some(false));
     assert_eq!(w.antialiasing, Some(false));
+    assert_eq!(w.fullscreen, Some(false));
     let v = cfg.viewer.unwrap();
     assert!((v.min_scale.unwrap() - 0.1).abs() < f32::EPSILON);
     assert!((v.max_scale.unwrap() - 100.0).abs() < f32::EPSILON);
     assert!((v.scale_step.unwrap() - 0.08).abs() < f32::EPSILON);
+    assert!((v.pan_step.unwrap() - 50.0).abs() < f32::EPSILON);
     assert_eq!(v.filter_method.unwrap(), FilterMethod::Nearest);
+    // Unset so that they follow `filter_method`.
+    assert!(v.upscale_filter.is_none());
+    assert!(v.downscale_filter.is_none());
+    assert_eq!(v.nearest_threshold, Some(4.0));
+    assert_eq!(v.interactive_filter, Some(FilterMethod::Triangle));
+    assert_eq!(v.refine_delay_ms, Some(150));
+    assert_eq!(v.linear_light, Some(false));
+    assert_eq!(v.fit_mode.unwrap(), FitMode::Fit);
+    assert_eq!(v.render_threads, Some(0));
     let k = cfg.keybindings.unwrap();
-    assert_eq!(k.quit.unwrap(), "q");
-    assert_eq!(k.rotate_left.unwrap(), "[");
-    assert_eq!(k.rotate_right.unwrap(), "]");
+    assert_eq!(k.quit.unwrap(), KeySpec::from("q"));
+    assert_eq!(k.rotate_left.unwrap(), KeySpec::from("["));
+    assert_eq!(k.rotate_right.unwrap(), KeySpec::from("]"));
+    assert_eq!(k.flip_horizontal.unwrap(), KeySpec::from("bar"));
+    assert_eq!(k.flip_vertical.unwrap(), KeySpec::from("underscore"));
+    assert_eq!(k.cycle_fit_mode.unwrap(), KeySpec::from("m"));
+    assert!(k.fit_width.is_none());
+    assert_eq!(k.zoom_in.unwrap(), KeySpec::from("plus"));
+    assert_eq!(k.pan_left.unwrap(), KeySpec::from("h"));
+    assert_eq!(k.jump_bottom.unwrap(), KeySpec::from("J"));
     assert!(cfg.logging.is_none());
 }
 
@@ -24,6 +42,8 @@ fn empty_toml_gives_all_none() {
     assert!(cfg.window.is_none());
     assert!(cfg.viewer.is_none());
     assert!(cfg.keybindings.is_none());
+    assert!(cfg.binds.is_none());
+    assert!(cfg.mousebindings.is_none());
     assert!(cfg.logging.is_none());
 }
 
@@ -47,10 +67,16 @@ fn unknown_keys_are_ignored() {
 fn filter_method_snake_case_variants() {
     let cases = [
         ("nearest", FilterMethod::Nearest),
+        ("box", FilterMethod::Box),
         ("triangle", FilterMethod::Triangle),
+        ("sharp_bilinear", FilterMethod::SharpBilinear),
+        ("hermite", FilterMethod::Hermite),
         ("catmull_rom", FilterMethod::CatmullRom),
+        ("mitchell", FilterMethod::Mitchell),
         ("gaussian", FilterMethod::Gaussian),
+        ("lanczos2", FilterMethod::Lanczos2),
         ("lanczos3", FilterMethod::Lanczos3),
+        ("lanczos4", FilterMethod::Lanczos4),
     ];
     for (s, expected) in cases {
         let cfg: Config = toml::from_str(&format!("[viewer]\nfilter_method = \"{s}\"")).unwrap();
@@ -64,30 +90,53 @@ fn filter_method_unknown_string_errors() {
     assert!(result.is_err());
 }
 
+#[test]
+fn fit_mode_snake_case_variants() {
+    let cases = [
+        ("fit", FitMode::Fit),
+        ("shrink_to_fit", FitMode::ShrinkToFit),
+        ("fill", FitMode::Fill),
+        ("fit_width", FitMode::FitWidth),
+        ("fit_height", FitMode::FitHeight),
+        ("actual_size", FitMode::ActualSize),
+    ];
+    for (s, expected) in cases {
+        let cfg: Config = toml::from_str(&format!("[viewer]\nfit_mode = \"{s}\"")).unwrap();
+        assert_eq!(cfg.viewer.unwrap().fit_mode.unwrap(), expected);
+    }
+}
+
 #[test]
 fn merge_overlay_wins_on_conflict() {
     let base = Config {
         window: Some(Window {
             decorations: Some(false),
             antialiasing: Some(true),
+            fullscreen: Some(true),
         }),
         viewer: None,
         keybindings: None,
+        binds: None,
+        mousebindings: None,
         logging: None,
     };
     let overlay = Config {
         window: Some(Window {
             decorations: Some(true),
             antialiasing: None,
+            fullscreen: None,
         }),
         viewer: None,
         keybindings: None,
+        binds: None,
+        mousebindings: None,
         logging: None,
     };
     let merged = Config::merge(base, overlay);
     let w = merged.window.unwrap();
     assert_eq!(w.decorations, Some(true));
     assert_eq!(w.antialiasing, Some(true));
+    assert_eq!(w.fullscreen, Some(true));
 }
 
 #[test]
@@ -97,6 +146,8 @@ fn merge_overlay_none_section_keeps_base() {
         window: None,
         viewer: None,
         keybindings: None,
+        binds: None,
+        mousebindings: None,
         logging: None,
     };
     let merged = Config::merge(base.clone(), overlay);
@@ -132,3 +183,57 @@ fn get_home_path_uses_home() {
         std::path::PathBuf::from("/tmp/home/.config/imgvwr/config.toml")
     );
 }
+
+#[test]
+fn keybinding_accepts_string_or_list() {
+    let cfg: Config =
+        toml::from_str("[keybindings]\nquit = \"q\"\nnext = [\"Right\", \"l\", \"space\"]")
+            .unwrap();
+    let k = cfg.keybindings.unwrap();
+    assert_eq!(k.quit.unwrap().keys(), ["q"]);
+    assert_eq!(k.next.unwrap().keys(), ["Right", "l", "space"]);
+}
+
+#[test]
+fn binds_tables_are_parsed_in_order() {
+    let cfg: Config = toml::from_str(
+        "[[binds]]\nkey = \"Ctrl+Delete\"\naction = \"delete\"\n\n\
+         [[binds]]\nkey = \"space\"\naction = \"next\"",
+    )
+    .unwrap();
+    let binds = cfg.binds.unwrap();
+    assert_eq!(binds.len(), 2);
+    assert_eq!(binds[0].key, "Ctrl+Delete");
+    assert_eq!(binds[0].action, "delete");
+    assert_eq!(binds[1].action, "next");
+}
+
+#[test]
+fn merge_appends_binds() {
+    let with_bind = |key: &str| Config {
+        window: None,
+        viewer: None,
+        keybindings: None,
+        binds: Some(vec![Bind {
+            key: key.to_string(),
+            action: "next".to_string(),
+        }]),
+        mousebindings: None,
+        logging: None,
+    };
+    let merged = Config::merge(with_bind("a"), with_bind("b"));
+    let keys: Vec<_> = merged.binds.unwrap().into_iter().map(|b| b.key).collect();
+    assert_eq!(keys, ["a", "b"]);
+}
+
+#[test]
+fn mousebindings_parse_and_merge_per_action() {
+    let cfg: Config = toml::from_str(
+        "[mousebindings]\nnext = \"wheel_down\"\nzoom_fit = [\"middle\", \"double_left\"]",
+    )
+    .unwrap();
+    let merged = Config::merge(Config::default(), cfg).mousebindings.unwrap();
+    assert_eq!(merged.0["next"], KeySpec::from("wheel_down"));
+    assert_eq!(merged.0["zoom_fit"].keys(), ["middle", "double_left"]);
+    assert_eq!(merged.0["pan_drag"], KeySpec::from("left"));
+}
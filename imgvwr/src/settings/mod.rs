@@ -2,28 +2,43 @@
 mod tests;
 
 use std::path::PathBuf;
+use std::time::Duration;
 
 use libimgvwr::{
-    keybinds::{KeybindMap, Keysym, keysym_from_str},
+    keybinds::{
+        Action, KeyCombo, KeybindError, KeybindMap,
+        mouse::{MouseCombo, MousebindMap},
+    },
     renderer,
+    viewport::FitMode,
 };
 
 use crate::{
     cli::Cli,
-    config::{Config, FilterMethod, Keybindings},
+    config::{self, Bind, Config, FilterMethod, KeySpec, Keybindings, Mousebindings},
 };
 
 pub(crate) struct AppSettings {
     pub(crate) paths: Vec<PathBuf>,
     pub(crate) decorations: bool,
     pub(crate) antialiasing: bool,
+    pub(crate) fullscreen: bool,
     pub(crate) min_scale: f32,
     pub(crate) max_scale: f32,
     pub(crate) scale_step: f32,
-    pub(crate) filter: renderer::FilterMethod,
+    pub(crate) pan_step: f32,
+    pub(crate) skip_step: usize,
+    pub(crate) upscale_filter: renderer::FilterMethod,
+    pub(crate) downscale_filter: renderer::FilterMethod,
+    pub(crate) nearest_threshold: f32,
+    pub(crate) interactive_filter: renderer::FilterMethod,
+    pub(crate) refine_delay: Duration,
+    pub(crate) linear_light: bool,
+    pub(crate) fit_mode: FitMode,
+    #[cfg_attr(any(feature = "gpu-vulkan", feature = "gpu-gles"), allow(dead_code))]
+    pub(crate) render_threads: usize,
     pub(crate) keybind_map: KeybindMap,
-    pub(crate) key_left: Keysym,
-    pub(crate) key_right: Keysym,
+    pub(crate) mousebind_map: MousebindMap,
     pub(crate) log_level: String,
 }
 
@@ -32,24 +47,61 @@ impl AppSettings {
         let window = config.window.clone().unwrap_or_default();
         let viewer = config.viewer.clone().unwrap_or_default();
         let keybindings = config.keybindings.clone().unwrap_or_default();
+        let mousebindings = config.mousebindings.clone().unwrap_or_default();
         let logging = config.logging.clone().unwrap_or_default();
+        // A filter set for one zoom direction beats `filter_method` from the
+        // same source, and the command line beats the config.
+        let filter = |cli_filter: &Option<FilterMethod>, config_filter: &Option<FilterMethod>| {
+            to_render_filter(
+                cli_filter
+                    .as_ref()
+                    .or(cli.filter_method.as_ref())
+                    .or(config_filter.as_ref())
+                    .or(viewer.filter_method.as_ref())
+                    .unwrap_or(&FilterMethod::Nearest),
+            )
+        };
 
         AppSettings {
             paths: cli.paths.clone(),
             decorations: cli.decorations.or(window.decorations).unwrap_or(false),
             antialiasing: cli.antialiasing.or(window.antialiasing).unwrap_or(false),
+            fullscreen: cli.fullscreen.or(window.fullscreen).unwrap_or(false),
             min_scale: cli.min_scale.or(viewer.min_scale).unwrap_or(0.1),
             max_scale: cli.max_scale.or(viewer.max_scale).unwrap_or(100.0),
             scale_step: cli.scale_step.or(viewer.scale_step).unwrap_or(0.08),
-            filter: to_render_filter(
-                cli.filter_method
+            pan_step: cli.pan_step.or(viewer.pan_step).unwrap_or(50.0),
+            skip_step: cli.skip_step.or(viewer.skip_step).unwrap_or(10),
+            upscale_filter: filter(&cli.upscale_filter, &viewer.upscale_filter),
+            downscale_filter: filter(&cli.downscale_filter, &viewer.downscale_filter),
+            nearest_threshold: cli
+                .nearest_threshold
+                .or(viewer.nearest_threshold)
+                .unwrap_or(4.0),
+            interactive_filter: to_render_filter(
+                cli.interactive_filter
                     .as_ref()
-                    .or(viewer.filter_method.as_ref())
-                    .unwrap_or(&FilterMethod::Nearest),
+                    .or(viewer.interactive_filter.as_ref())
+                    .unwrap_or(&FilterMethod::Triangle),
+            ),
+            refine_delay: Duration::from_millis(
+                cli.refine_delay_ms
+                    .or(viewer.refine_delay_ms)
+                    .unwrap_or(150),
             ),
-            keybind_map: build_keybind_map(&keybindings),
-            key_left: keysym_from_str("Left").expect("Left keysym must resolve"),
-            key_right: keysym_from_str("Right").expect("Right keysym must resolve"),
+            linear_light: cli.linear_light.or(viewer.linear_light).unwrap_or(false),
+            fit_mode: to_fit_mode(
+                cli.fit_mode
+                    .as_ref()
+                    .or(viewer.fit_mode.as_ref())
+                    .unwrap_or(&config::FitMode::Fit),
+            ),
+            render_threads: cli.render_threads.or(viewer.render_threads).unwrap_or(0),
+            keybind_map: build_keybind_map(
+                &keybindings,
+                config.binds.as_deref().unwrap_or_default(),
+            ),
+            mousebind_map: build_mousebind_map(&mousebindings),
             log_level: cli
                 .log_level
                 .clone()
@@ -57,49 +109,209 @@ impl AppSettings {
                 .unwrap_or_else(|| "warn".to_string()),
         }
     }
+
+    /// The filter to draw the image with at zoom factor `scale`, while the
+    /// user is panning or zooming if `interactive`.
+    ///
+    /// Without antialiasing every zoom level uses nearest neighbour, as do
+    /// zoom levels from `nearest_threshold` on. Interactive frames use the
+    /// cheaper `interactive_filter` unless the image is drawn with nearest
+    /// neighbour anyway.
+    pub(crate) fn filter_for(&self, scale: f32, interactive: bool) -> renderer::FilterMethod {
+        let nearest = renderer::FilterMethod::Nearest;
+        if !self.antialiasing || (self.nearest_threshold > 0.0 && scale >= self.nearest_threshold) {
+            return nearest;
+        }
+        let filter = if scale > 1.0 {
+            self.upscale_filter
+        } else {
+            self.downscale_filter
+        };
+        if interactive && filter != nearest {
+            self.interactive_filter
+        } else {
+            filter
+        }
+    }
 }
 
 fn to_render_filter(f: &FilterMethod) -> renderer::FilterMethod {
     match f {
         FilterMethod::Nearest => renderer::FilterMethod::Nearest,
+        FilterMethod::Box => renderer::FilterMethod::Box,
         FilterMethod::Triangle => renderer::FilterMethod::Triangle,
+        FilterMethod::SharpBilinear => renderer::FilterMethod::SharpBilinear,
+        FilterMethod::Hermite => renderer::FilterMethod::Hermite,
         FilterMethod::CatmullRom => renderer::FilterMethod::CatmullRom,
+        FilterMethod::Mitchell => renderer::FilterMethod::Mitchell,
         FilterMethod::Gaussian => renderer::FilterMethod::Gaussian,
+        FilterMethod::Lanczos2 => renderer::FilterMethod::Lanczos2,
         FilterMethod::Lanczos3 => renderer::FilterMethod::Lanczos3,
+        FilterMethod::Lanczos4 => renderer::FilterMethod::Lanczos4,
+    }
+}
+
+fn to_fit_mode(f: &config::FitMode) -> FitMode {
+    match f {
+        config::FitMode::Fit => FitMode::Fit,
+        config::FitMode::ShrinkToFit => FitMode::ShrinkToFit,
+        config::FitMode::Fill => FitMode::Fill,
+        config::FitMode::FitWidth => FitMode::FitWidth,
+        config::FitMode::FitHeight => FitMode::FitHeight,
+        config::FitMode::ActualSize => FitMode::ActualSize,
     }
 }
 
+/// A key spec that more than one action claims. The later binding is kept:
+/// a rebound action beats a built-in default, and among rebound actions
+/// `[[binds]]` entries come after `[keybindings]`.
+#[derive(Debug, PartialEq)]
+struct KeyConflict {
+    key: String,
+    kept: Action,
+    dropped: Action,
+}
+
 #[cfg(feature = "keybinds")]
-fn build_keybind_map(keybindings: &Keybindings) -> KeybindMap {
-    let quit = resolve_keysym(keybindings.quit.as_deref().unwrap_or("q"), "q");
-    let rotate_left = resolve_keysym(
-        keybindings.rotate_left.as_deref().unwrap_or("bracketleft"),
-        "bracketleft",
-    );
-    let rotate_right = resolve_keysym(
-        keybindings
-            .rotate_right
-            .as_deref()
-            .unwrap_or("bracketright"),
-        "bracketright",
-    );
-    let delete = resolve_keysym(keybindings.delete.as_deref().unwrap_or("Delete"), "Delete");
-    KeybindMap::new(quit, rotate_left, rotate_right, delete)
+fn build_keybind_map(keybindings: &Keybindings, binds: &[Bind]) -> KeybindMap {
+    let (bindings, conflicts) = collect_bindings(keybindings, binds);
+    warn_conflicts(&conflicts, "key");
+    KeybindMap::new(bindings)
 }
 
 #[cfg(not(feature = "keybinds"))]
-fn build_keybind_map(_keybindings: &Keybindings) -> KeybindMap {
-    KeybindMap::new(
-        keysym_from_str("q").expect("q keysym must resolve"),
-        keysym_from_str("bracketleft").expect("bracketleft keysym must resolve"),
-        keysym_from_str("bracketright").expect("bracketright keysym must resolve"),
-        keysym_from_str("Delete").expect("Delete keysym must resolve"),
-    )
+fn build_keybind_map(_keybindings: &Keybindings, _binds: &[Bind]) -> KeybindMap {
+    KeybindMap::new(collect_bindings(&Keybindings::default(), &[]).0)
 }
 
 #[cfg(feature = "keybinds")]
-fn resolve_keysym(name: &str, fallback: &str) -> Keysym {
-    keysym_from_str(name)
-        .or_else(|_| keysym_from_str(fallback))
-        .expect("fallback keysym must resolve")
+fn build_mousebind_map(mousebindings: &Mousebindings) -> MousebindMap {
+    let (bindings, conflicts) = collect_mousebindings(mousebindings);
+    warn_conflicts(&conflicts, "mouse input");
+    MousebindMap::new(bindings)
+}
+
+#[cfg(not(feature = "keybinds"))]
+fn build_mousebind_map(_mousebindings: &Mousebindings) -> MousebindMap {
+    MousebindMap::new(collect_mousebindings(&Mousebindings::default()).0)
+}
+
+#[cfg(feature = "keybinds")]
+fn warn_conflicts(conflicts: &[KeyConflict], kind: &str) {
+    for c in conflicts {
+        tracing::warn!(
+            key = %c.key,
+            kept = %c.kept,
+            dropped = %c.dropped,
+            "{kind} bound to more than one action"
+        );
+    }
+}
+
+/// Resolve every configured key spec to a `(combo, action)` pair, ordered so
+/// that [`KeybindMap::new`]'s last-wins rule applies the precedence described
+/// on [`KeyConflict`]. Invalid key or action names are logged and skipped; an
+/// action whose configured keys are all invalid keeps its default keys.
+fn collect_bindings(
+    keybindings: &Keybindings,
+    binds: &[Bind],
+) -> (Vec<(KeyCombo, Action)>, Vec<KeyConflict>) {
+    let defaults = Keybindings::default();
+    let defaults = defaults.entries();
+    let mut specs: Vec<(bool, &str, Action)> = Vec::new();
+    for (i, (name, spec)) in keybindings.entries().into_iter().enumerate() {
+        let action = name
+            .parse()
+            .expect("keybinding field names are action names");
+        let (rebound, keys) = keys_or_default(spec, defaults[i].1, action, KeyCombo::parse);
+        for key in keys {
+            specs.push((rebound, key, action));
+        }
+    }
+    for bind in binds {
+        match bind.action.parse() {
+            Ok(action) => specs.push((true, &bind.key, action)),
+            Err(e) => tracing::warn!(error = %e, key = %bind.key, "ignoring [[binds]] entry"),
+        }
+    }
+    resolve_specs(specs, KeyCombo::parse)
+}
+
+/// Resolve `[mousebindings]` like [`collect_bindings`] resolves keys.
+fn collect_mousebindings(
+    mousebindings: &Mousebindings,
+) -> (Vec<(MouseCombo, Action)>, Vec<KeyConflict>) {
+    let defaults = Mousebindings::default();
+    let mut specs: Vec<(bool, &str, Action)> = Vec::new();
+    for (name, spec) in &mousebindings.0 {
+        let action = match name.parse() {
+            Ok(action) => action,
+            Err(e) => {
+                tracing::warn!(error = %e, "ignoring [mousebindings] entry");
+                continue;
+            }
+        };
+        let (rebound, keys) =
+            keys_or_default(Some(spec), defaults.0.get(name), action, MouseCombo::parse);
+        for key in keys {
+            specs.push((rebound, key, action));
+        }
+    }
+    resolve_specs(specs, MouseCombo::parse)
+}
+
+/// Pick the keys to bind to `action`: those of `spec`, or those of `default`
+/// when `spec` lists keys but none of them parse, so a typo never leaves an
+/// action unbound. Also returns whether the chosen keys differ from
+/// `default`.
+fn keys_or_default<'a, C>(
+    spec: Option<&'a KeySpec>,
+    default: Option<&'a KeySpec>,
+    action: Action,
+    parse: fn(&str) -> Result<C, KeybindError>,
+) -> (bool, &'a [String]) {
+    let keys = spec.map(KeySpec::keys).unwrap_or_default();
+    let errors: Vec<_> = keys.iter().filter_map(|k| parse(k).err()).collect();
+    if keys.is_empty() || errors.len() < keys.len() {
+        return (spec != default, keys);
+    }
+    for e in errors {
+        tracing::warn!(error = %e, %action, "ignoring invalid binding; keeping the default");
+    }
+    (false, default.map(KeySpec::keys).unwrap_or_default())
+}
+
+/// Parse `(rebound, spec, action)` triples into combos, applying the
+/// precedence described on [`KeyConflict`] and recording every conflict.
+fn resolve_specs<C: Copy + PartialEq>(
+    mut specs: Vec<(bool, &str, Action)>,
+    parse: fn(&str) -> Result<C, KeybindError>,
+) -> (Vec<(C, Action)>, Vec<KeyConflict>) {
+    specs.sort_by_key(|&(rebound, _, _)| rebound);
+
+    let mut bound: Vec<(C, Action)> = Vec::new();
+    let mut conflicts = Vec::new();
+    for (_, key, action) in specs {
+        let combo = match parse(key) {
+            Ok(combo) => combo,
+            Err(e) => {
+                tracing::warn!(error = %e, %action, "ignoring invalid binding");
+                continue;
+            }
+        };
+        match bound.iter_mut().find(|(c, _)| *c == combo) {
+            Some((_, prev)) => {
+                if *prev != action {
+                    conflicts.push(KeyConflict {
+                        key: key.to_string(),
+                        kept: action,
+                        dropped: *prev,
+                    });
+                }
+                *prev = action;
+            }
+            None => bound.push((combo, action)),
+        }
+    }
+    (bound, conflicts)
 }
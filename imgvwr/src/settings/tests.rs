@@ -1 +1,328 @@
+use clap::Parser;
+use libimgvwr::keybinds::{
+    Action, KeyCombo, KeybindMap, keysym_from_str,
+    mouse::{BTN_LEFT, BTN_MIDDLE, BTN_RIGHT, MouseCombo, MouseInput},
+};
 
+use super::*;
+
+fn resolve_default() -> AppSettings {
+    AppSettings::resolve(&Cli::parse_from(["imgvwr"]), &Config::default())
+}
+
+#[test]
+fn navigation_keys_come_from_keybind_map() {
+    let settings = resolve_default();
+    let lookup = |name| settings.keybind_map.lookup(keysym_from_str(name).unwrap());
+    assert_eq!(lookup("Left"), Some(Action::Prev));
+    assert_eq!(lookup("Right"), Some(Action::Next));
+    assert_eq!(lookup("Home"), Some(Action::First));
+    assert_eq!(lookup("End"), Some(Action::Last));
+    assert_eq!(lookup("Page_Down"), Some(Action::SkipForward));
+    assert_eq!(lookup("Page_Up"), Some(Action::SkipBackward));
+    assert_eq!(lookup("r"), Some(Action::Random));
+    assert_eq!(lookup("g"), Some(Action::GoTo));
+    assert_eq!(settings.skip_step, 10);
+}
+
+#[cfg(feature = "keybinds")]
+#[test]
+fn rebound_key_wins_over_builtin_default() {
+    let mut config = Config::default();
+    let keybindings = config.keybindings.as_mut().unwrap();
+    keybindings.prev = Some("h".into());
+    keybindings.next = Some("l".into());
+    let settings = AppSettings::resolve(&Cli::parse_from(["imgvwr"]), &config);
+    let lookup = |name| settings.keybind_map.lookup(keysym_from_str(name).unwrap());
+    assert_eq!(lookup("h"), Some(Action::Prev));
+    assert_eq!(lookup("l"), Some(Action::Next));
+    assert_eq!(lookup("Left"), None);
+}
+
+#[test]
+fn skip_step_cli_overrides_config() {
+    let cli = Cli::parse_from(["imgvwr", "--skip-step", "3"]);
+    let settings = AppSettings::resolve(&cli, &Config::default());
+    assert_eq!(settings.skip_step, 3);
+}
+
+#[test]
+fn render_threads_cli_overrides_config() {
+    let settings = resolve_default();
+    assert_eq!(settings.render_threads, 0);
+    let mut config = Config::default();
+    config.viewer.as_mut().unwrap().render_threads = Some(2);
+    let cli = Cli::parse_from(["imgvwr", "--render-threads", "6"]);
+    assert_eq!(AppSettings::resolve(&cli, &config).render_threads, 6);
+    let settings = AppSettings::resolve(&Cli::parse_from(["imgvwr"]), &config);
+    assert_eq!(settings.render_threads, 2);
+}
+
+#[cfg(feature = "keybinds")]
+#[test]
+fn modifier_combo_binding_is_resolved() {
+    let mut config = Config::default();
+    config.keybindings.as_mut().unwrap().delete = Some("Ctrl+Delete".into());
+    let settings = AppSettings::resolve(&Cli::parse_from(["imgvwr"]), &config);
+    let ctrl_delete = KeyCombo::parse("Ctrl+Delete").unwrap();
+    assert_eq!(
+        settings.keybind_map.lookup(ctrl_delete),
+        Some(Action::DeleteFile)
+    );
+    assert_eq!(
+        settings
+            .keybind_map
+            .lookup(keysym_from_str("Delete").unwrap()),
+        None
+    );
+}
+
+fn bind(key: &str, action: &str) -> Bind {
+    Bind {
+        key: key.to_string(),
+        action: action.to_string(),
+    }
+}
+
+fn lookup(bindings: &[(KeyCombo, Action)], key: &str) -> Option<Action> {
+    let combo = KeyCombo::parse(key).unwrap();
+    KeybindMap::new(bindings.iter().copied()).lookup(combo)
+}
+
+#[test]
+fn defaults_have_no_conflicts() {
+    let (_, conflicts) = collect_bindings(&Keybindings::default(), &[]);
+    assert_eq!(conflicts, []);
+}
+
+#[test]
+fn action_accepts_a_list_of_keys() {
+    let keybindings = Keybindings {
+        next: Some(KeySpec::Many(vec![
+            "Right".to_string(),
+            "n".to_string(),
+            "space".to_string(),
+        ])),
+        ..Keybindings::default()
+    };
+    let (bindings, conflicts) = collect_bindings(&keybindings, &[]);
+    assert_eq!(conflicts, []);
+    for key in ["Right", "n", "space"] {
+        assert_eq!(lookup(&bindings, key), Some(Action::Next));
+    }
+}
+
+#[test]
+fn empty_list_unbinds_action() {
+    let keybindings = Keybindings {
+        quit: Some(KeySpec::Many(Vec::new())),
+        ..Keybindings::default()
+    };
+    let (bindings, _) = collect_bindings(&keybindings, &[]);
+    assert_eq!(lookup(&bindings, "q"), None);
+}
+
+#[test]
+fn binds_table_maps_any_key_to_any_action() {
+    let binds = [bind("Ctrl+o", "fit_width"), bind("x", "teleport")];
+    let (bindings, conflicts) = collect_bindings(&Keybindings::default(), &binds);
+    assert_eq!(conflicts, []);
+    assert_eq!(
+        lookup(&bindings, "Ctrl+o"),
+        Some(Action::SetFitMode(libimgvwr::viewport::FitMode::FitWidth))
+    );
+    assert_eq!(lookup(&bindings, "x"), None);
+}
+
+#[test]
+fn rebinding_over_a_default_is_reported() {
+    let keybindings = Keybindings {
+        next: Some(KeySpec::Many(vec!["Right".to_string(), "l".to_string()])),
+        ..Keybindings::default()
+    };
+    let (bindings, conflicts) = collect_bindings(&keybindings, &[]);
+    assert_eq!(lookup(&bindings, "l"), Some(Action::Next));
+    assert_eq!(
+        conflicts,
+        [KeyConflict {
+            key: "l".to_string(),
+            kept: Action::Next,
+            dropped: Action::PanRight,
+        }]
+    );
+}
+
+#[test]
+fn binds_entry_wins_over_keybindings_conflict() {
+    let keybindings = Keybindings {
+        quit: Some("x".into()),
+        ..Keybindings::default()
+    };
+    let (bindings, conflicts) = collect_bindings(&keybindings, &[bind("x", "random")]);
+    assert_eq!(lookup(&bindings, "x"), Some(Action::Random));
+    assert_eq!(
+        conflicts,
+        [KeyConflict {
+            key: "x".to_string(),
+            kept: Action::Random,
+            dropped: Action::Quit,
+        }]
+    );
+}
+
+#[test]
+fn invalid_key_is_skipped_without_dropping_others() {
+    let keybindings = Keybindings {
+        next: Some(KeySpec::Many(vec!["Hyper+n".to_string(), "n".to_string()])),
+        ..Keybindings::default()
+    };
+    let (bindings, _) = collect_bindings(&keybindings, &[]);
+    assert_eq!(lookup(&bindings, "n"), Some(Action::Next));
+}
+
+#[test]
+fn action_with_only_invalid_keys_keeps_its_default() {
+    let keybindings = Keybindings {
+        next: Some(KeySpec::Many(vec![
+            "Hyper+n".to_string(),
+            "nosuchkey".to_string(),
+        ])),
+        ..Keybindings::default()
+    };
+    let (bindings, conflicts) = collect_bindings(&keybindings, &[]);
+    assert_eq!(conflicts, []);
+    assert_eq!(lookup(&bindings, "Right"), Some(Action::Next));
+}
+
+#[test]
+fn mouse_action_with_only_invalid_inputs_keeps_its_default() {
+    let mut mousebindings = Mousebindings::default();
+    mousebindings
+        .0
+        .insert("next".to_string(), "wheel_sideways".into());
+    let (bindings, _) = collect_mousebindings(&mousebindings);
+    assert!(bindings.contains(&(MouseCombo::parse("right").unwrap(), Action::Next)));
+}
+
+#[test]
+fn default_mouse_bindings() {
+    let settings = resolve_default();
+    let map = &settings.mousebind_map;
+    assert_eq!(
+        map.lookup(MouseInput::Button(BTN_LEFT)),
+        Some(Action::PanDrag)
+    );
+    assert_eq!(
+        map.lookup(MouseInput::Button(BTN_MIDDLE)),
+        Some(Action::ZoomFit)
+    );
+    assert_eq!(
+        map.lookup(MouseInput::DoubleClick(BTN_LEFT)),
+        Some(Action::ToggleFullscreen)
+    );
+    assert_eq!(
+        map.lookup(MouseInput::Button(BTN_RIGHT)),
+        Some(Action::Next)
+    );
+    assert_eq!(map.lookup(MouseInput::WheelDown), Some(Action::Next));
+    assert_eq!(map.lookup(MouseInput::WheelUp), Some(Action::Prev));
+    assert_eq!(map.lookup(MouseInput::WheelLeft), Some(Action::Prev));
+    assert_eq!(map.lookup(MouseInput::WheelRight), Some(Action::Next));
+    let ctrl_up = MouseCombo::parse("Ctrl+wheel_up").unwrap();
+    assert_eq!(map.lookup(ctrl_up), Some(Action::ZoomIn));
+}
+
+#[test]
+fn default_mouse_bindings_have_no_conflicts() {
+    let (_, conflicts) = collect_mousebindings(&Mousebindings::default());
+    assert_eq!(conflicts, []);
+}
+
+#[test]
+fn unknown_mouse_action_is_skipped() {
+    let mut mousebindings = Mousebindings::default();
+    mousebindings
+        .0
+        .insert("teleport".to_string(), "back".into());
+    mousebindings
+        .0
+        .insert("first".to_string(), "Ctrl+back".into());
+    let (bindings, _) = collect_mousebindings(&mousebindings);
+    let combo = MouseCombo::parse("Ctrl+back").unwrap();
+    assert!(bindings.contains(&(combo, Action::First)));
+    assert_eq!(bindings.len(), 13);
+}
+
+#[test]
+fn fullscreen_comes_from_config_unless_cli_overrides() {
+    let mut config = Config::default();
+    config.window.as_mut().unwrap().fullscreen = Some(true);
+    let settings = AppSettings::resolve(&Cli::parse_from(["imgvwr"]), &config);
+    assert!(settings.fullscreen);
+    let cli = Cli::parse_from(["imgvwr", "--fullscreen", "false"]);
+    assert!(!AppSettings::resolve(&cli, &config).fullscreen);
+}
+
+#[test]
+fn linear_light_comes_from_config_unless_cli_overrides() {
+    assert!(!resolve_default().linear_light);
+    let mut config = Config::default();
+    config.viewer.as_mut().unwrap().linear_light = Some(true);
+    let settings = AppSettings::resolve(&Cli::parse_from(["imgvwr"]), &config);
+    assert!(settings.linear_light);
+    let cli = Cli::parse_from(["imgvwr", "--linear-light", "false"]);
+    assert!(!AppSettings::resolve(&cli, &config).linear_light);
+}
+
+#[test]
+fn zoom_direction_filters_fall_back_to_filter_method() {
+    let mut config = Config::default();
+    let viewer = config.viewer.as_mut().unwrap();
+    viewer.filter_method = Some(FilterMethod::Lanczos3);
+    viewer.downscale_filter = Some(FilterMethod::Box);
+    let settings = AppSettings::resolve(&Cli::parse_from(["imgvwr"]), &config);
+    assert_eq!(settings.upscale_filter, renderer::FilterMethod::Lanczos3);
+    assert_eq!(settings.downscale_filter, renderer::FilterMethod::Box);
+
+    // The command line beats the config, specific or not.
+    let cli = Cli::parse_from(["imgvwr", "--filter-method", "mitchell"]);
+    let settings = AppSettings::resolve(&cli, &config);
+    assert_eq!(settings.upscale_filter, renderer::FilterMethod::Mitchell);
+    assert_eq!(settings.downscale_filter, renderer::FilterMethod::Mitchell);
+    let cli = Cli::parse_from(["imgvwr", "--upscale-filter", "hermite"]);
+    let settings = AppSettings::resolve(&cli, &config);
+    assert_eq!(settings.upscale_filter, renderer::FilterMethod::Hermite);
+    assert_eq!(settings.downscale_filter, renderer::FilterMethod::Box);
+}
+
+#[test]
+fn filter_follows_zoom_direction_threshold_and_interaction() {
+    use renderer::FilterMethod::{Box, Lanczos3, Nearest, Triangle};
+
+    let cli = Cli::parse_from([
+        "imgvwr",
+        "--antialiasing",
+        "--upscale-filter",
+        "lanczos3",
+        "--downscale-filter",
+        "box",
+    ]);
+    let mut settings = AppSettings::resolve(&cli, &Config::default());
+    assert_eq!(settings.nearest_threshold, 4.0);
+    assert_eq!(settings.interactive_filter, Triangle);
+    assert_eq!(settings.refine_delay, Duration::from_millis(150));
+
+    assert_eq!(settings.filter_for(0.5, false), Box);
+    assert_eq!(settings.filter_for(1.0, false), Box);
+    assert_eq!(settings.filter_for(2.0, false), Lanczos3);
+    assert_eq!(settings.filter_for(4.0, false), Nearest);
+    assert_eq!(settings.filter_for(2.0, true), Triangle);
+    assert_eq!(settings.filter_for(8.0, true), Nearest);
+
+    settings.nearest_threshold = 0.0;
+    assert_eq!(settings.filter_for(50.0, false), Lanczos3);
+
+    settings.antialiasing = false;
+    assert_eq!(settings.filter_for(2.0, false), Nearest);
+    assert_eq!(settings.filter_for(0.5, true), Nearest);
+}
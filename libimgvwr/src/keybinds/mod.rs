@@ -1,8 +1,14 @@
 //! Keybind resolution: keysym lookup and action dispatch.
 //!
-//! [`keysym_from_str`] is exported so `imgvwr::config` can resolve key names
-//! from the config file at startup. [`KeybindMap`] is constructed from the
-//! already-resolved keysyms and used in the event loop.
+//! [`keysym_from_str`] and [`KeyCombo::parse`] are exported so
+//! `imgvwr::config` can resolve key names such as `"Ctrl+Shift+s"` from the
+//! config file at startup. [`KeybindMap`] is constructed from the
+//! already-resolved combos and used in the event loop.
+//!
+//! Submodules:
+//! - [`mouse`]: pointer button and wheel bindings ([`mouse::MousebindMap`])
+
+pub mod mouse;
 
 #[cfg(test)]
 mod tests;
@@ -12,58 +18,344 @@ use std::collections::HashMap;
 pub use xkbcommon::xkb::Keysym;
 use xkbcommon::xkb::{self, KEYSYM_NO_FLAGS};
 
+use crate::viewport::{Edge, FitMode};
+
 /// Actions that can be triggered by a key press.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Action {
     Quit,
+    /// Show the previous image in the directory.
+    Prev,
+    /// Show the next image in the directory.
+    Next,
+    /// Show the first image in the directory.
+    First,
+    /// Show the last image in the directory.
+    Last,
+    /// Move forward by the configured skip step.
+    SkipForward,
+    /// Move backward by the configured skip step.
+    SkipBackward,
+    /// Show a randomly chosen image.
+    Random,
+    /// Show the image whose 1-based index was typed as a digit prefix.
+    GoTo,
     RotateLeft,
     RotateRight,
+    FlipHorizontal,
+    FlipVertical,
+    /// Switch to the next [`FitMode`] and apply it.
+    CycleFitMode,
+    /// Switch to the given [`FitMode`] and apply it.
+    SetFitMode(FitMode),
+    /// Zoom in by one step around the window centre.
+    ZoomIn,
+    /// Zoom out by one step around the window centre.
+    ZoomOut,
+    /// Zoom to 100 % (one image pixel per display pixel).
+    ZoomActual,
+    /// Reapply the current [`FitMode`].
+    ZoomFit,
+    /// Pan to reveal more of the image on the left.
+    PanLeft,
+    /// Pan to reveal more of the image on the right.
+    PanRight,
+    /// Pan to reveal more of the image above.
+    PanUp,
+    /// Pan to reveal more of the image below.
+    PanDown,
+    /// Pan until the given image edge meets the window edge.
+    JumpToEdge(Edge),
+    /// Enter or leave fullscreen.
+    ToggleFullscreen,
+    /// Maximize or restore the window.
+    ToggleMaximize,
+    /// Pan by dragging while the bound mouse button is held. Has no effect
+    /// when bound to a key.
+    PanDrag,
     DeleteFile,
 }
 
-/// Error returned when a key name cannot be resolved to a keysym.
+/// Config name of every [`Action`], as used for `[keybindings]` keys and
+/// `[[binds]]` `action` values.
+const ACTION_NAMES: [(&str, Action); 36] = [
+    ("quit", Action::Quit),
+    ("prev", Action::Prev),
+    ("next", Action::Next),
+    ("first", Action::First),
+    ("last", Action::Last),
+    ("skip_forward", Action::SkipForward),
+    ("skip_backward", Action::SkipBackward),
+    ("random", Action::Random),
+    ("go_to", Action::GoTo),
+    ("rotate_left", Action::RotateLeft),
+    ("rotate_right", Action::RotateRight),
+    ("flip_horizontal", Action::FlipHorizontal),
+    ("flip_vertical", Action::FlipVertical),
+    ("cycle_fit_mode", Action::CycleFitMode),
+    ("fit", Action::SetFitMode(FitMode::Fit)),
+    ("shrink_to_fit", Action::SetFitMode(FitMode::ShrinkToFit)),
+    ("fill", Action::SetFitMode(FitMode::Fill)),
+    ("fit_width", Action::SetFitMode(FitMode::FitWidth)),
+    ("fit_height", Action::SetFitMode(FitMode::FitHeight)),
+    ("actual_size", Action::SetFitMode(FitMode::ActualSize)),
+    ("zoom_in", Action::ZoomIn),
+    ("zoom_out", Action::ZoomOut),
+    ("zoom_actual", Action::ZoomActual),
+    ("zoom_fit", Action::ZoomFit),
+    ("pan_left", Action::PanLeft),
+    ("pan_right", Action::PanRight),
+    ("pan_up", Action::PanUp),
+    ("pan_down", Action::PanDown),
+    ("jump_left", Action::JumpToEdge(Edge::Left)),
+    ("jump_right", Action::JumpToEdge(Edge::Right)),
+    ("jump_top", Action::JumpToEdge(Edge::Top)),
+    ("jump_bottom", Action::JumpToEdge(Edge::Bottom)),
+    ("toggle_fullscreen", Action::ToggleFullscreen),
+    ("toggle_maximize", Action::ToggleMaximize),
+    ("pan_drag", Action::PanDrag),
+    ("delete", Action::DeleteFile),
+];
+
+impl Action {
+    /// Every action, in config order.
+    pub fn all() -> impl Iterator<Item = Action> {
+        ACTION_NAMES.iter().map(|&(_, action)| action)
+    }
+
+    /// The config name of this action (e.g. `"zoom_in"`, `"fit_width"`).
+    pub fn name(self) -> &'static str {
+        ACTION_NAMES
+            .iter()
+            .find(|&&(_, action)| action == self)
+            .map(|&(name, _)| name)
+            .expect("every action has a name")
+    }
+
+    /// Whether holding the bound key repeats the action. Only incremental
+    /// moves repeat; one-shot and destructive actions (quit, delete, …) fire
+    /// once per physical press.
+    pub fn is_repeatable(self) -> bool {
+        matches!(
+            self,
+            Action::Prev
+                | Action::Next
+                | Action::SkipForward
+                | Action::SkipBackward
+                | Action::ZoomIn
+                | Action::ZoomOut
+                | Action::PanLeft
+                | Action::PanRight
+                | Action::PanUp
+                | Action::PanDown
+        )
+    }
+}
+
+impl std::str::FromStr for Action {
+    type Err = KeybindError;
+
+    /// Parse a config action name; see [`Action::name`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ACTION_NAMES
+            .iter()
+            .find(|&&(name, _)| name == s)
+            .map(|&(_, action)| action)
+            .ok_or_else(|| KeybindError::UnknownAction(s.to_owned()))
+    }
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Error returned when a key binding string or action name cannot be
+/// resolved.
 #[derive(Debug)]
-pub struct KeybindError(String);
+pub enum KeybindError {
+    /// The key name is not recognised by xkbcommon.
+    UnknownKey(String),
+    /// A `+`-separated prefix is not a known modifier name.
+    UnknownModifier(String),
+    /// The action name does not match any [`Action`].
+    UnknownAction(String),
+    /// The name is not a known mouse button or wheel direction.
+    UnknownMouseInput(String),
+}
 
 impl std::fmt::Display for KeybindError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "unknown key name: {:?}", self.0)
+        match self {
+            Self::UnknownKey(name) => write!(f, "unknown key name: {name:?}"),
+            Self::UnknownModifier(name) => write!(f, "unknown modifier: {name:?}"),
+            Self::UnknownAction(name) => write!(f, "unknown action: {name:?}"),
+            Self::UnknownMouseInput(name) => write!(f, "unknown mouse input: {name:?}"),
+        }
     }
 }
 
 impl std::error::Error for KeybindError {}
 
 /// Resolve a key name string (e.g. `"q"`, `"bracketleft"`) to an XKB
-/// [`Keysym`].
+/// [`Keysym`]. A single character that is not itself a keysym name (e.g.
+/// `"["`, `"+"`) resolves to the keysym that types it.
 ///
 /// Returns [`KeybindError`] if the name is not recognised by xkbcommon.
 pub fn keysym_from_str(name: &str) -> Result<Keysym, KeybindError> {
-    let sym = xkb::keysym_from_name(name, KEYSYM_NO_FLAGS);
+    let mut sym = xkb::keysym_from_name(name, KEYSYM_NO_FLAGS);
+    let mut chars = name.chars();
+    if let (Keysym::NoSymbol, Some(c), None) = (sym, chars.next(), chars.next()) {
+        sym = xkb::utf32_to_keysym(c as u32);
+    }
     if sym == Keysym::NoSymbol {
-        Err(KeybindError(name.to_owned()))
+        Err(KeybindError::UnknownKey(name.to_owned()))
     } else {
         Ok(sym)
     }
 }
 
-/// Maps resolved [`Keysym`]s to [`Action`]s for O(1) lookup in the event loop.
+/// Return the decimal digit `sym` types (including keypad digits), if any.
+pub fn keysym_digit(sym: Keysym) -> Option<u32> {
+    char::from_u32(xkb::keysym_to_utf32(sym))?.to_digit(10)
+}
+
+/// Return whether `sym` is a modifier key (Shift, Control, Caps Lock, Alt,
+/// Super, AltGr and the like), which only qualifies the next key press.
+pub fn keysym_is_modifier(sym: Keysym) -> bool {
+    let raw = sym.raw();
+    // Shift_L..=Hyper_R, ISO_Lock..=ISO_Level5_Lock, Mode_switch, Num_Lock.
+    (0xffe1..=0xffee).contains(&raw)
+        || (0xfe01..=0xfe13).contains(&raw)
+        || raw == 0xff7e
+        || raw == 0xff7f
+}
+
+/// Modifier keys that qualify a [`KeyCombo`].
+///
+/// Lock modifiers (Caps Lock, Num Lock) are deliberately absent so bindings
+/// work regardless of their state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    /// The Super / Windows / Command key.
+    pub logo: bool,
+}
+
+impl Modifiers {
+    /// No modifiers held.
+    pub const NONE: Modifiers = Modifiers {
+        ctrl: false,
+        shift: false,
+        alt: false,
+        logo: false,
+    };
+
+    /// Return `true` if no modifier is held.
+    pub fn is_empty(self) -> bool {
+        self == Self::NONE
+    }
+}
+
+/// A keysym together with the modifiers that must be held for it to match.
+///
+/// Modifiers consumed while translating the key (e.g. the Shift in
+/// Shift+`equal` → `plus`) are not part of the combo; see
+/// [`crate::wayland::keyboard::key_event`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyCombo {
+    pub sym: Keysym,
+    pub mods: Modifiers,
+}
+
+impl KeyCombo {
+    /// Parse a binding string such as `"q"`, `"Ctrl+Delete"`, `"Ctrl++"` or
+    /// `"Ctrl+Shift+s"`.
+    ///
+    /// Modifier names are case-insensitive: `Ctrl`/`Control`, `Shift`,
+    /// `Alt`/`Mod1`, `Super`/`Logo`/`Mod4`. Because Shift is consumed when it
+    /// changes the typed symbol, `Shift+<letter>` is normalised to the
+    /// upper-case letter (`"Ctrl+Shift+s"` ≡ `"Ctrl+S"`). For other shifted
+    /// symbols name the result directly (`"plus"`, not `"Shift+equal"`).
+    ///
+    /// # Errors
+    /// Returns [`KeybindError`] for an unknown key or modifier name.
+    pub fn parse(s: &str) -> Result<KeyCombo, KeybindError> {
+        let (prefix, key) = match s.rsplit_once('+') {
+            Some((prefix, key)) if !key.is_empty() => (Some(prefix), key),
+            // A trailing `+` is the key itself: `"+"` or `"Ctrl++"`.
+            Some(("", _)) => (None, "+"),
+            Some((prefix, _)) => match prefix.strip_suffix('+') {
+                Some(prefix) => (Some(prefix), "+"),
+                None => return Err(KeybindError::UnknownKey(s.to_owned())),
+            },
+            None => (None, s),
+        };
+        let mut mods = parse_modifiers(prefix)?;
+        let mut sym = keysym_from_str(key)?;
+        if mods.shift {
+            let mut chars = key.chars();
+            if let (Some(c), None) = (chars.next(), chars.next())
+                && c.is_lowercase()
+                && let Ok(upper) = keysym_from_str(&c.to_uppercase().to_string())
+            {
+                sym = upper;
+                mods.shift = false;
+            }
+        }
+        Ok(KeyCombo { sym, mods })
+    }
+}
+
+/// Parse a `+`-separated modifier prefix such as `"Ctrl+Shift"`.
+fn parse_modifiers(prefix: Option<&str>) -> Result<Modifiers, KeybindError> {
+    let mut mods = Modifiers::NONE;
+    for name in prefix.into_iter().flat_map(|p| p.split('+')) {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => mods.ctrl = true,
+            "shift" => mods.shift = true,
+            "alt" | "mod1" => mods.alt = true,
+            "super" | "logo" | "mod4" => mods.logo = true,
+            _ => return Err(KeybindError::UnknownModifier(name.to_owned())),
+        }
+    }
+    Ok(mods)
+}
+
+impl From<Keysym> for KeyCombo {
+    fn from(sym: Keysym) -> Self {
+        KeyCombo {
+            sym,
+            mods: Modifiers::NONE,
+        }
+    }
+}
+
+/// Maps resolved [`KeyCombo`]s to [`Action`]s for O(1) lookup in the event
+/// loop.
 pub struct KeybindMap {
-    inner: HashMap<Keysym, Action>,
+    inner: HashMap<KeyCombo, Action>,
 }
 
 impl KeybindMap {
-    /// Build the map from pre-resolved keysyms.
-    pub fn new(quit: Keysym, rotate_left: Keysym, rotate_right: Keysym, delete: Keysym) -> Self {
-        let mut inner = HashMap::with_capacity(4);
-        inner.insert(quit, Action::Quit);
-        inner.insert(rotate_left, Action::RotateLeft);
-        inner.insert(rotate_right, Action::RotateRight);
-        inner.insert(delete, Action::DeleteFile);
-        KeybindMap { inner }
+    /// Build the map from pre-resolved `(combo, action)` pairs. A bare
+    /// [`Keysym`] binds the key without modifiers.
+    ///
+    /// When the same combo appears more than once, the last pair wins.
+    pub fn new<K: Into<KeyCombo>>(bindings: impl IntoIterator<Item = (K, Action)>) -> Self {
+        KeybindMap {
+            inner: bindings
+                .into_iter()
+                .map(|(combo, action)| (combo.into(), action))
+                .collect(),
+        }
     }
 
-    /// Look up the [`Action`] bound to `sym`, if any.
-    pub fn lookup(&self, sym: Keysym) -> Option<Action> {
-        self.inner.get(&sym).copied()
+    /// Look up the [`Action`] bound to `combo`, if any.
+    pub fn lookup(&self, combo: impl Into<KeyCombo>) -> Option<Action> {
+        self.inner.get(&combo.into()).copied()
     }
 }
@@ -0,0 +1,125 @@
+//! Mouse button and wheel bindings.
+//!
+//! [`MouseCombo::parse`] resolves binding strings such as `"right"`,
+//! `"Ctrl+wheel_up"` or `"double_left"` from the config file;
+//! [`MousebindMap`] maps the resolved combos to [`Action`]s.
+
+use std::collections::HashMap;
+
+use super::{Action, KeybindError, Modifiers, parse_modifiers};
+
+/// Linux evdev code of the left mouse button (`BTN_LEFT`).
+pub const BTN_LEFT: u32 = 0x110;
+/// Linux evdev code of the right mouse button (`BTN_RIGHT`).
+pub const BTN_RIGHT: u32 = 0x111;
+/// Linux evdev code of the middle mouse button (`BTN_MIDDLE`).
+pub const BTN_MIDDLE: u32 = 0x112;
+/// Linux evdev code of the "back" side button (`BTN_SIDE`).
+pub const BTN_SIDE: u32 = 0x113;
+/// Linux evdev code of the "forward" side button (`BTN_EXTRA`).
+pub const BTN_EXTRA: u32 = 0x114;
+
+const BUTTON_NAMES: [(&str, u32); 5] = [
+    ("left", BTN_LEFT),
+    ("right", BTN_RIGHT),
+    ("middle", BTN_MIDDLE),
+    ("back", BTN_SIDE),
+    ("forward", BTN_EXTRA),
+];
+
+/// A mouse event that can be bound to an [`Action`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseInput {
+    /// A button press, identified by its evdev code.
+    Button(u32),
+    /// The second press of a double click.
+    DoubleClick(u32),
+    /// One wheel notch away from the user.
+    WheelUp,
+    /// One wheel notch towards the user.
+    WheelDown,
+    /// One horizontal wheel (tilt) notch to the left.
+    WheelLeft,
+    /// One horizontal wheel (tilt) notch to the right.
+    WheelRight,
+}
+
+/// A [`MouseInput`] together with the keyboard modifiers that must be held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MouseCombo {
+    pub input: MouseInput,
+    pub mods: Modifiers,
+}
+
+impl MouseCombo {
+    /// Parse a binding string: an optional modifier prefix as in
+    /// [`KeyCombo::parse`](super::KeyCombo::parse), then one of `left`,
+    /// `right`, `middle`, `back`, `forward`, `button<N>` (raw evdev code),
+    /// `double_<button>`, `wheel_up`, `wheel_down`, `wheel_left` or
+    /// `wheel_right`.
+    ///
+    /// # Errors
+    /// Returns [`KeybindError`] for an unknown modifier or input name.
+    pub fn parse(s: &str) -> Result<MouseCombo, KeybindError> {
+        let (prefix, name) = match s.rsplit_once('+') {
+            Some((prefix, name)) => (Some(prefix), name),
+            None => (None, s),
+        };
+        let mods = parse_modifiers(prefix)?;
+        let unknown = || KeybindError::UnknownMouseInput(name.to_owned());
+        let input = match name.to_ascii_lowercase().as_str() {
+            "wheel_up" => MouseInput::WheelUp,
+            "wheel_down" => MouseInput::WheelDown,
+            "wheel_left" => MouseInput::WheelLeft,
+            "wheel_right" => MouseInput::WheelRight,
+            name => match name.strip_prefix("double_") {
+                Some(button) => MouseInput::DoubleClick(button_code(button).ok_or_else(unknown)?),
+                None => MouseInput::Button(button_code(name).ok_or_else(unknown)?),
+            },
+        };
+        Ok(MouseCombo { input, mods })
+    }
+}
+
+fn button_code(name: &str) -> Option<u32> {
+    BUTTON_NAMES
+        .iter()
+        .find(|&&(n, _)| n == name)
+        .map(|&(_, code)| code)
+        .or_else(|| name.strip_prefix("button")?.parse().ok())
+}
+
+impl From<MouseInput> for MouseCombo {
+    fn from(input: MouseInput) -> Self {
+        MouseCombo {
+            input,
+            mods: Modifiers::NONE,
+        }
+    }
+}
+
+/// Maps resolved [`MouseCombo`]s to [`Action`]s for O(1) lookup in the event
+/// loop.
+pub struct MousebindMap {
+    inner: HashMap<MouseCombo, Action>,
+}
+
+impl MousebindMap {
+    /// Build the map from pre-resolved `(combo, action)` pairs. A bare
+    /// [`MouseInput`] binds the input without modifiers.
+    ///
+    /// When the same combo appears more than once, the last pair wins.
+    pub fn new<M: Into<MouseCombo>>(bindings: impl IntoIterator<Item = (M, Action)>) -> Self {
+        MousebindMap {
+            inner: bindings
+                .into_iter()
+                .map(|(combo, action)| (combo.into(), action))
+                .collect(),
+        }
+    }
+
+    /// Look up the [`Action`] bound to `combo`, if any.
+    pub fn lookup(&self, combo: impl Into<MouseCombo>) -> Option<Action> {
+        self.inner.get(&combo.into()).copied()
+    }
+}
@@ -1,4 +1,7 @@
-use super::*;
+use super::{
+    mouse::{BTN_LEFT, BTN_MIDDLE, BTN_SIDE, MouseCombo, MouseInput, MousebindMap},
+    *,
+};
 
 #[test]
 fn keysym_from_str_known_key() {
@@ -12,6 +15,18 @@ fn keysym_from_str_bracket_keys() {
     keysym_from_str("bracketright").unwrap();
 }
 
+#[test]
+fn keysym_from_str_single_characters() {
+    assert_eq!(
+        keysym_from_str("[").unwrap(),
+        keysym_from_str("bracketleft").unwrap()
+    );
+    assert_eq!(
+        keysym_from_str("+").unwrap(),
+        keysym_from_str("plus").unwrap()
+    );
+}
+
 #[test]
 fn keysym_from_str_unknown_errors() {
     assert!(keysym_from_str("invalid_xyz_key").is_err());
@@ -23,23 +38,248 @@ fn keybind_map_lookup_round_trip() {
     let rl = keysym_from_str("bracketleft").unwrap();
     let rr = keysym_from_str("bracketright").unwrap();
 
+    let fh = keysym_from_str("bar").unwrap();
+    let fv = keysym_from_str("underscore").unwrap();
     let del = keysym_from_str("Delete").unwrap();
-    let map = KeybindMap::new(quit, rl, rr, del);
+    let map = KeybindMap::new([
+        (quit, Action::Quit),
+        (rl, Action::RotateLeft),
+        (rr, Action::RotateRight),
+        (fh, Action::FlipHorizontal),
+        (fv, Action::FlipVertical),
+        (del, Action::DeleteFile),
+    ]);
 
     assert_eq!(map.lookup(quit), Some(Action::Quit));
     assert_eq!(map.lookup(rl), Some(Action::RotateLeft));
     assert_eq!(map.lookup(rr), Some(Action::RotateRight));
+    assert_eq!(map.lookup(fh), Some(Action::FlipHorizontal));
+    assert_eq!(map.lookup(fv), Some(Action::FlipVertical));
     assert_eq!(map.lookup(del), Some(Action::DeleteFile));
 }
 
+#[test]
+fn keybind_map_last_binding_wins() {
+    let q = keysym_from_str("q").unwrap();
+    let map = KeybindMap::new([(q, Action::Quit), (q, Action::FlipVertical)]);
+    assert_eq!(map.lookup(q), Some(Action::FlipVertical));
+}
+
+#[test]
+fn keybind_map_fit_mode_actions() {
+    let m = keysym_from_str("m").unwrap();
+    let e = keysym_from_str("e").unwrap();
+    let map = KeybindMap::new([
+        (m, Action::CycleFitMode),
+        (e, Action::SetFitMode(FitMode::FitWidth)),
+    ]);
+    assert_eq!(map.lookup(m), Some(Action::CycleFitMode));
+    assert_eq!(map.lookup(e), Some(Action::SetFitMode(FitMode::FitWidth)));
+}
+
+#[test]
+fn keybind_map_zoom_and_pan_actions() {
+    let plus = keysym_from_str("plus").unwrap();
+    let h = keysym_from_str("h").unwrap();
+    let big_h = keysym_from_str("H").unwrap();
+    let map = KeybindMap::new([
+        (plus, Action::ZoomIn),
+        (h, Action::PanLeft),
+        (big_h, Action::JumpToEdge(Edge::Left)),
+    ]);
+    assert_eq!(map.lookup(plus), Some(Action::ZoomIn));
+    assert_eq!(map.lookup(h), Some(Action::PanLeft));
+    assert_eq!(map.lookup(big_h), Some(Action::JumpToEdge(Edge::Left)));
+}
+
 #[test]
 fn keybind_map_unknown_sym_returns_none() {
     let quit = keysym_from_str("q").unwrap();
     let rl = keysym_from_str("bracketleft").unwrap();
     let rr = keysym_from_str("bracketright").unwrap();
     let del = keysym_from_str("Delete").unwrap();
-    let map = KeybindMap::new(quit, rl, rr, del);
+    let map = KeybindMap::new([
+        (quit, Action::Quit),
+        (rl, Action::RotateLeft),
+        (rr, Action::RotateRight),
+        (del, Action::DeleteFile),
+    ]);
 
     let unbound = keysym_from_str("z").unwrap();
     assert_eq!(map.lookup(unbound), None);
 }
+
+#[test]
+fn keysym_digit_reads_main_and_keypad_digits() {
+    assert_eq!(keysym_digit(keysym_from_str("7").unwrap()), Some(7));
+    assert_eq!(keysym_digit(keysym_from_str("KP_3").unwrap()), Some(3));
+    assert_eq!(keysym_digit(keysym_from_str("g").unwrap()), None);
+    assert_eq!(keysym_digit(keysym_from_str("Left").unwrap()), None);
+}
+
+#[test]
+fn keysym_is_modifier_recognises_modifier_keys_only() {
+    for name in [
+        "Shift_L",
+        "Control_R",
+        "Alt_L",
+        "Super_L",
+        "ISO_Level3_Shift",
+    ] {
+        assert!(keysym_is_modifier(keysym_from_str(name).unwrap()), "{name}");
+    }
+    for name in ["a", "5", "Left", "Escape"] {
+        assert!(
+            !keysym_is_modifier(keysym_from_str(name).unwrap()),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn key_combo_parses_plain_key() {
+    let combo = KeyCombo::parse("Delete").unwrap();
+    assert_eq!(combo, KeyCombo::from(keysym_from_str("Delete").unwrap()));
+}
+
+#[test]
+fn key_combo_parses_modifiers_case_insensitively() {
+    let combo = KeyCombo::parse("ctrl+ALT+Super+Right").unwrap();
+    assert_eq!(combo.sym, keysym_from_str("Right").unwrap());
+    assert_eq!(
+        combo.mods,
+        Modifiers {
+            ctrl: true,
+            alt: true,
+            logo: true,
+            ..Modifiers::NONE
+        }
+    );
+}
+
+#[test]
+fn key_combo_shift_letter_becomes_upper_case() {
+    let combo = KeyCombo::parse("Ctrl+Shift+s").unwrap();
+    assert_eq!(combo, KeyCombo::parse("Ctrl+S").unwrap());
+    assert_eq!(combo.sym, keysym_from_str("S").unwrap());
+    assert!(combo.mods.ctrl && !combo.mods.shift);
+}
+
+#[test]
+fn key_combo_shift_kept_for_non_letters() {
+    let combo = KeyCombo::parse("Shift+Right").unwrap();
+    assert_eq!(combo.sym, keysym_from_str("Right").unwrap());
+    assert!(combo.mods.shift);
+}
+
+#[test]
+fn key_combo_rejects_unknown_names() {
+    assert!(matches!(
+        KeyCombo::parse("Hyper+q"),
+        Err(KeybindError::UnknownModifier(_))
+    ));
+    assert!(matches!(
+        KeyCombo::parse("Ctrl+nope"),
+        Err(KeybindError::UnknownKey(_))
+    ));
+}
+
+#[test]
+fn keybind_map_distinguishes_modifiers() {
+    let del = keysym_from_str("Delete").unwrap();
+    let ctrl_del = KeyCombo::parse("Ctrl+Delete").unwrap();
+    let map = KeybindMap::new([(ctrl_del, Action::DeleteFile)]);
+    assert_eq!(map.lookup(ctrl_del), Some(Action::DeleteFile));
+    assert_eq!(map.lookup(del), None);
+}
+
+#[test]
+fn only_incremental_actions_repeat() {
+    assert!(Action::Next.is_repeatable());
+    assert!(Action::PanLeft.is_repeatable());
+    assert!(Action::ZoomIn.is_repeatable());
+    assert!(!Action::Quit.is_repeatable());
+    assert!(!Action::DeleteFile.is_repeatable());
+    assert!(!Action::Random.is_repeatable());
+    assert!(!Action::RotateLeft.is_repeatable());
+}
+
+#[test]
+fn key_combo_plus_key() {
+    let plus = keysym_from_str("plus").unwrap();
+    assert_eq!(KeyCombo::parse("+").unwrap(), KeyCombo::from(plus));
+    let ctrl_plus = KeyCombo::parse("Ctrl++").unwrap();
+    assert_eq!(ctrl_plus.sym, plus);
+    assert!(ctrl_plus.mods.ctrl);
+    assert!(KeyCombo::parse("Ctrl+").is_err());
+}
+
+#[test]
+fn action_names_round_trip() {
+    for action in Action::all() {
+        assert_eq!(action.name().parse::<Action>().unwrap(), action);
+    }
+    assert_eq!(
+        "fit_width".parse::<Action>().unwrap(),
+        Action::SetFitMode(FitMode::FitWidth)
+    );
+    assert_eq!(
+        "jump_top".parse::<Action>().unwrap(),
+        Action::JumpToEdge(Edge::Top)
+    );
+    assert!(matches!(
+        "teleport".parse::<Action>(),
+        Err(KeybindError::UnknownAction(_))
+    ));
+}
+
+#[test]
+fn mouse_combo_parses_buttons_and_wheel() {
+    assert_eq!(
+        MouseCombo::parse("left").unwrap(),
+        MouseCombo::from(MouseInput::Button(BTN_LEFT))
+    );
+    assert_eq!(
+        MouseCombo::parse("back").unwrap().input,
+        MouseInput::Button(BTN_SIDE)
+    );
+    assert_eq!(
+        MouseCombo::parse("button274").unwrap().input,
+        MouseInput::Button(BTN_MIDDLE)
+    );
+    assert_eq!(
+        MouseCombo::parse("double_left").unwrap().input,
+        MouseInput::DoubleClick(BTN_LEFT)
+    );
+    assert_eq!(
+        MouseCombo::parse("wheel_left").unwrap().input,
+        MouseInput::WheelLeft
+    );
+    let ctrl_wheel = MouseCombo::parse("Ctrl+wheel_up").unwrap();
+    assert_eq!(ctrl_wheel.input, MouseInput::WheelUp);
+    assert!(ctrl_wheel.mods.ctrl);
+}
+
+#[test]
+fn mouse_combo_rejects_unknown_input() {
+    assert!(matches!(
+        MouseCombo::parse("Ctrl+thumb"),
+        Err(KeybindError::UnknownMouseInput(_))
+    ));
+    assert!(matches!(
+        MouseCombo::parse("double_wheel_up"),
+        Err(KeybindError::UnknownMouseInput(_))
+    ));
+}
+
+#[test]
+fn mousebind_map_distinguishes_modifiers() {
+    let ctrl_up = MouseCombo::parse("Ctrl+wheel_up").unwrap();
+    let map = MousebindMap::new([
+        (MouseCombo::from(MouseInput::WheelUp), Action::Prev),
+        (ctrl_up, Action::ZoomIn),
+    ]);
+    assert_eq!(map.lookup(MouseInput::WheelUp), Some(Action::Prev));
+    assert_eq!(map.lookup(ctrl_up), Some(Action::ZoomIn));
+    assert_eq!(map.lookup(MouseInput::WheelDown), None);
+}
@@ -2,12 +2,14 @@
 //!
 //! [`Navigator`] scans a directory for supported image files (determined by
 //! enabled Cargo features), sorts them by filename, and tracks the current
-//! position. Wrap-around is always enabled.
+//! position. Wrap-around is always enabled for relative moves; absolute moves
+//! ([`Navigator::go_to`]) reject out-of-range indices.
 
 #[cfg(test)]
 mod tests;
 
 use std::{
+    hash::{BuildHasher, RandomState},
     io,
     path::{Path, PathBuf},
 };
@@ -93,6 +95,47 @@ impl Navigator {
         self.current = self.current.checked_sub(1).unwrap_or(self.paths.len() - 1);
         &self.paths[self.current]
     }
+
+    /// Jump to the first image.
+    pub fn first(&mut self) -> &Path {
+        self.current = 0;
+        &self.paths[self.current]
+    }
+
+    /// Jump to the last image.
+    pub fn last(&mut self) -> &Path {
+        self.current = self.paths.len() - 1;
+        &self.paths[self.current]
+    }
+
+    /// Move `n` images forward (or backward when negative), wrapping around
+    /// either end of the list.
+    pub fn skip(&mut self, n: isize) -> &Path {
+        let len = self.paths.len() as isize;
+        self.current = (self.current as isize + n).rem_euclid(len) as usize;
+        &self.paths[self.current]
+    }
+
+    /// Jump to the image at zero-based `index`. Returns `None` and leaves the
+    /// cursor unchanged if `index` is out of range.
+    pub fn go_to(&mut self, index: usize) -> Option<&Path> {
+        if index >= self.paths.len() {
+            return None;
+        }
+        self.current = index;
+        Some(&self.paths[self.current])
+    }
+
+    /// Jump to a randomly chosen image other than the current one (unless it
+    /// is the only image).
+    pub fn random(&mut self) -> &Path {
+        let len = self.paths.len();
+        if len > 1 {
+            let pick = RandomState::new().hash_one(self.current) as usize % (len - 1);
+            self.current = if pick >= self.current { pick + 1 } else { pick };
+        }
+        &self.paths[self.current]
+    }
 }
 
 fn is_supported(path: &Path) -> bool {
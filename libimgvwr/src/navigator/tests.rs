@@ -99,3 +99,57 @@ fn non_image_files_are_excluded() {
     assert_eq!(nav.paths.len(), 1);
     assert_eq!(nav.current().file_name().unwrap(), "image.png");
 }
+
+#[cfg(feature = "png")]
+#[test]
+fn first_and_last_jump_to_ends() {
+    let dir = tempdir().unwrap();
+    make_png_files(dir.path(), &["a.png", "b.png", "c.png"]);
+    let mut nav = Navigator::from_path(&dir.path().join("b.png")).unwrap();
+    assert_eq!(nav.last().file_name().unwrap(), "c.png");
+    assert_eq!(nav.first().file_name().unwrap(), "a.png");
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn skip_wraps_in_both_directions() {
+    let dir = tempdir().unwrap();
+    make_png_files(dir.path(), &["a.png", "b.png", "c.png"]);
+    let mut nav = Navigator::from_path(dir.path()).unwrap();
+    assert_eq!(nav.skip(5).file_name().unwrap(), "c.png");
+    assert_eq!(nav.skip(-4).file_name().unwrap(), "b.png");
+    assert_eq!(nav.skip(0).file_name().unwrap(), "b.png");
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn go_to_rejects_out_of_range() {
+    let dir = tempdir().unwrap();
+    make_png_files(dir.path(), &["a.png", "b.png"]);
+    let mut nav = Navigator::from_path(dir.path()).unwrap();
+    assert_eq!(nav.go_to(1).unwrap().file_name().unwrap(), "b.png");
+    assert!(nav.go_to(2).is_none());
+    assert_eq!(nav.current().file_name().unwrap(), "b.png");
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn random_never_repeats_current() {
+    let dir = tempdir().unwrap();
+    make_png_files(dir.path(), &["a.png", "b.png", "c.png"]);
+    let mut nav = Navigator::from_path(dir.path()).unwrap();
+    for _ in 0..20 {
+        let before = nav.current;
+        nav.random();
+        assert_ne!(nav.current, before);
+    }
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn random_with_single_image_stays_put() {
+    let dir = tempdir().unwrap();
+    make_png_files(dir.path(), &["a.png"]);
+    let mut nav = Navigator::from_path(dir.path()).unwrap();
+    assert_eq!(nav.random().file_name().unwrap(), "a.png");
+}
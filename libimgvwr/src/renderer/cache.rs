@@ -0,0 +1,98 @@
+//! Cache of transformed images for the CPU render path.
+//!
+//! Resizing the source dominates software rendering, yet a pan leaves the
+//! scaled and rotated image unchanged. [`RenderCache`] keeps recently
+//! transformed parts of images keyed on everything that affects their
+//! pixels, so only the blit runs again. Once the cached images exceed a byte
+//! ceiling the least recently used ones are evicted.
+
+use std::borrow::Cow;
+
+use image::RgbaImage;
+
+use super::frame::DEFAULT_CACHE_BYTES;
+
+struct Entry<K> {
+    key: K,
+    image: RgbaImage,
+    /// Value of [`RenderCache::clock`] at the last hit.
+    used: u64,
+}
+
+/// Least-recently-used cache of transformed images, keyed on `K`.
+pub(crate) struct RenderCache<K> {
+    entries: Vec<Entry<K>>,
+    max_bytes: usize,
+    clock: u64,
+}
+
+impl<K> std::fmt::Debug for RenderCache<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RenderCache")
+            .field("entries", &self.entries.len())
+            .field("bytes", &self.bytes())
+            .field("max_bytes", &self.max_bytes)
+            .finish()
+    }
+}
+
+impl<K> Default for RenderCache<K> {
+    fn default() -> Self {
+        RenderCache::new(DEFAULT_CACHE_BYTES)
+    }
+}
+
+impl<K> RenderCache<K> {
+    /// An empty cache that holds at most `max_bytes` of pixel data.
+    pub(crate) fn new(max_bytes: usize) -> RenderCache<K> {
+        RenderCache {
+            entries: Vec::new(),
+            max_bytes,
+            clock: 0,
+        }
+    }
+
+    /// Total size of the cached images in bytes.
+    pub(crate) fn bytes(&self) -> usize {
+        self.entries.iter().map(|e| e.image.as_raw().len()).sum()
+    }
+
+    /// `true` if an entry whose key satisfies `hit` is cached.
+    pub(crate) fn contains(&self, hit: impl Fn(&K) -> bool) -> bool {
+        self.entries.iter().any(|e| hit(&e.key))
+    }
+
+    /// The first cached entry whose key satisfies `hit`, marked as just
+    /// used.
+    pub(crate) fn find(&mut self, hit: impl Fn(&K) -> bool) -> Option<(&K, &RgbaImage)> {
+        self.clock += 1;
+        let entry = self.entries.iter_mut().find(|e| hit(&e.key))?;
+        entry.used = self.clock;
+        Some((&entry.key, &entry.image))
+    }
+
+    /// Cache `image` under `key`, evicting the least recently used entries
+    /// to stay under the ceiling, and return it. An image larger than the
+    /// whole ceiling is returned without being cached.
+    pub(crate) fn insert(&mut self, key: K, image: RgbaImage) -> Cow<'_, RgbaImage> {
+        self.clock += 1;
+        let size = image.as_raw().len();
+        if size > self.max_bytes {
+            return Cow::Owned(image);
+        }
+        let mut total = self.bytes();
+        while total + size > self.max_bytes {
+            let Some(oldest) = (0..self.entries.len()).min_by_key(|&i| self.entries[i].used) else {
+                break;
+            };
+            total -= self.entries.swap_remove(oldest).image.as_raw().len();
+        }
+        self.entries.push(Entry {
+            key,
+            image,
+            used: self.clock,
+        });
+        let last = self.entries.len() - 1;
+        Cow::Borrowed(&self.entries[last].image)
+    }
+}
@@ -0,0 +1,475 @@
+//! Incremental redraws of a persistent frame.
+//!
+//! [`FrameBuffer`] keeps the last rendered frame and the layout it was drawn
+//! with. On the next redraw it compares layouts: a pan only moves the image,
+//! so the old pixels are [`scroll`]ed and just the newly exposed strips are
+//! rendered; any other change re-renders the frame. Either way it returns
+//! the [`Rect`]s that differ from the previous frame, ready to be passed to
+//! `wl_surface::damage_buffer`.
+
+#[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+use std::borrow::Cow;
+
+#[cfg(any(feature = "gpu-vulkan", feature = "gpu-gles"))]
+use super::render_regions;
+use super::{FilterMethod, ImageId, Source, blit_origin, rotated_size, scaled_size};
+#[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+use super::{blit_regions, cache::RenderCache, cpu_render_regions, transform};
+use crate::viewport::ViewportState;
+
+/// Default byte ceiling of the transformed-image cache of a [`FrameBuffer`]:
+/// enough for the windows of a few zoom levels on a 4K output, or of many
+/// animation frames.
+#[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+pub const DEFAULT_CACHE_BYTES: usize = 256 * 1024 * 1024;
+
+/// The CPU path resamples the visible part of the image plus this fraction
+/// of the buffer size on each side, so that short pans are blitted from the
+/// cache.
+#[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+const PAN_MARGIN_DIVISOR: u32 = 4;
+
+/// An axis-aligned rectangle in buffer pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+}
+
+impl Rect {
+    /// The whole `w × h` buffer.
+    pub fn full(w: u32, h: u32) -> Rect {
+        Rect { x: 0, y: 0, w, h }
+    }
+
+    /// `true` when the rectangle covers no pixels.
+    pub fn is_empty(&self) -> bool {
+        self.w == 0 || self.h == 0
+    }
+
+    /// `true` when `other` lies entirely inside this rectangle.
+    pub fn contains(&self, other: &Rect) -> bool {
+        other.x >= self.x
+            && other.y >= self.y
+            && other.x + other.w <= self.x + self.w
+            && other.y + other.h <= self.y + self.h
+    }
+
+    /// The smallest rectangle covering both rectangles.
+    pub fn union(&self, other: &Rect) -> Rect {
+        let x0 = self.x.min(other.x);
+        let y0 = self.y.min(other.y);
+        let x1 = (self.x + self.w).max(other.x + other.w);
+        let y1 = (self.y + self.h).max(other.y + other.h);
+        Rect {
+            x: x0,
+            y: y0,
+            w: x1 - x0,
+            h: y1 - y0,
+        }
+    }
+
+    /// The overlap of two rectangles, or `None` when they do not overlap.
+    pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+        let x0 = self.x.max(other.x);
+        let y0 = self.y.max(other.y);
+        let x1 = (self.x + self.w).min(other.x + other.w);
+        let y1 = (self.y + self.h).min(other.y + other.h);
+        (x0 < x1 && y0 < y1).then(|| Rect {
+            x: x0,
+            y: y0,
+            w: x1 - x0,
+            h: y1 - y0,
+        })
+    }
+
+    /// Clip a `w × h` rectangle whose top-left corner may lie outside the
+    /// buffer to a `dst_w × dst_h` buffer.
+    pub(crate) fn clipped(
+        (x, y): (i32, i32),
+        (w, h): (u32, u32),
+        dst_w: u32,
+        dst_h: u32,
+    ) -> Option<Rect> {
+        let x0 = i64::from(x).clamp(0, i64::from(dst_w));
+        let y0 = i64::from(y).clamp(0, i64::from(dst_h));
+        let x1 = (i64::from(x) + i64::from(w)).clamp(0, i64::from(dst_w));
+        let y1 = (i64::from(y) + i64::from(h)).clamp(0, i64::from(dst_h));
+        (x0 < x1 && y0 < y1).then_some(Rect {
+            x: x0 as u32,
+            y: y0 as u32,
+            w: (x1 - x0) as u32,
+            h: (y1 - y0) as u32,
+        })
+    }
+}
+
+/// Shift the contents of a `w × h` ARGB8888 buffer by `(dx, dy)` pixels in
+/// place, so that the pixel at `(x, y)` moves to `(x + dx, y + dy)`.
+///
+/// Returns the strips left uncovered by the shift, which still hold stale
+/// pixels and must be redrawn. A shift of the whole width or height exposes
+/// the entire buffer.
+pub fn scroll(buf: &mut [u8], w: u32, h: u32, dx: i32, dy: i32) -> Vec<Rect> {
+    if (dx, dy) == (0, 0) {
+        return Vec::new();
+    }
+    if dx.unsigned_abs() >= w || dy.unsigned_abs() >= h {
+        return vec![Rect::full(w, h)];
+    }
+
+    let stride = w as usize * 4;
+    let kept_w = (w - dx.unsigned_abs()) as usize * 4;
+    let src_x = (-dx).max(0) as usize * 4;
+    let dst_x = dx.max(0) as usize * 4;
+    let kept_h = h - dy.unsigned_abs();
+    for i in 0..kept_h {
+        // When moving down, copy the bottom rows first so that no source row
+        // is overwritten before it is read.
+        let row = if dy > 0 { kept_h - 1 - i } else { i };
+        let src_y = (row + (-dy).max(0) as u32) as usize;
+        let dst_y = (row + dy.max(0) as u32) as usize;
+        let src = src_y * stride + src_x;
+        buf.copy_within(src..src + kept_w, dst_y * stride + dst_x);
+    }
+
+    let mut exposed = Vec::with_capacity(2);
+    let kept_y = dy.max(0) as u32;
+    if dy > 0 {
+        exposed.push(Rect::full(w, dy as u32));
+    } else if dy < 0 {
+        exposed.push(Rect {
+            x: 0,
+            y: kept_h,
+            w,
+            h: dy.unsigned_abs(),
+        });
+    }
+    if dx > 0 {
+        exposed.push(Rect {
+            x: 0,
+            y: kept_y,
+            w: dx as u32,
+            h: kept_h,
+        });
+    } else if dx < 0 {
+        exposed.push(Rect {
+            x: w - dx.unsigned_abs(),
+            y: kept_y,
+            w: dx.unsigned_abs(),
+            h: kept_h,
+        });
+    }
+    exposed
+}
+
+/// Everything that determines the rendered pixels of the image itself,
+/// independent of where it is placed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Content {
+    /// Identity of the source pixels; `None` never matches a previous frame
+    /// or cache entry.
+    image: Option<ImageId>,
+    /// Mip level resampled from; `0` for the image itself.
+    level: u8,
+    scale: u32,
+    rotation: u16,
+    flips: (bool, bool),
+    filter: FilterMethod,
+}
+
+/// A transformed part of the image kept in the cache.
+#[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    content: Content,
+    /// Position in the displayed image.
+    rect: Rect,
+}
+
+/// Where and how a frame was drawn.
+#[derive(Debug, Clone, Copy)]
+struct Layout {
+    content: Content,
+    /// Size of the displayed (scaled and rotated) image.
+    #[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+    size: (u32, u32),
+    /// Top-left corner of the image in the buffer.
+    origin: (i32, i32),
+    /// On-screen part of the image.
+    rect: Option<Rect>,
+}
+
+impl Layout {
+    fn new(
+        src: Source<'_>,
+        viewport: &ViewportState,
+        filter: FilterMethod,
+        dst_w: u32,
+        dst_h: u32,
+    ) -> Layout {
+        let size = rotated_size(src.image, viewport);
+        let origin = blit_origin(size, viewport, dst_w, dst_h);
+        let (level, _) = src.level(scaled_size(src.image, viewport), filter);
+        Layout {
+            content: Content {
+                image: src.id,
+                level,
+                scale: viewport.scale.to_bits(),
+                rotation: viewport.rotation,
+                flips: (viewport.flip_h, viewport.flip_v),
+                filter,
+            },
+            #[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+            size,
+            origin,
+            rect: Rect::clipped(origin, size, dst_w, dst_h),
+        }
+    }
+
+    /// The displayed image pixels shown in `regions` of the buffer, as one
+    /// bounding rectangle, or `None` when no region shows the image.
+    #[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+    fn image_area(&self, regions: &[Rect]) -> Option<Rect> {
+        let rect = self.rect?;
+        regions
+            .iter()
+            .filter_map(|region| region.intersect(&rect))
+            .map(|r| Rect {
+                x: (r.x as i32 - self.origin.0) as u32,
+                y: (r.y as i32 - self.origin.1) as u32,
+                ..r
+            })
+            .reduce(|a, b| a.union(&b))
+    }
+
+    /// `area` of the displayed image grown by `margin` on each side, within
+    /// the image.
+    #[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+    fn grow(&self, area: Rect, (mx, my): (u32, u32)) -> Rect {
+        let x0 = area.x.saturating_sub(mx);
+        let y0 = area.y.saturating_sub(my);
+        let x1 = (area.x + area.w).saturating_add(mx).min(self.size.0);
+        let y1 = (area.y + area.h).saturating_add(my).min(self.size.1);
+        Rect {
+            x: x0,
+            y: y0,
+            w: x1 - x0,
+            h: y1 - y0,
+        }
+    }
+}
+
+/// A persistent ARGB8888 frame that is redrawn incrementally.
+///
+/// On the CPU path only the visible part of the image, plus a margin for
+/// pans, is resampled. These windows are cached (see
+/// [`FrameBuffer::with_cache_limit`]), so redraws that keep the scale,
+/// rotation, flips and filter within a cached window skip the resize; a pan
+/// beyond it resamples just the exposed strips. Resampling and blitting are
+/// spread over worker threads (see [`FrameBuffer::with_threads`]).
+#[derive(Debug, Default)]
+pub struct FrameBuffer {
+    pixels: Vec<u8>,
+    size: (u32, u32),
+    layout: Option<Layout>,
+    #[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+    cache: RenderCache<Window>,
+    /// Worker threads of the CPU path; `0` for one per CPU.
+    #[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+    threads: usize,
+    /// Whether the CPU path resamples in linear light.
+    #[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+    linear: bool,
+}
+
+impl FrameBuffer {
+    /// Limit the cache of transformed windows to `max_bytes` of pixels;
+    /// [`FrameBuffer::default`] allows [`DEFAULT_CACHE_BYTES`].
+    #[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+    pub fn with_cache_limit(self, max_bytes: usize) -> FrameBuffer {
+        FrameBuffer {
+            cache: RenderCache::new(max_bytes),
+            ..self
+        }
+    }
+
+    /// Render on `threads` worker threads; `0`, the default, uses one per
+    /// CPU. The output is the same for every thread count.
+    #[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+    pub fn with_threads(self, threads: usize) -> FrameBuffer {
+        FrameBuffer { threads, ..self }
+    }
+
+    /// Resample colours in linear light rather than as sRGB-encoded values
+    /// if `linear`; off by default. Sources should carry a mip pyramid built
+    /// the same way.
+    #[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+    pub fn with_linear_light(self, linear: bool) -> FrameBuffer {
+        FrameBuffer { linear, ..self }
+    }
+
+    /// The current frame, `w × h × 4` bytes as passed to the last
+    /// [`render`](Self::render).
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Forget the previous frame so that the next [`render`](Self::render)
+    /// redraws and damages everything, e.g. after a frame was never shown.
+    pub fn invalidate(&mut self) {
+        self.layout = None;
+    }
+
+    /// Whether rendering `src` at `viewport` with `filter` would reuse
+    /// resampled pixels, from the previous frame or a cached transform,
+    /// instead of resampling the image again.
+    #[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+    pub fn reuses<'a>(
+        &self,
+        src: impl Into<Source<'a>>,
+        viewport: &ViewportState,
+        filter: FilterMethod,
+    ) -> bool {
+        let (w, h) = self.size;
+        let layout = Layout::new(src.into(), viewport, filter, w, h);
+        let content = layout.content;
+        let cached = |area: Rect| {
+            self.cache
+                .contains(|k| k.content == content && k.rect.contains(&area))
+        };
+        content.image.is_some()
+            && self.layout.is_some_and(|l| {
+                l.content == content || layout.image_area(&[Rect::full(w, h)]).is_none_or(cached)
+            })
+    }
+
+    /// Bring the frame up to date with `src` and `viewport`, redrawing as
+    /// little as possible, and return the damaged rectangles. An empty list
+    /// means the frame is unchanged.
+    ///
+    /// The [`ImageId`] of `src` decides what can be reused: a source without
+    /// one is redrawn in full every time and never cached. A mip pyramid
+    /// arriving for the same image only causes a redraw when the current
+    /// scale resamples from one of its levels.
+    ///
+    /// On the GPU path a pan re-renders the whole frame: the GPU only
+    /// resamples the visible crop of the source, so its output is not
+    /// translation-invariant and scrolled pixels could differ from freshly
+    /// rendered ones. The damage is still limited to the image.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render<'a>(
+        &mut self,
+        src: impl Into<Source<'a>>,
+        viewport: &ViewportState,
+        dst_w: u32,
+        dst_h: u32,
+        filter: FilterMethod,
+        #[cfg(any(feature = "gpu-vulkan", feature = "gpu-gles"))] gpu: &super::gpu::GpuContext,
+    ) -> Vec<Rect> {
+        let src = src.into();
+        let layout = Layout::new(src, viewport, filter, dst_w, dst_h);
+        let full = Rect::full(dst_w, dst_h);
+        let previous = self.layout.replace(layout);
+        let resized = self.size != (dst_w, dst_h);
+        if resized {
+            self.size = (dst_w, dst_h);
+            self.pixels = vec![0u8; dst_w as usize * dst_h as usize * 4];
+        }
+
+        let (regions, damage) = match previous {
+            Some(previous) if !resized => {
+                // Outside the image both frames are background, so only the
+                // old and new image areas can differ.
+                let damage: Vec<Rect> =
+                    [previous.rect, layout.rect].into_iter().flatten().collect();
+                let (dx, dy) = (
+                    layout.origin.0 - previous.origin.0,
+                    layout.origin.1 - previous.origin.1,
+                );
+                if previous.content != layout.content || layout.content.image.is_none() {
+                    (vec![full], damage)
+                } else if (dx, dy) == (0, 0) {
+                    return Vec::new();
+                } else if cfg!(any(feature = "gpu-vulkan", feature = "gpu-gles")) {
+                    (vec![full], damage)
+                } else {
+                    (scroll(&mut self.pixels, dst_w, dst_h, dx, dy), damage)
+                }
+            }
+            _ => (vec![full], vec![full]),
+        };
+
+        #[cfg(any(feature = "gpu-vulkan", feature = "gpu-gles"))]
+        render_regions(
+            src,
+            viewport,
+            &mut self.pixels,
+            dst_w,
+            dst_h,
+            &regions,
+            filter,
+            gpu,
+        );
+
+        #[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+        self.render_cpu(src, viewport, &layout, &regions, filter);
+        damage
+    }
+
+    /// CPU part of [`render`](Self::render): draw `regions` from a cached
+    /// window of the transformed image that covers them. Otherwise, when the
+    /// regions show the whole visible image, resample it plus the pan margin
+    /// and cache that window; a pan that left the cached window resamples
+    /// just the exposed regions.
+    #[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+    fn render_cpu(
+        &mut self,
+        src: Source<'_>,
+        viewport: &ViewportState,
+        layout: &Layout,
+        regions: &[Rect],
+        filter: FilterMethod,
+    ) {
+        let (dst_w, dst_h) = self.size;
+        let (linear, threads) = (self.linear, self.threads);
+        let content = layout.content;
+        if let (Some(area), Some(_)) = (layout.image_area(regions), content.image) {
+            let visible = layout.image_area(&[Rect::full(dst_w, dst_h)]);
+            let part = match self
+                .cache
+                .find(|k| k.content == content && k.rect.contains(&area))
+            {
+                Some((window, image)) => Some((window.rect, Cow::Borrowed(image))),
+                None if visible == Some(area) => {
+                    let margin = (dst_w / PAN_MARGIN_DIVISOR, dst_h / PAN_MARGIN_DIVISOR);
+                    let rect = layout.grow(area, margin);
+                    let image = transform(src, viewport, filter, linear, rect, threads);
+                    Some((rect, self.cache.insert(Window { content, rect }, image)))
+                }
+                None => None,
+            };
+            if let Some((rect, image)) = part {
+                let at = (
+                    layout.origin.0 + rect.x as i32,
+                    layout.origin.1 + rect.y as i32,
+                );
+                blit_regions(&image, at, &mut self.pixels, dst_w, dst_h, regions, threads);
+                return;
+            }
+        }
+        cpu_render_regions(
+            src,
+            viewport,
+            &mut self.pixels,
+            dst_w,
+            dst_h,
+            regions,
+            filter,
+            linear,
+            threads,
+        );
+    }
+}
@@ -2,11 +2,15 @@
 // Compiled only when `gpu-vulkan` or `gpu-gles` feature is enabled.
 //
 // All render pipelines and bind group layouts are compiled once at GpuContext
-// initialisation; per-frame work is limited to texture upload, uniform buffer
-// writes, bind group creation, and draw calls.
+// initialisation, and the source texture of the current image stays on the
+// GPU between frames; per-frame work is limited to uniform buffer writes,
+// bind group creation, a texture-to-texture crop, and draw calls.
 
-use super::FilterMethod;
-use image::DynamicImage;
+use std::borrow::Cow;
+use std::cell::RefCell;
+
+use super::{FilterMethod, ImageId, frame::Rect};
+use image::{DynamicImage, RgbaImage};
 
 /// Errors produced during GPU initialisation.
 #[derive(Debug)]
@@ -37,43 +41,90 @@ impl From<wgpu::RequestDeviceError> for GpuError {
 /// All render pipelines compiled once at init.
 ///
 /// Two bind group layout shapes are used:
-/// - `sampler_bgl`:  binding 0 = texture (filterable float), binding 1 = sampler
+/// - `sampler_bgl`:  binding 0 = texture (filterable float), binding 1 = sampler,
+///   binding 2 = uniform buffer (vertex stage)
 /// - `uniform_bgl`:  binding 0 = texture (non-filterable float), binding 1 = uniform buffer
 pub(crate) struct GpuPipelines {
     pub sampler_bgl: wgpu::BindGroupLayout,
     pub uniform_bgl: wgpu::BindGroupLayout,
 
     pub blit: wgpu::RenderPipeline, // blit.wgsl         → Rgba8Unorm
-    pub lanczos3_h: wgpu::RenderPipeline, // lanczos3 horiz     → Rgba16Float
-    pub lanczos3_v: wgpu::RenderPipeline, // lanczos3 vert      → Rgba8Unorm
-    pub catmull_h: wgpu::RenderPipeline, // catmull_rom horiz  → Rgba16Float
-    pub catmull_v: wgpu::RenderPipeline, // catmull_rom vert   → Rgba8Unorm
-    pub rotate: wgpu::RenderPipeline, // rotate.wgsl        → Rgba8Unorm
+    pub kernels: Vec<(FilterMethod, KernelPipelines)>, // see KERNELS
+    pub rotate: wgpu::RenderPipeline, // rotate.wgsl (+flip) → Rgba8Unorm
+}
+
+/// The kernel file appended to separable.wgsl for each filter not drawn with
+/// the sampler.
+const KERNELS: [(FilterMethod, &str); 10] = [
+    (FilterMethod::Box, include_str!("shaders/box.wgsl")),
+    (
+        FilterMethod::Triangle,
+        include_str!("shaders/triangle.wgsl"),
+    ),
+    (
+        FilterMethod::SharpBilinear,
+        include_str!("shaders/sharp_bilinear.wgsl"),
+    ),
+    (FilterMethod::Hermite, include_str!("shaders/hermite.wgsl")),
+    (
+        FilterMethod::CatmullRom,
+        include_str!("shaders/catmull_rom.wgsl"),
+    ),
+    (
+        FilterMethod::Mitchell,
+        include_str!("shaders/mitchell.wgsl"),
+    ),
+    (
+        FilterMethod::Gaussian,
+        include_str!("shaders/gaussian.wgsl"),
+    ),
+    (FilterMethod::Lanczos2, include_str!("shaders/lanczos.wgsl")),
+    (FilterMethod::Lanczos3, include_str!("shaders/lanczos.wgsl")),
+    (FilterMethod::Lanczos4, include_str!("shaders/lanczos.wgsl")),
+];
+
+/// The two passes of separable.wgsl with one kernel file appended.
+pub(crate) struct KernelPipelines {
+    pub h: wgpu::RenderPipeline, // horizontal → Rgba16Float
+    pub v: wgpu::RenderPipeline, // vertical   → Rgba8Unorm
+}
+
+impl KernelPipelines {
+    fn new(
+        device: &wgpu::Device,
+        bgl: &wgpu::BindGroupLayout,
+        filter: FilterMethod,
+        kernel: &str,
+    ) -> Self {
+        let src = format!(
+            "const SUPPORT: f32 = {:?};\n{}\n{kernel}",
+            filter.support(),
+            include_str!("shaders/separable.wgsl")
+        );
+        Self {
+            h: make_pipeline(
+                device,
+                bgl,
+                &src,
+                "vs_main",
+                "fs_horizontal",
+                wgpu::TextureFormat::Rgba16Float,
+            ),
+            v: make_pipeline(
+                device,
+                bgl,
+                &src,
+                "vs_main",
+                "fs_vertical",
+                wgpu::TextureFormat::Rgba8Unorm,
+            ),
+        }
+    }
 }
 
 impl GpuPipelines {
     fn new(device: &wgpu::Device) -> Self {
-        let sampler_bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("sampler_bgl"),
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        multisampled: false,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                    count: None,
-                },
-            ],
-        });
+        let sampler_bgl = sampler_bind_group_layout(device, "sampler_bgl");
 
         let uniform_bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("uniform_bgl"),
@@ -109,38 +160,15 @@ impl GpuPipelines {
             "fs_main",
             wgpu::TextureFormat::Rgba8Unorm,
         );
-        let lanczos3_h = make_pipeline(
-            device,
-            &uniform_bgl,
-            include_str!("shaders/lanczos3.wgsl"),
-            "vs_main",
-            "fs_horizontal",
-            wgpu::TextureFormat::Rgba16Float,
-        );
-        let lanczos3_v = make_pipeline(
-            device,
-            &uniform_bgl,
-            include_str!("shaders/lanczos3.wgsl"),
-            "vs_main",
-            "fs_vertical",
-            wgpu::TextureFormat::Rgba8Unorm,
-        );
-        let catmull_h = make_pipeline(
-            device,
-            &uniform_bgl,
-            include_str!("shaders/catmull_rom.wgsl"),
-            "vs_main",
-            "fs_horizontal",
-            wgpu::TextureFormat::Rgba16Float,
-        );
-        let catmull_v = make_pipeline(
-            device,
-            &uniform_bgl,
-            include_str!("shaders/catmull_rom.wgsl"),
-            "vs_main",
-            "fs_vertical",
-            wgpu::TextureFormat::Rgba8Unorm,
-        );
+        let kernels = KERNELS
+            .iter()
+            .map(|&(filter, src)| {
+                (
+                    filter,
+                    KernelPipelines::new(device, &uniform_bgl, filter, src),
+                )
+            })
+            .collect();
         let rotate = make_pipeline(
             device,
             &uniform_bgl,
@@ -154,13 +182,55 @@ impl GpuPipelines {
             sampler_bgl,
             uniform_bgl,
             blit,
-            lanczos3_h,
-            lanczos3_v,
-            catmull_h,
-            catmull_v,
+            kernels,
             rotate,
         }
     }
+
+    /// The separable kernel passes for `filter`, or `None` for filters drawn
+    /// with the sampler.
+    fn kernel(&self, filter: FilterMethod) -> Option<&KernelPipelines> {
+        self.kernels
+            .iter()
+            .find(|(f, _)| *f == filter)
+            .map(|(_, kernel)| kernel)
+    }
+}
+
+/// Layout of blit.wgsl: a filterable texture, its sampler, and the texture
+/// coordinates to stretch over the target (see [`sampler_bind_group`]).
+fn sampler_bind_group_layout(device: &wgpu::Device, label: &str) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some(label),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+    })
 }
 
 fn make_pipeline(
@@ -219,6 +289,10 @@ pub struct GpuContext {
     pub(crate) device: wgpu::Device,
     pub(crate) queue: wgpu::Queue,
     pub(crate) pipelines: GpuPipelines,
+    /// Source textures of the image on screen.
+    pub(crate) textures: RefCell<TextureCache<wgpu::Texture>>,
+    /// Whether the kernel filters resample in linear light.
+    pub(crate) linear_light: bool,
     /// Present swapchain state. `None` when running the SHM/readback path.
     #[cfg(feature = "dmabuf")]
     pub(crate) swapchain: Option<SwapchainState>,
@@ -270,35 +344,197 @@ impl GpuContext {
                 device,
                 queue,
                 pipelines,
+                textures: RefCell::default(),
+                linear_light: false,
                 #[cfg(feature = "dmabuf")]
                 swapchain: None,
             })
         })
     }
+
+    /// Resample colours in linear light rather than as sRGB-encoded values
+    /// if `linear`; off by default. Sources should carry a mip pyramid built
+    /// the same way.
+    pub fn with_linear_light(self, linear: bool) -> GpuContext {
+        GpuContext {
+            linear_light: linear,
+            ..self
+        }
+    }
+}
+
+// ─── Source texture cache ─────────────────────────────────────────────────────
+
+/// One uploaded mip level of the current image.
+struct CachedTexture<T> {
+    level: u8,
+    /// Animation frame whose pixels the texture holds.
+    frame: usize,
+    size: (u32, u32),
+    texture: T,
+}
+
+/// Source textures of one image, one per mip level, kept between frames so
+/// that pans and zooms skip the upload.
+///
+/// Switching to another image drops them all. A new frame of the same
+/// animation is written into the existing texture of its level.
+pub(crate) struct TextureCache<T> {
+    image: Option<u64>,
+    levels: Vec<CachedTexture<T>>,
+}
+
+impl<T> Default for TextureCache<T> {
+    fn default() -> Self {
+        TextureCache {
+            image: None,
+            levels: Vec::new(),
+        }
+    }
+}
+
+impl<T: Clone> TextureCache<T> {
+    /// The texture of mip `level` of `id`, a `size` image: cached, updated
+    /// in place with `rewrite` when only the frame changed, or made with
+    /// `upload`.
+    pub(crate) fn get(
+        &mut self,
+        id: ImageId,
+        level: u8,
+        size: (u32, u32),
+        upload: impl FnOnce() -> T,
+        rewrite: impl FnOnce(&T),
+    ) -> T {
+        if self.image != Some(id.image) {
+            self.levels.clear();
+            self.image = Some(id.image);
+        }
+        match self.levels.iter_mut().find(|c| c.level == level) {
+            Some(cached) if cached.size == size => {
+                if cached.frame != id.frame {
+                    rewrite(&cached.texture);
+                    cached.frame = id.frame;
+                }
+                cached.texture.clone()
+            }
+            Some(cached) => {
+                cached.texture = upload();
+                cached.frame = id.frame;
+                cached.size = size;
+                cached.texture.clone()
+            }
+            None => {
+                let texture = upload();
+                self.levels.push(CachedTexture {
+                    level,
+                    frame: id.frame,
+                    size,
+                    texture: texture.clone(),
+                });
+                texture
+            }
+        }
+    }
 }
 
 // ─── Per-frame GPU operations ─────────────────────────────────────────────────
 
+/// A texture holding the `(x, y, w, h)` crop of `img`, which is mip `level`
+/// of the image `id`.
+///
+/// With an `id`, the whole of `img` stays on the GPU in
+/// [`GpuContext::textures`] and the crop is a GPU-side copy. Without one, or
+/// when `img` exceeds the device's texture size limit, only the crop is
+/// uploaded.
+pub(crate) fn source_texture(
+    ctx: &GpuContext,
+    id: Option<ImageId>,
+    level: u8,
+    img: &DynamicImage,
+    (x, y, w, h): (u32, u32, u32, u32),
+) -> wgpu::Texture {
+    let max = ctx.device.limits().max_texture_dimension_2d;
+    let Some(id) = id.filter(|_| img.width() <= max && img.height() <= max) else {
+        return upload_texture(&ctx.device, &ctx.queue, &img.crop_imm(x, y, w, h));
+    };
+
+    let full = ctx.textures.borrow_mut().get(
+        id,
+        level,
+        (img.width(), img.height()),
+        || upload_texture(&ctx.device, &ctx.queue, img),
+        |texture| write_image(&ctx.queue, texture, img),
+    );
+    crop_texture(ctx, &full, (x, y, w, h))
+}
+
+/// The `(x, y, w, h)` region of `src`, which is returned itself when the
+/// region covers all of it.
+fn crop_texture(
+    ctx: &GpuContext,
+    src: &wgpu::Texture,
+    (x, y, w, h): (u32, u32, u32, u32),
+) -> wgpu::Texture {
+    if (x, y, w, h) == (0, 0, src.width(), src.height()) {
+        return src.clone();
+    }
+
+    let crop = make_texture(
+        &ctx.device,
+        w,
+        h,
+        wgpu::TextureFormat::Rgba8Unorm,
+        wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_SRC
+            | wgpu::TextureUsages::COPY_DST,
+    );
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.copy_texture_to_texture(
+        wgpu::TexelCopyTextureInfo {
+            texture: src,
+            mip_level: 0,
+            origin: wgpu::Origin3d { x, y, z: 0 },
+            aspect: wgpu::TextureAspect::All,
+        },
+        crop.as_image_copy(),
+        crop.size(),
+    );
+    ctx.queue.submit(std::iter::once(encoder.finish()));
+    crop
+}
+
 /// Upload a [`DynamicImage`] to a GPU texture (`Rgba8Unorm`).
 pub(crate) fn upload_texture(
     device: &wgpu::Device,
     queue: &wgpu::Queue,
     img: &DynamicImage,
 ) -> wgpu::Texture {
-    let rgba = img.to_rgba8();
-    let (width, height) = rgba.dimensions();
-
     let texture = make_texture(
         device,
-        width,
-        height,
+        img.width(),
+        img.height(),
         wgpu::TextureFormat::Rgba8Unorm,
-        wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_DST
+            | wgpu::TextureUsages::COPY_SRC,
     );
+    write_image(queue, &texture, img);
+    texture
+}
+
+/// Overwrite `texture` with `img`, which must have the texture's size.
+fn write_image(queue: &wgpu::Queue, texture: &wgpu::Texture, img: &DynamicImage) {
+    let rgba: Cow<'_, RgbaImage> = match img.as_rgba8() {
+        Some(rgba) => Cow::Borrowed(rgba),
+        None => Cow::Owned(img.to_rgba8()),
+    };
+    let (width, height) = rgba.dimensions();
 
     queue.write_texture(
         wgpu::TexelCopyTextureInfo {
-            texture: &texture,
+            texture,
             mip_level: 0,
             origin: wgpu::Origin3d::ZERO,
             aspect: wgpu::TextureAspect::All,
@@ -315,16 +551,15 @@ pub(crate) fn upload_texture(
             depth_or_array_layers: 1,
         },
     );
-
-    texture
 }
 
-/// Resize `src` to `(dst_w, dst_h)` then optionally rotate by `rotation` degrees.
+/// Resize `src` to `(dst_w, dst_h)` then optionally rotate by `rotation` degrees
+/// and mirror the rotated result.
 ///
 /// `rotation` must be a multiple of 90; any other value is treated as 0.
-/// Dispatches resize to the appropriate GPU path:
-/// - `Lanczos3` / `CatmullRom` → two-pass separable kernel
-/// - All others → sampler-based blit (nearest or bilinear)
+/// The resize follows `resize`: a copy at 1:1, a sampler-based blit for
+/// `Nearest`, and the two-pass separable kernel for every other filter.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn resize_blit(
     ctx: &GpuContext,
     src: &wgpu::Texture,
@@ -332,31 +567,260 @@ pub(crate) fn resize_blit(
     dst_h: u32,
     filter: FilterMethod,
     rotation: u16,
+    flip_h: bool,
+    flip_v: bool,
 ) -> wgpu::Texture {
-    let resized = match filter {
-        FilterMethod::Lanczos3 => resize_kernel_two_pass(
-            ctx,
-            src,
-            dst_w,
-            dst_h,
-            &ctx.pipelines.lanczos3_h,
-            &ctx.pipelines.lanczos3_v,
-        ),
-        FilterMethod::CatmullRom => resize_kernel_two_pass(
-            ctx,
-            src,
-            dst_w,
-            dst_h,
-            &ctx.pipelines.catmull_h,
-            &ctx.pipelines.catmull_v,
-        ),
-        _ => resize_sampler(ctx, src, dst_w, dst_h, filter),
+    let map = Mapping::stretch((src.width(), src.height()), dst_w, dst_h);
+    let resized = resize(ctx, src, dst_w, dst_h, filter, map);
+    orient(ctx, resized, rotation, flip_h, flip_v)
+}
+
+/// [`resize_blit`] for the `(x, y, w, h)` crop of `img` when it exceeds the
+/// device's texture size limit.
+///
+/// The crop is resized in tiles planned by [`plan_tiles`]: each tile uploads
+/// only the source pixels under its kernel taps and samples them exactly
+/// where a single resize of the whole crop would, so the composited result
+/// has no seams. If even one output pixel's taps exceed the limit, which
+/// takes a downscale by thousands, nearest-neighbour sampling is used.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn resize_blit_tiled(
+    ctx: &GpuContext,
+    img: &DynamicImage,
+    (x, y, w, h): (u32, u32, u32, u32),
+    dst_w: u32,
+    dst_h: u32,
+    filter: FilterMethod,
+    rotation: u16,
+    flip_h: bool,
+    flip_v: bool,
+) -> wgpu::Texture {
+    let max = ctx.device.limits().max_texture_dimension_2d;
+    let resized = resize_in_tiles(ctx, img, (x, y, w, h), dst_w, dst_h, filter, max);
+    orient(ctx, resized, rotation, flip_h, flip_v)
+}
+
+/// Resize the `(x, y, w, h)` crop of `img` to `dst_w × dst_h` in tiles that
+/// read at most `max` source pixels per side.
+pub(crate) fn resize_in_tiles(
+    ctx: &GpuContext,
+    img: &DynamicImage,
+    (x, y, w, h): (u32, u32, u32, u32),
+    dst_w: u32,
+    dst_h: u32,
+    filter: FilterMethod,
+    max: u32,
+) -> wgpu::Texture {
+    let (filter, tiles) = match plan_tiles((w, h), (dst_w, dst_h), filter, max) {
+        Some(tiles) => (filter, tiles),
+        None => {
+            tracing::debug!(?filter, "kernel too wide to tile; using nearest neighbour");
+            let tiles = plan_tiles((w, h), (dst_w, dst_h), FilterMethod::Nearest, max)
+                .expect("nearest-neighbour taps span a few pixels");
+            (FilterMethod::Nearest, tiles)
+        }
     };
 
-    if rotation.is_multiple_of(360) {
+    let composite = make_texture(
+        &ctx.device,
+        dst_w,
+        dst_h,
+        wgpu::TextureFormat::Rgba8Unorm,
+        wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::COPY_SRC
+            | wgpu::TextureUsages::COPY_DST
+            | wgpu::TextureUsages::TEXTURE_BINDING,
+    );
+    let ratio = [w as f32 / dst_w as f32, h as f32 / dst_h as f32];
+    for tile in &tiles {
+        let src = upload_texture(
+            &ctx.device,
+            &ctx.queue,
+            &img.crop_imm(x + tile.src.x, y + tile.src.y, tile.src.w, tile.src.h),
+        );
+        let map = Mapping {
+            ratio,
+            origin: [
+                tile.dst.x as f32 * ratio[0] - tile.src.x as f32,
+                tile.dst.y as f32 * ratio[1] - tile.src.y as f32,
+            ],
+        };
+        let part = resize(ctx, &src, tile.dst.w, tile.dst.h, filter, map);
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_texture_to_texture(
+            part.as_image_copy(),
+            wgpu::TexelCopyTextureInfo {
+                texture: &composite,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: tile.dst.x,
+                    y: tile.dst.y,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            part.size(),
+        );
+        ctx.queue.submit(std::iter::once(encoder.finish()));
+    }
+    composite
+}
+
+/// Part of a resize too large for one texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Tile {
+    /// Destination pixels the tile produces.
+    pub(crate) dst: Rect,
+    /// Source pixels it reads, including the kernel overlap with its
+    /// neighbours.
+    pub(crate) src: Rect,
+}
+
+/// Split resizing a `src` sized image to `dst` with `filter` into tiles that
+/// each read at most `max` source pixels per side, or `None` if the taps of
+/// a single output pixel already exceed that.
+pub(crate) fn plan_tiles(
+    src: (u32, u32),
+    dst: (u32, u32),
+    filter: FilterMethod,
+    max: u32,
+) -> Option<Vec<Tile>> {
+    let cols = axis_spans(src.0, dst.0, filter, max)?;
+    let rows = axis_spans(src.1, dst.1, filter, max)?;
+    let tiles = rows
+        .iter()
+        .flat_map(|&((dy, dh), (sy, sh))| {
+            cols.iter().map(move |&((dx, dw), (sx, sw))| Tile {
+                dst: Rect {
+                    x: dx,
+                    y: dy,
+                    w: dw,
+                    h: dh,
+                },
+                src: Rect {
+                    x: sx,
+                    y: sy,
+                    w: sw,
+                    h: sh,
+                },
+            })
+        })
+        .collect();
+    Some(tiles)
+}
+
+/// Radius in source pixels of the taps of `filter` when resizing by `ratio`
+/// source pixels per output pixel, matching the kernel shaders.
+fn tap_radius(filter: FilterMethod, ratio: f32) -> f32 {
+    match filter {
+        // The sampler reads the nearest texel.
+        FilterMethod::Nearest => 1.0,
+        _ => filter.support() * ratio.max(1.0),
+    }
+}
+
+/// [`plan_tiles`] along one axis: `((dst_start, dst_len), (src_start,
+/// src_len))` spans, each as long as the source limit allows.
+#[allow(clippy::type_complexity)]
+fn axis_spans(
+    src_len: u32,
+    dst_len: u32,
+    filter: FilterMethod,
+    max: u32,
+) -> Option<Vec<((u32, u32), (u32, u32))>> {
+    let ratio = src_len as f32 / dst_len as f32;
+    let radius = tap_radius(filter, ratio);
+    // Source pixels read by output pixels `d0..d1`, with a pixel of slack on
+    // each side for rounding in the shaders.
+    let reads = |d0: u32, d1: u32| {
+        let lo = ((d0 as f32 + 0.5) * ratio - radius).floor() - 1.0;
+        let hi = ((d1 as f32 - 0.5) * ratio + radius).ceil() + 2.0;
+        let lo = lo.clamp(0.0, (src_len - 1) as f32) as u32;
+        let hi = (hi.max(0.0) as u32).clamp(lo + 1, src_len);
+        (lo, hi - lo)
+    };
+
+    let mut spans = Vec::new();
+    let mut d0 = 0;
+    while d0 < dst_len {
+        let fits = |d1| reads(d0, d1).1 <= max;
+        if !fits(d0 + 1) {
+            return None;
+        }
+        // The source span grows with the tile: find the longest that fits.
+        let (mut good, mut bad) = (d0 + 1, dst_len + 1);
+        while bad - good > 1 {
+            let mid = good + (bad - good) / 2;
+            if fits(mid) {
+                good = mid;
+            } else {
+                bad = mid;
+            }
+        }
+        spans.push(((d0, good - d0), reads(d0, good)));
+        d0 = good;
+    }
+    Some(spans)
+}
+
+/// Where the output pixels of a resize sample its source texture.
+#[derive(Debug, Clone, Copy)]
+struct Mapping {
+    /// Source pixels per output pixel.
+    ratio: [f32; 2],
+    /// Source position of the output's top-left corner.
+    origin: [f32; 2],
+}
+
+impl Mapping {
+    /// Stretch a whole `src` sized texture over `dst_w × dst_h`.
+    fn stretch((src_w, src_h): (u32, u32), dst_w: u32, dst_h: u32) -> Mapping {
+        Mapping {
+            ratio: [src_w as f32 / dst_w as f32, src_h as f32 / dst_h as f32],
+            origin: [0.0; 2],
+        }
+    }
+}
+
+/// Resize `src` to `(dst_w, dst_h)` following `map`:
+/// - 1:1 → copy, as on the CPU path; Gaussian and Mitchell would blur
+/// - `Nearest` → sampler-based blit
+/// - All others → two-pass separable kernel
+fn resize(
+    ctx: &GpuContext,
+    src: &wgpu::Texture,
+    dst_w: u32,
+    dst_h: u32,
+    filter: FilterMethod,
+    map: Mapping,
+) -> wgpu::Texture {
+    if map.ratio == [1.0; 2] {
+        // Tiles of a 1:1 resize start at whole source pixels.
+        let [x, y] = map.origin.map(|o| o as u32);
+        return crop_texture(ctx, src, (x, y, dst_w, dst_h));
+    }
+    match ctx.pipelines.kernel(filter) {
+        Some(kernel) => resize_kernel_two_pass(ctx, src, dst_w, dst_h, map, kernel),
+        None => resize_sampler(ctx, src, dst_w, dst_h, filter, map),
+    }
+}
+
+/// Rotate and mirror a resized texture, or return it as is when neither
+/// applies.
+fn orient(
+    ctx: &GpuContext,
+    resized: wgpu::Texture,
+    rotation: u16,
+    flip_h: bool,
+    flip_v: bool,
+) -> wgpu::Texture {
+    if rotation.is_multiple_of(360) && !flip_h && !flip_v {
         resized
     } else {
-        rotate_texture(ctx, &resized, rotation)
+        rotate_texture(ctx, &resized, rotation, flip_h, flip_v)
     }
 }
 
@@ -433,7 +897,8 @@ pub(crate) fn readback(ctx: &GpuContext, tex: &wgpu::Texture, w: u32, h: u32) ->
     out
 }
 
-/// Rotate `src` by `rotation` degrees (must be 90, 180, or 270; others → identity).
+/// Rotate `src` by `rotation` degrees (must be 90, 180, or 270; others → identity),
+/// then mirror the rotated result horizontally and/or vertically.
 ///
 /// For 90° and 270° the output texture dimensions are swapped relative to `src`.
 /// Uses a pixel-exact `textureLoad` shader — no sampler blur.
@@ -441,6 +906,8 @@ pub(crate) fn rotate_texture(
     ctx: &GpuContext,
     src: &wgpu::Texture,
     rotation: u16,
+    flip_h: bool,
+    flip_v: bool,
 ) -> wgpu::Texture {
     let src_w = src.width();
     let src_h = src.height();
@@ -451,6 +918,7 @@ pub(crate) fn rotate_texture(
         270 => 3u32,
         _ => 0u32,
     };
+    let flip_code = u32::from(flip_h) | (u32::from(flip_v) << 1);
     let (out_w, out_h) = if rot_code == 1 || rot_code == 3 {
         (src_h, src_w)
     } else {
@@ -471,6 +939,7 @@ pub(crate) fn rotate_texture(
     ub[0..4].copy_from_slice(&src_w.to_ne_bytes());
     ub[4..8].copy_from_slice(&src_h.to_ne_bytes());
     ub[8..12].copy_from_slice(&rot_code.to_ne_bytes());
+    ub[12..16].copy_from_slice(&flip_code.to_ne_bytes());
 
     let bind_group = uniform_bind_group(ctx, src, &ub);
     let dst_view = dst.create_view(&wgpu::TextureViewDescriptor::default());
@@ -486,6 +955,7 @@ fn resize_sampler(
     dst_w: u32,
     dst_h: u32,
     filter: FilterMethod,
+    map: Mapping,
 ) -> wgpu::Texture {
     let filter_mode = match filter {
         FilterMethod::Nearest => wgpu::FilterMode::Nearest,
@@ -507,12 +977,57 @@ fn resize_sampler(
         min_filter: filter_mode,
         ..Default::default()
     });
-    let src_view = src.create_view(&wgpu::TextureViewDescriptor::default());
     let dst_view = dst.create_view(&wgpu::TextureViewDescriptor::default());
 
-    let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+    // The mapping in normalised texture coordinates.
+    let (src_w, src_h) = (src.width() as f32, src.height() as f32);
+    let origin = [map.origin[0] / src_w, map.origin[1] / src_h];
+    let extent = [
+        dst_w as f32 * map.ratio[0] / src_w,
+        dst_h as f32 * map.ratio[1] / src_h,
+    ];
+    let bind_group = sampler_bind_group(
+        &ctx.device,
+        &ctx.queue,
+        &ctx.pipelines.sampler_bgl,
+        src,
+        &sampler,
+        origin,
+        extent,
+    );
+
+    draw_fullscreen(ctx, &ctx.pipelines.blit, &bind_group, &dst_view);
+    dst
+}
+
+/// Bind `src` and `sampler` for blit.wgsl, which stretches texture
+/// coordinates `origin..origin + extent` over the whole target.
+#[allow(clippy::too_many_arguments)]
+fn sampler_bind_group(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    layout: &wgpu::BindGroupLayout,
+    src: &wgpu::Texture,
+    sampler: &wgpu::Sampler,
+    origin: [f32; 2],
+    extent: [f32; 2],
+) -> wgpu::BindGroup {
+    let mut ub = [0u8; 16];
+    for (chunk, v) in ub.chunks_exact_mut(4).zip(origin.into_iter().chain(extent)) {
+        chunk.copy_from_slice(&v.to_ne_bytes());
+    }
+    let uniform_buf = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: ub.len() as u64,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    queue.write_buffer(&uniform_buf, 0, &ub);
+
+    let src_view = src.create_view(&wgpu::TextureViewDescriptor::default());
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
         label: None,
-        layout: &ctx.pipelines.sampler_bgl,
+        layout,
         entries: &[
             wgpu::BindGroupEntry {
                 binding: 0,
@@ -520,13 +1035,18 @@ fn resize_sampler(
             },
             wgpu::BindGroupEntry {
                 binding: 1,
-                resource: wgpu::BindingResource::Sampler(&sampler),
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &uniform_buf,
+                    offset: 0,
+                    size: None,
+                }),
             },
         ],
-    });
-
-    draw_fullscreen(ctx, &ctx.pipelines.blit, &bind_group, &dst_view);
-    dst
+    })
 }
 
 /// Two-pass separable kernel resize using pre-compiled pipelines.
@@ -538,42 +1058,43 @@ fn resize_kernel_two_pass(
     src: &wgpu::Texture,
     dst_w: u32,
     dst_h: u32,
-    h_pipeline: &wgpu::RenderPipeline,
-    v_pipeline: &wgpu::RenderPipeline,
+    map: Mapping,
+    kernel: &KernelPipelines,
 ) -> wgpu::Texture {
-    let src_w = src.width();
-    let src_h = src.height();
-
+    let horizontal = Mapping {
+        ratio: [map.ratio[0], 1.0],
+        origin: [map.origin[0], 0.0],
+    };
+    // The intermediate holds linear values in linear-light mode: the
+    // horizontal pass decodes its sRGB source and the vertical pass encodes
+    // its output.
     let intermediate = run_kernel_pass(
         ctx,
         src,
-        src_w,
-        src_h,
-        dst_w,
-        src_h,
+        (dst_w, src.height()),
+        horizontal,
         wgpu::TextureFormat::Rgba16Float,
-        h_pipeline,
+        &kernel.h,
     );
+    let vertical = Mapping {
+        ratio: [1.0, map.ratio[1]],
+        origin: [0.0, map.origin[1]],
+    };
     run_kernel_pass(
         ctx,
         &intermediate,
-        dst_w,
-        src_h,
-        dst_w,
-        dst_h,
+        (dst_w, dst_h),
+        vertical,
         wgpu::TextureFormat::Rgba8Unorm,
-        v_pipeline,
+        &kernel.v,
     )
 }
 
-#[allow(clippy::too_many_arguments)]
 fn run_kernel_pass(
     ctx: &GpuContext,
     src: &wgpu::Texture,
-    src_w: u32,
-    src_h: u32,
-    dst_w: u32,
-    dst_h: u32,
+    (dst_w, dst_h): (u32, u32),
+    map: Mapping,
     out_format: wgpu::TextureFormat,
     pipeline: &wgpu::RenderPipeline,
 ) -> wgpu::Texture {
@@ -587,11 +1108,17 @@ fn run_kernel_pass(
             | wgpu::TextureUsages::TEXTURE_BINDING,
     );
 
-    let mut ub = [0u8; 16];
-    ub[0..4].copy_from_slice(&src_w.to_ne_bytes());
-    ub[4..8].copy_from_slice(&src_h.to_ne_bytes());
+    // Padded to 48 bytes, the size of the struct under std140 rules.
+    let mut ub = [0u8; 48];
+    ub[0..4].copy_from_slice(&src.width().to_ne_bytes());
+    ub[4..8].copy_from_slice(&src.height().to_ne_bytes());
     ub[8..12].copy_from_slice(&dst_w.to_ne_bytes());
     ub[12..16].copy_from_slice(&dst_h.to_ne_bytes());
+    let floats = map.ratio.into_iter().chain(map.origin);
+    for (chunk, v) in ub[16..32].chunks_exact_mut(4).zip(floats) {
+        chunk.copy_from_slice(&v.to_ne_bytes());
+    }
+    ub[32..36].copy_from_slice(&u32::from(ctx.linear_light).to_ne_bytes());
 
     let bind_group = uniform_bind_group(ctx, src, &ub);
     let dst_view = dst.create_view(&wgpu::TextureViewDescriptor::default());
@@ -599,15 +1126,15 @@ fn run_kernel_pass(
     dst
 }
 
-/// Create a bind group for shaders that take a texture + 16-byte uniform buffer.
+/// Create a bind group for shaders that take a texture + uniform buffer.
 fn uniform_bind_group(
     ctx: &GpuContext,
     src: &wgpu::Texture,
-    uniform_data: &[u8; 16],
+    uniform_data: &[u8],
 ) -> wgpu::BindGroup {
     let uniform_buf = ctx.device.create_buffer(&wgpu::BufferDescriptor {
         label: None,
-        size: 16,
+        size: uniform_data.len() as u64,
         usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         mapped_at_creation: false,
     });
@@ -818,27 +1345,7 @@ impl GpuContext {
 
             let pipelines = GpuPipelines::new(&device);
 
-            let present_bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("present_bgl"),
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                            view_dimension: wgpu::TextureViewDimension::D2,
-                            multisampled: false,
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                ],
-            });
+            let present_bgl = sampler_bind_group_layout(&device, "present_bgl");
             let present_pipeline = make_pipeline(
                 &device,
                 &present_bgl,
@@ -859,6 +1366,8 @@ impl GpuContext {
                 device,
                 queue,
                 pipelines,
+                textures: RefCell::default(),
+                linear_light: false,
                 swapchain: Some(swapchain),
             })
         })
@@ -878,16 +1387,17 @@ impl GpuContext {
     ///
     /// On `wgpu::SurfaceError::Outdated` the swapchain is reconfigured and the
     /// frame is skipped. All other errors are returned to the caller.
-    pub fn render_and_present(
+    pub fn render_and_present<'a>(
         &mut self,
-        src: &image::DynamicImage,
+        src: impl Into<super::Source<'a>>,
         viewport: &crate::viewport::ViewportState,
         dst_w: u32,
         dst_h: u32,
         filter: super::FilterMethod,
     ) -> Result<(), PresentError> {
-        let scaled_w = ((src.width() as f32) * viewport.scale).max(1.0) as u32;
-        let scaled_h = ((src.height() as f32) * viewport.scale).max(1.0) as u32;
+        let src = src.into();
+        let scaled_w = ((src.image.width() as f32) * viewport.scale).max(1.0) as u32;
+        let scaled_h = ((src.image.height() as f32) * viewport.scale).max(1.0) as u32;
 
         let rendered = super::gpu_render_inner(
             src, viewport, dst_w, dst_h, scaled_w, scaled_h, filter, self,
@@ -949,7 +1459,6 @@ impl GpuContext {
             if let Some((out_tex, win_x, win_y)) = rendered {
                 let vis_w = out_tex.width();
                 let vis_h = out_tex.height();
-                let src_view = out_tex.create_view(&wgpu::TextureViewDescriptor::default());
                 let sampler = self.device.create_sampler(&wgpu::SamplerDescriptor {
                     mag_filter: wgpu::FilterMode::Linear,
                     min_filter: wgpu::FilterMode::Linear,
@@ -957,20 +1466,15 @@ impl GpuContext {
                 });
                 // Re-borrow sc for pipeline/bgl after frame acquisition.
                 let sc = self.swapchain.as_ref().unwrap();
-                let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
-                    label: None,
-                    layout: &sc.present_bgl,
-                    entries: &[
-                        wgpu::BindGroupEntry {
-                            binding: 0,
-                            resource: wgpu::BindingResource::TextureView(&src_view),
-                        },
-                        wgpu::BindGroupEntry {
-                            binding: 1,
-                            resource: wgpu::BindingResource::Sampler(&sampler),
-                        },
-                    ],
-                });
+                let bind_group = sampler_bind_group(
+                    &self.device,
+                    &self.queue,
+                    &sc.present_bgl,
+                    &out_tex,
+                    &sampler,
+                    [0.0; 2],
+                    [1.0; 2],
+                );
 
                 rpass.set_pipeline(&sc.present_pipeline);
                 rpass.set_bind_group(0, &bind_group, &[]);
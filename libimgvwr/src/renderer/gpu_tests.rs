@@ -0,0 +1,274 @@
+#![cfg(any(feature = "gpu-vulkan", feature = "gpu-gles"))]
+
+use std::cell::Cell;
+
+use image::{DynamicImage, ImageBuffer, Rgba};
+
+use super::frame::Rect;
+use super::gpu::{TextureCache, plan_tiles};
+use super::{FilterMethod, ImageId, gpu, resample, visible_source_crop};
+
+/// Map a displayed pixel back to the source pixel it shows at scale 1,
+/// following the rotate.wgsl / image-rs conventions.
+fn display_to_source(
+    (x, y): (u32, u32),
+    rotation: u16,
+    flip_h: bool,
+    flip_v: bool,
+    (sw, sh): (u32, u32),
+) -> (u32, u32) {
+    let (rw, rh) = if rotation % 180 == 90 {
+        (sh, sw)
+    } else {
+        (sw, sh)
+    };
+    let x = if flip_h { rw - 1 - x } else { x };
+    let y = if flip_v { rh - 1 - y } else { y };
+    match rotation {
+        90 => (y, sh - 1 - x),
+        180 => (sw - 1 - x, sh - 1 - y),
+        270 => (sw - 1 - y, x),
+        _ => (x, y),
+    }
+}
+
+#[test]
+fn crop_covers_visible_source_for_every_rotation_and_flip() {
+    let (sw, sh) = (5u32, 3u32);
+    for rotation in [0u16, 90, 180, 270] {
+        for flip_h in [false, true] {
+            for flip_v in [false, true] {
+                let (rw, rh) = if rotation % 180 == 90 {
+                    (sh, sw)
+                } else {
+                    (sw, sh)
+                };
+                // A visible window touching one corner of the displayed image.
+                let (x0, y0, x1, y1) = (0, 1, rw - 1, rh);
+                let (vis_w, vis_h) = (x1 - x0, y1 - y0);
+                let got = visible_source_crop(
+                    rotation, flip_h, flip_v, x0, y0, x1, y1, 1.0, sw, sh, sw, sh,
+                );
+
+                let (mut min_x, mut min_y, mut max_x, mut max_y) = (u32::MAX, u32::MAX, 0, 0);
+                for y in y0..y1 {
+                    for x in x0..x1 {
+                        let (px, py) =
+                            display_to_source((x, y), rotation, flip_h, flip_v, (sw, sh));
+                        min_x = min_x.min(px);
+                        min_y = min_y.min(py);
+                        max_x = max_x.max(px);
+                        max_y = max_y.max(py);
+                    }
+                }
+                let (resize_w, resize_h) = if rotation % 180 == 90 {
+                    (vis_h, vis_w)
+                } else {
+                    (vis_w, vis_h)
+                };
+                let expected = (
+                    min_x,
+                    min_y,
+                    max_x - min_x + 1,
+                    max_y - min_y + 1,
+                    resize_w,
+                    resize_h,
+                );
+                assert_eq!(
+                    got, expected,
+                    "rotation={rotation} flip_h={flip_h} flip_v={flip_v}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn textures_are_reused_rewritten_and_dropped_per_image() {
+    let uploads = Cell::new(0);
+    let rewrites = Cell::new(0);
+    let mut cache = TextureCache::default();
+    let mut get = |image, frame, level, size| {
+        cache.get(
+            ImageId { image, frame },
+            level,
+            size,
+            || {
+                uploads.set(uploads.get() + 1);
+                uploads.get()
+            },
+            |_| rewrites.set(rewrites.get() + 1),
+        )
+    };
+
+    // Pans and zooms reuse the uploaded level.
+    assert_eq!(get(7, 0, 0, (4, 4)), 1);
+    assert_eq!(get(7, 0, 0, (4, 4)), 1);
+    // Another mip level is kept alongside it.
+    assert_eq!(get(7, 0, 1, (2, 2)), 2);
+    assert_eq!(get(7, 0, 0, (4, 4)), 1);
+    // The next animation frame rewrites the same texture.
+    assert_eq!(get(7, 1, 0, (4, 4)), 1);
+    assert_eq!((uploads.get(), rewrites.get()), (2, 1));
+
+    // A new image drops the old textures.
+    assert_eq!(get(8, 0, 0, (4, 4)), 3);
+    assert_eq!(get(7, 0, 0, (4, 4)), 4);
+    // A frame of another size gets a new texture.
+    assert_eq!(get(7, 1, 0, (5, 4)), 5);
+    assert_eq!((uploads.get(), rewrites.get()), (5, 1));
+}
+
+/// Source pixels the shaders read for output pixel `d` along one axis.
+fn taps(filter: FilterMethod, src: u32, dst: u32, d: u32) -> (u32, u32) {
+    let ratio = src as f32 / dst as f32;
+    let c = (d as f32 + 0.5) * ratio;
+    let r = match filter {
+        FilterMethod::Nearest => 1.0,
+        _ => filter.support() * ratio.max(1.0),
+    };
+    let clamp = |v: f32| v.clamp(0.0, (src - 1) as f32) as u32;
+    (clamp((c - r).floor()), clamp((c + r).ceil() - 1.0))
+}
+
+#[test]
+fn tiles_cover_the_output_and_hold_their_taps() {
+    let cases = [
+        ((5000, 300), (1200, 700), FilterMethod::Lanczos3),
+        ((5000, 3000), (640, 480), FilterMethod::CatmullRom),
+        ((9000, 200), (9000, 200), FilterMethod::Triangle),
+        ((2000, 2000), (300, 300), FilterMethod::Mitchell),
+        ((3000, 2500), (250, 700), FilterMethod::Lanczos4),
+        ((3000, 1000), (700, 333), FilterMethod::Box),
+        ((3000, 3000), (4000, 4000), FilterMethod::Nearest),
+    ];
+    let max = 1024;
+    for (src, dst, filter) in cases {
+        let tiles = plan_tiles(src, dst, filter, max).unwrap();
+        let mut covered = vec![0u8; (dst.0 * dst.1) as usize];
+        for t in &tiles {
+            assert!(t.src.w <= max && t.src.h <= max, "{filter:?} {t:?}");
+            assert!(t.src.x + t.src.w <= src.0 && t.src.y + t.src.h <= src.1);
+            for y in t.dst.y..t.dst.y + t.dst.h {
+                for x in t.dst.x..t.dst.x + t.dst.w {
+                    covered[(y * dst.0 + x) as usize] += 1;
+                }
+            }
+            for (d0, dl, s0, sl, s, d) in [
+                (t.dst.x, t.dst.w, t.src.x, t.src.w, src.0, dst.0),
+                (t.dst.y, t.dst.h, t.src.y, t.src.h, src.1, dst.1),
+            ] {
+                for p in d0..d0 + dl {
+                    let (lo, hi) = taps(filter, s, d, p);
+                    assert!(lo >= s0 && hi < s0 + sl, "{filter:?} {t:?} pixel {p}");
+                }
+            }
+        }
+        assert!(covered.iter().all(|&n| n == 1), "{filter:?}");
+    }
+}
+
+#[test]
+fn kernels_wider_than_the_limit_cannot_be_tiled() {
+    // Lanczos3 shrinking 1000× reads 6000 source pixels per output pixel.
+    assert_eq!(
+        plan_tiles((100_000, 10), (100, 10), FilterMethod::Lanczos3, 4096),
+        None
+    );
+    assert!(plan_tiles((100_000, 10), (100, 10), FilterMethod::Nearest, 4096).is_some());
+}
+
+const KERNELS: [FilterMethod; 10] = [
+    FilterMethod::Box,
+    FilterMethod::Triangle,
+    FilterMethod::SharpBilinear,
+    FilterMethod::Hermite,
+    FilterMethod::CatmullRom,
+    FilterMethod::Mitchell,
+    FilterMethod::Gaussian,
+    FilterMethod::Lanczos2,
+    FilterMethod::Lanczos3,
+    FilterMethod::Lanczos4,
+];
+
+/// A GPU context. The tests that need one are `#[ignore]`d, as CI runners
+/// have no GPU; run them with `--ignored` where an adapter (e.g. Mesa's
+/// llvmpipe) is present, and a missing one fails them.
+fn context() -> gpu::GpuContext {
+    gpu::GpuContext::new().expect("GPU feature enabled but no usable adapter")
+}
+
+fn noise_61x47() -> DynamicImage {
+    DynamicImage::ImageRgba8(ImageBuffer::from_fn(61, 47, |x, y| {
+        let v = (x * 7919 + y * 104_729) ^ (x * y * 31);
+        Rgba([
+            v as u8,
+            (v >> 3) as u8,
+            (v >> 7) as u8,
+            128 + (v >> 2) as u8 % 128,
+        ])
+    }))
+}
+
+/// Largest per-channel difference between GPU ARGB8888 and CPU RGBA8.
+fn max_diff(argb: &[u8], rgba: &[u8]) -> u8 {
+    argb.chunks_exact(4)
+        .zip(rgba.chunks_exact(4))
+        .flat_map(|(g, c)| [g[2], g[1], g[0], g[3]].into_iter().zip(c.iter().copied()))
+        .map(|(g, c)| g.abs_diff(c))
+        .max()
+        .unwrap_or(0)
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn kernels_match_the_cpu_resampler() {
+    let ctx = context();
+    let src = noise_61x47();
+    for filter in KERNELS {
+        for (w, h) in [(23, 17), (61, 47), (150, 101), (40, 90), (183, 141)] {
+            let tex = gpu::upload_texture(&ctx.device, &ctx.queue, &src);
+            let out = gpu::resize_blit(&ctx, &tex, w, h, filter, 0, false, false);
+            let got = gpu::readback(&ctx, &out, w, h);
+            let want = resample::resize_window(&src, (w, h), Rect::full(w, h), filter, false, 1);
+            let diff = max_diff(&got, &want);
+            // Half-float intermediates and GPU rounding cost a level or two.
+            assert!(diff <= 2, "{filter:?} {w}x{h}: off by {diff}");
+        }
+    }
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn linear_light_kernels_match_the_cpu_resampler() {
+    let ctx = context().with_linear_light(true);
+    let src = noise_61x47();
+    for filter in KERNELS {
+        for (w, h) in [(23, 17), (150, 101)] {
+            let tex = gpu::upload_texture(&ctx.device, &ctx.queue, &src);
+            let out = gpu::resize_blit(&ctx, &tex, w, h, filter, 0, false, false);
+            let got = gpu::readback(&ctx, &out, w, h);
+            let want = resample::resize_window(&src, (w, h), Rect::full(w, h), filter, true, 1);
+            let diff = max_diff(&got, &want);
+            assert!(diff <= 2, "{filter:?} {w}x{h}: off by {diff}");
+        }
+    }
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn tiles_have_no_seams() {
+    let ctx = context();
+    let src = noise_61x47();
+    let crop = (3, 2, 55, 41);
+    for filter in KERNELS {
+        for (w, h) in [(20, 15), (130, 97)] {
+            let whole = gpu::resize_in_tiles(&ctx, &src, crop, w, h, filter, u32::MAX);
+            let tiled = gpu::resize_in_tiles(&ctx, &src, crop, w, h, filter, 32);
+            let whole = gpu::readback(&ctx, &whole, w, h);
+            let tiled = gpu::readback(&ctx, &tiled, w, h);
+            let diff = whole.iter().zip(&tiled).map(|(a, b)| a.abs_diff(*b)).max();
+            assert!(diff <= Some(1), "{filter:?} {w}x{h}: off by {diff:?}");
+        }
+    }
+}
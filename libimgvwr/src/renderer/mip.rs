@@ -0,0 +1,187 @@
+//! Mip pyramids for fast, alias-free zoom-out.
+//!
+//! Shrinking a large image makes every output pixel's kernel span many
+//! source pixels, so each frame costs time proportional to the whole source
+//! and any filter that undersamples it aliases. A [`MipPyramid`] holds
+//! successive 2×2 box-filtered halvings of an image; the renderer resamples
+//! from the smallest level that is still at least as large as the scaled
+//! image (see [`Source`](super::Source)), so the kernel never spans more than
+//! about two level pixels per output pixel.
+//!
+//! Levels are averaged in sRGB-encoded values or, for linear-light
+//! rendering, in linear light, to match how the renderer resamples them.
+//!
+//! [`PendingMips`] builds a pyramid on a background thread, so that loading
+//! an image is not delayed; frames drawn before it finishes use the full
+//! image. The thread does not wake anyone: callers check back while
+//! [`PendingMips::is_building`] and redraw once [`PendingMips::take_ready`]
+//! reports the pyramid.
+
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use image::{DynamicImage, RgbaImage};
+
+use super::srgb;
+
+/// Successive halvings of an image, largest first, down to 1×1.
+#[derive(Debug)]
+pub struct MipPyramid {
+    /// Level `i + 1`: the image halved `i + 1` times, as RGBA8.
+    levels: Vec<DynamicImage>,
+}
+
+impl MipPyramid {
+    /// Build every level of `src`, averaging colours in linear light if
+    /// `linear`.
+    pub fn build(src: &DynamicImage, linear: bool) -> MipPyramid {
+        MipPyramid::build_until(src, linear, || false)
+    }
+
+    /// Build levels of `src` until done or until `cancelled` returns `true`.
+    fn build_until(src: &DynamicImage, linear: bool, cancelled: impl Fn() -> bool) -> MipPyramid {
+        let base: Cow<'_, RgbaImage> = match src.as_rgba8() {
+            Some(rgba) => Cow::Borrowed(rgba),
+            None => Cow::Owned(src.to_rgba8()),
+        };
+        let mut levels = Vec::new();
+        let mut next = halve(&base, linear);
+        while let Some(level) = next {
+            if cancelled() {
+                break;
+            }
+            next = halve(&level, linear);
+            levels.push(DynamicImage::ImageRgba8(level));
+        }
+        MipPyramid { levels }
+    }
+
+    /// Number of levels below the full image.
+    pub fn len(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// `true` for a 1×1 image, which has no smaller levels.
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    /// The smallest level at least `w × h`, with its number (`1` for the
+    /// first halving), or `None` when only the full image is that large.
+    pub(crate) fn level_for(&self, (w, h): (u32, u32)) -> Option<(u8, &DynamicImage)> {
+        self.levels
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, level)| level.width() >= w && level.height() >= h)
+            .map(|(i, level)| (i as u8 + 1, level))
+    }
+}
+
+/// `src` halved along each axis longer than one pixel, averaging 2×2 (or
+/// 2×1) blocks, in linear light if `linear`; an odd last row or column is
+/// dropped. `None` for 1×1.
+fn halve(src: &RgbaImage, linear: bool) -> Option<RgbaImage> {
+    let (w, h) = src.dimensions();
+    if (w, h) == (1, 1) {
+        return None;
+    }
+    // Step and offset of the second sample along each axis.
+    let (sx, dx) = if w > 1 { (2, 1) } else { (1, 0) };
+    let (sy, dy) = if h > 1 { (2, 1) } else { (1, 0) };
+    let (out_w, out_h) = (w / sx, h / sy);
+
+    let stride = w as usize * 4;
+    let raw = src.as_raw();
+    let mut out = RgbaImage::new(out_w, out_h);
+    for (y, line) in out.chunks_exact_mut(out_w as usize * 4).enumerate() {
+        let top = &raw[y * sy as usize * stride..][..stride];
+        let bottom = &raw[(y * sy as usize + dy) * stride..][..stride];
+        for (x, px) in line.chunks_exact_mut(4).enumerate() {
+            let left = x * sx as usize * 4;
+            let right = left + dx * 4;
+            for (c, p) in px.iter_mut().enumerate() {
+                let block = [
+                    top[left + c],
+                    top[right + c],
+                    bottom[left + c],
+                    bottom[right + c],
+                ];
+                *p = if linear && c < 3 {
+                    srgb::from_linear(block.map(srgb::to_linear).iter().sum::<f32>() / 4.0)
+                } else {
+                    let sum: u16 = block.map(u16::from).iter().sum();
+                    ((sum + 2) / 4) as u8
+                };
+            }
+        }
+    }
+    Some(out)
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    pyramid: OnceLock<MipPyramid>,
+    cancelled: AtomicBool,
+}
+
+/// A [`MipPyramid`] being built on a background thread. Dropping it stops
+/// the build after the level in progress.
+#[derive(Debug)]
+pub struct PendingMips {
+    shared: Arc<Shared>,
+    /// Whether the worker thread started.
+    spawned: bool,
+    /// Whether [`take_ready`](Self::take_ready) has reported the pyramid.
+    reported: bool,
+}
+
+impl PendingMips {
+    /// Start building the pyramid of `src` on a new thread, as
+    /// [`MipPyramid::build`] does with `linear`.
+    pub fn spawn(src: Arc<DynamicImage>, linear: bool) -> PendingMips {
+        let shared = Arc::new(Shared::default());
+        let worker = Arc::clone(&shared);
+        let spawned = std::thread::Builder::new()
+            .name("mipmaps".into())
+            .spawn(move || {
+                let pyramid = MipPyramid::build_until(&src, linear, || {
+                    worker.cancelled.load(Ordering::Relaxed)
+                });
+                let _ = worker.pyramid.set(pyramid);
+            });
+        if let Err(e) = &spawned {
+            tracing::warn!(error = %e, "failed to start mipmap thread");
+        }
+        PendingMips {
+            shared,
+            spawned: spawned.is_ok(),
+            reported: false,
+        }
+    }
+
+    /// The pyramid, once it is complete.
+    pub fn get(&self) -> Option<&MipPyramid> {
+        self.shared.pyramid.get()
+    }
+
+    /// `true` while the pyramid is still being built.
+    pub fn is_building(&self) -> bool {
+        self.spawned && self.get().is_none()
+    }
+
+    /// `true` the first time this is called after the pyramid completed, so
+    /// that the caller redraws with it once.
+    pub fn take_ready(&mut self) -> bool {
+        let ready = !self.reported && self.get().is_some();
+        self.reported |= ready;
+        ready
+    }
+}
+
+impl Drop for PendingMips {
+    fn drop(&mut self) {
+        self.shared.cancelled.store(true, Ordering::Relaxed);
+    }
+}
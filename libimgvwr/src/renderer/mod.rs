@@ -2,132 +2,546 @@
 //! Wayland-compatible ARGB8888 pixel buffer.
 //!
 //! Without a GPU feature the pipeline is fully CPU-based:
-//! 1. Scale the source image to `(scaled_w, scaled_h)` using `imageops`.
-//! 2. Apply rotation if `viewport.rotation != 0`.
+//! 1. Scale the source image to `(scaled_w, scaled_h)`. Only the part that
+//!    lands on screen is resampled (see `resample`), with the same result as
+//!    `imageops::resize` over the whole image.
+//! 2. Apply rotation if `viewport.rotation != 0`, then the horizontal and
+//!    vertical flips from `viewport.flip_h` / `viewport.flip_v`.
 //! 3. Blit the result centred in a `dst_w × dst_h` buffer, offset by
 //!    `viewport.offset`. Pixels outside the destination rectangle stay black.
 //! 4. Convert each pixel from RGBA to little-endian ARGB8888
 //!    (`wl_shm_format::ARGB8888`).
 //!
+//! Steps 1 and 3–4 are split into row bands across worker threads (see
+//! `parallel`); the output does not depend on the thread count.
+//!
 //! With a GPU feature (`gpu-vulkan` or `gpu-gles`) the pipeline is:
 //! 1. Upload the source image to a GPU texture.
-//! 2. GPU resize + rotate → `Rgba8Unorm` texture.
+//! 2. GPU resize + rotate/flip → `Rgba8Unorm` texture.
 //! 3. Readback to CPU as ARGB8888 bytes.
 //! 4. CPU blit-center into the `dst_w × dst_h` output buffer.
+//!
+//! Both paths resample from the [`mip::MipPyramid`] level nearest above the
+//! scaled size when a [`Source`] carries one, so zooming out of a large image
+//! neither scans the whole source nor aliases.
+//!
+//! By default both paths filter the sRGB-encoded values as stored, like
+//! `imageops::resize`. `FrameBuffer::with_linear_light` on the CPU path and
+//! `GpuContext::with_linear_light` on the GPU path filter in linear light
+//! instead, which keeps downscaled high-contrast detail from darkening; the
+//! mip pyramid should then be built in linear light too.
+//!
+//! [`render_regions`] redraws only some rectangles of an existing buffer.
+//! [`frame::FrameBuffer`] builds incremental redraws with damage tracking on
+//! top of it and, on the CPU path, caches steps 1–2 so that panning only
+//! repeats the blit.
 
+#[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+mod cache;
+pub mod frame;
 #[cfg(any(feature = "gpu-vulkan", feature = "gpu-gles"))]
 pub mod gpu;
 #[cfg(test)]
+mod gpu_tests;
+pub mod mip;
+// GPU builds compile the CPU resampler for tests, as the reference output.
+#[cfg(any(test, not(any(feature = "gpu-vulkan", feature = "gpu-gles"))))]
+mod parallel;
+#[cfg(any(test, not(any(feature = "gpu-vulkan", feature = "gpu-gles"))))]
+mod resample;
+mod srgb;
+#[cfg(test)]
 mod tests;
 
-use image::{DynamicImage, imageops};
 #[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
-use image::{ImageBuffer, Rgba};
+use image::RgbaImage;
+use image::{DynamicImage, imageops};
 
 use crate::viewport::ViewportState;
+use frame::Rect;
+use mip::MipPyramid;
 
 /// Scaling filter applied during image resize.
 ///
-/// Maps 1-to-1 onto [`image::imageops::FilterType`]; exposed here so callers
+/// A superset of [`image::imageops::FilterType`]; exposed here so callers
 /// don't need to depend on `image` directly.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum FilterMethod {
     /// Nearest-neighbour — fastest, pixelated at high zoom.
     Nearest,
+    /// Box filter averaging the source area under each output pixel — exact
+    /// for downscaling, crisp when upscaling.
+    Box,
     /// Bilinear interpolation.
     Triangle,
+    /// Nearest-neighbour upscaling by a whole factor, then bilinear — square
+    /// pixels without shimmer, for pixel art.
+    SharpBilinear,
+    /// Hermite cubic — smooth like bilinear without its kinks, no ringing.
+    Hermite,
     /// Catmull-Rom cubic spline.
     CatmullRom,
+    /// Mitchell-Netravali cubic (B = C = 1/3) — softer than Catmull-Rom,
+    /// with less ringing.
+    Mitchell,
     /// Gaussian blur kernel.
     Gaussian,
+    /// Lanczos with window 2 — sharper than the cubics, little ringing.
+    Lanczos2,
     /// Lanczos with window 3 — best quality, slowest.
     #[default]
     Lanczos3,
+    /// Lanczos with window 4 — sharpest, with the most ringing.
+    Lanczos4,
 }
 
+impl FilterMethod {
+    /// Radius of the kernel in source pixels at 1:1 scale; it widens by the
+    /// downscaling ratio.
+    pub(crate) fn support(self) -> f32 {
+        match self {
+            FilterMethod::Nearest => 0.0,
+            FilterMethod::Box => 0.5,
+            FilterMethod::Triangle | FilterMethod::SharpBilinear | FilterMethod::Hermite => 1.0,
+            FilterMethod::CatmullRom | FilterMethod::Mitchell | FilterMethod::Lanczos2 => 2.0,
+            FilterMethod::Gaussian | FilterMethod::Lanczos3 => 3.0,
+            FilterMethod::Lanczos4 => 4.0,
+        }
+    }
+}
+
+/// Filters `image` lacks map onto the closest one it has.
 impl From<FilterMethod> for imageops::FilterType {
     fn from(f: FilterMethod) -> imageops::FilterType {
         match f {
             FilterMethod::Nearest => imageops::FilterType::Nearest,
-            FilterMethod::Triangle => imageops::FilterType::Triangle,
-            FilterMethod::CatmullRom => imageops::FilterType::CatmullRom,
+            FilterMethod::Box | FilterMethod::Triangle | FilterMethod::SharpBilinear => {
+                imageops::FilterType::Triangle
+            }
+            FilterMethod::Hermite | FilterMethod::CatmullRom | FilterMethod::Mitchell => {
+                imageops::FilterType::CatmullRom
+            }
             FilterMethod::Gaussian => imageops::FilterType::Gaussian,
-            FilterMethod::Lanczos3 => imageops::FilterType::Lanczos3,
+            FilterMethod::Lanczos2 | FilterMethod::Lanczos3 | FilterMethod::Lanczos4 => {
+                imageops::FilterType::Lanczos3
+            }
+        }
+    }
+}
+
+/// Identity of the pixels of a [`Source`]: a loaded image and one of its
+/// animation frames (`0` for still images).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageId {
+    /// Unique per loaded image; shared by all frames of an animation.
+    pub image: u64,
+    pub frame: usize,
+}
+
+/// An image to render, with its mip pyramid once one is built.
+///
+/// A `&DynamicImage` converts into a `Source` without a pyramid or identity.
+/// The GPU path keeps the textures of a source with an [`ImageId`] between
+/// frames.
+#[derive(Debug, Clone, Copy)]
+pub struct Source<'a> {
+    pub image: &'a DynamicImage,
+    pub mips: Option<&'a MipPyramid>,
+    pub id: Option<ImageId>,
+}
+
+impl<'a> From<&'a DynamicImage> for Source<'a> {
+    fn from(image: &'a DynamicImage) -> Source<'a> {
+        Source {
+            image,
+            mips: None,
+            id: None,
         }
     }
 }
 
+impl<'a> Source<'a> {
+    /// The pixels to resample to `scaled_w × scaled_h` with `filter`, and the
+    /// mip level they come from (`0` for the image itself).
+    ///
+    /// Nearest-neighbour sampling always reads the image itself, keeping its
+    /// hard pixel edges.
+    fn level(&self, scaled: (u32, u32), filter: FilterMethod) -> (u8, &'a DynamicImage) {
+        match self.mips {
+            Some(mips) if filter != FilterMethod::Nearest => {
+                mips.level_for(scaled).unwrap_or((0, self.image))
+            }
+            _ => (0, self.image),
+        }
+    }
+
+    /// The mip level that drawing at `viewport` with `filter` resamples
+    /// from: `0` for the image itself, as always without a pyramid.
+    pub fn mip_level(&self, viewport: &ViewportState, filter: FilterMethod) -> u8 {
+        self.level(scaled_size(self.image, viewport), filter).0
+    }
+}
+
 /// Render `src` into a `dst_w × dst_h` ARGB8888 pixel buffer.
 ///
 /// The image is scaled according to `viewport.scale`, rotated by
-/// `viewport.rotation`, then blitted centred in the destination with
+/// `viewport.rotation`, mirrored by `viewport.flip_h` / `viewport.flip_v`,
+/// then blitted centred in the destination with
 /// `viewport.offset` applied. Regions not covered by the image are filled
 /// with opaque black (`0xFF000000`).
 ///
 /// The returned `Vec<u8>` is suitable for writing directly into a Wayland SHM
 /// pool (`wl_shm_format::ARGB8888`, 4 bytes per pixel, row-major).
 ///
-/// When compiled with `gpu-vulkan` or `gpu-gles`, the resize and rotation
-/// steps are executed on the GPU; the CPU is only used for the final
+/// When compiled with `gpu-vulkan` or `gpu-gles`, the resize, rotation and
+/// flip steps are executed on the GPU; the CPU is only used for the final
 /// blit-center copy.
-pub fn render(
-    src: &DynamicImage,
+pub fn render<'a>(
+    src: impl Into<Source<'a>>,
     viewport: &ViewportState,
     dst_w: u32,
     dst_h: u32,
     filter: FilterMethod,
     #[cfg(any(feature = "gpu-vulkan", feature = "gpu-gles"))] gpu: &gpu::GpuContext,
 ) -> Vec<u8> {
-    let scaled_w = ((src.width() as f32) * viewport.scale).max(1.0) as u32;
-    let scaled_h = ((src.height() as f32) * viewport.scale).max(1.0) as u32;
+    let src = src.into();
 
     #[cfg(any(feature = "gpu-vulkan", feature = "gpu-gles"))]
     {
+        let (scaled_w, scaled_h) = scaled_size(src.image, viewport);
         gpu_render(src, viewport, dst_w, dst_h, scaled_w, scaled_h, filter, gpu)
     }
 
     #[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
     {
-        let scaled: ImageBuffer<Rgba<u8>, Vec<u8>> =
-            imageops::resize(src, scaled_w, scaled_h, filter.into());
-
-        let rotated: ImageBuffer<Rgba<u8>, Vec<u8>> = match viewport.rotation {
-            90 => imageops::rotate90(&scaled),
-            180 => imageops::rotate180(&scaled),
-            270 => imageops::rotate270(&scaled),
-            _ => scaled,
-        };
-
-        let rot_w = rotated.width();
-        let rot_h = rotated.height();
-        let blit_x = (dst_w as i32 / 2) - (rot_w as i32 / 2) + viewport.offset.0 as i32;
-        let blit_y = (dst_h as i32 / 2) - (rot_h as i32 / 2) + viewport.offset.1 as i32;
         let mut buf = vec![0u8; (dst_w * dst_h * 4) as usize];
+        cpu_render_regions(
+            src,
+            viewport,
+            &mut buf,
+            dst_w,
+            dst_h,
+            &[Rect::full(dst_w, dst_h)],
+            filter,
+            false,
+            0,
+        );
+        buf
+    }
+}
+
+/// Redraw only `regions` of an existing `dst_w × dst_h` ARGB8888 buffer,
+/// leaving every other pixel untouched.
+///
+/// Each region ends up exactly as [`render`] would have drawn it. On the CPU
+/// path the image is transformed once and only the pixels inside the regions
+/// are written, using one thread per CPU; the GPU path renders the full
+/// frame and copies the regions.
+#[allow(clippy::too_many_arguments)]
+pub fn render_regions<'a>(
+    src: impl Into<Source<'a>>,
+    viewport: &ViewportState,
+    buf: &mut [u8],
+    dst_w: u32,
+    dst_h: u32,
+    regions: &[Rect],
+    filter: FilterMethod,
+    #[cfg(any(feature = "gpu-vulkan", feature = "gpu-gles"))] gpu: &gpu::GpuContext,
+) {
+    let src = src.into();
+    let bounds = Rect::full(dst_w, dst_h);
+    let regions: Vec<Rect> = regions
+        .iter()
+        .filter_map(|r| r.intersect(&bounds))
+        .collect();
+    if regions.is_empty() {
+        return;
+    }
 
-        for sy in 0..rot_h {
-            let dy = blit_y + sy as i32;
-            if dy < 0 || dy >= dst_h as i32 {
-                continue;
+    #[cfg(any(feature = "gpu-vulkan", feature = "gpu-gles"))]
+    {
+        let stride = dst_w as usize * 4;
+        let full = render(src, viewport, dst_w, dst_h, filter, gpu);
+        for region in &regions {
+            let x0 = region.x as usize * 4;
+            let x1 = x0 + region.w as usize * 4;
+            for y in region.y..region.y + region.h {
+                let row = y as usize * stride;
+                buf[row + x0..row + x1].copy_from_slice(&full[row + x0..row + x1]);
             }
-            for sx in 0..rot_w {
-                let dx = blit_x + sx as i32;
-                if dx < 0 || dx >= dst_w as i32 {
+        }
+    }
+
+    #[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+    cpu_render_regions(src, viewport, buf, dst_w, dst_h, &regions, filter, false, 0);
+}
+
+/// CPU implementation of [`render_regions`] for regions inside the buffer,
+/// resampling in linear light if `linear`, on `threads` worker threads (`0`
+/// for one per CPU).
+#[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+#[allow(clippy::too_many_arguments)]
+fn cpu_render_regions(
+    src: Source<'_>,
+    viewport: &ViewportState,
+    buf: &mut [u8],
+    dst_w: u32,
+    dst_h: u32,
+    regions: &[Rect],
+    filter: FilterMethod,
+    linear: bool,
+    threads: usize,
+) {
+    let size = rotated_size(src.image, viewport);
+    let origin = blit_origin(size, viewport, dst_w, dst_h);
+    let image = Rect::clipped(origin, size, dst_w, dst_h);
+    for region in regions {
+        // Resample only the part of the image under this region.
+        let Some(visible) = image.and_then(|image| image.intersect(region)) else {
+            clear_region(buf, dst_w, region);
+            continue;
+        };
+        let window = Rect {
+            x: (visible.x as i32 - origin.0) as u32,
+            y: (visible.y as i32 - origin.1) as u32,
+            ..visible
+        };
+        let part = transform(src, viewport, filter, linear, window, threads);
+        blit_regions(
+            &part,
+            (visible.x as i32, visible.y as i32),
+            buf,
+            dst_w,
+            dst_h,
+            std::slice::from_ref(region),
+            threads,
+        );
+    }
+}
+
+/// The `window` (in displayed pixels) of `src` scaled by `viewport.scale`,
+/// rotated and flipped: that part of the image as it appears on screen,
+/// before placement. Only the source pixels under the window are resampled,
+/// from the mip level chosen by [`Source::level`] and in linear light if
+/// `linear`, on `threads` worker threads (`0` for one per CPU).
+#[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+fn transform(
+    src: Source<'_>,
+    viewport: &ViewportState,
+    filter: FilterMethod,
+    linear: bool,
+    window: Rect,
+    threads: usize,
+) -> RgbaImage {
+    let scaled_size = scaled_size(src.image, viewport);
+    let (_, pixels) = src.level(scaled_size, filter);
+    let scaled_window = displayed_to_scaled(
+        viewport.rotation,
+        viewport.flip_h,
+        viewport.flip_v,
+        window,
+        scaled_size,
+    );
+    let scaled =
+        resample::resize_window(pixels, scaled_size, scaled_window, filter, linear, threads);
+
+    let mut rotated: RgbaImage = match viewport.rotation {
+        90 => imageops::rotate90(&scaled),
+        180 => imageops::rotate180(&scaled),
+        270 => imageops::rotate270(&scaled),
+        _ => scaled,
+    };
+    if viewport.flip_h {
+        imageops::flip_horizontal_in_place(&mut rotated);
+    }
+    if viewport.flip_v {
+        imageops::flip_vertical_in_place(&mut rotated);
+    }
+    rotated
+}
+
+/// Fill `region` of the buffer with the background.
+#[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+fn clear_region(buf: &mut [u8], dst_w: u32, region: &Rect) {
+    let stride = dst_w as usize * 4;
+    let x0 = region.x as usize * 4;
+    let x1 = x0 + region.w as usize * 4;
+    for y in region.y..region.y + region.h {
+        let row = y as usize * stride;
+        buf[row + x0..row + x1].fill(0);
+    }
+}
+
+/// Write the parts of a [`transform`]ed image whose top-left corner sits at
+/// `at` in the buffer that fall inside `regions`, as ARGB8888; the rest of
+/// each region becomes background. `regions` must lie inside the buffer.
+///
+/// The rows spanned by `regions` are split into bands across `threads`
+/// worker threads (`0` for one per CPU).
+#[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+fn blit_regions(
+    part: &RgbaImage,
+    at: (i32, i32),
+    buf: &mut [u8],
+    dst_w: u32,
+    dst_h: u32,
+    regions: &[Rect],
+    threads: usize,
+) {
+    let (Some(y0), Some(y1)) = (
+        regions.iter().map(|r| r.y).min(),
+        regions.iter().map(|r| r.y + r.h).max(),
+    ) else {
+        return;
+    };
+    let stride = dst_w as usize * 4;
+    let part_w = part.width() as usize;
+    let image = Rect::clipped(at, part.dimensions(), dst_w, dst_h);
+    let work = regions
+        .iter()
+        .map(|r| r.w as usize * r.h as usize * 4)
+        .sum();
+
+    let rows = &mut buf[y0 as usize * stride..y1 as usize * stride];
+    parallel::for_each_band(
+        rows,
+        stride,
+        parallel::resolve_threads(threads),
+        work,
+        |first_row, band| {
+            let band_y = y0 + first_row as u32;
+            let band_rect = Rect {
+                x: 0,
+                y: band_y,
+                w: dst_w,
+                h: (band.len() / stride) as u32,
+            };
+            for region in regions {
+                let Some(region) = region.intersect(&band_rect) else {
+                    continue;
+                };
+                let x0 = region.x as usize * 4;
+                let x1 = x0 + region.w as usize * 4;
+                for y in region.y..region.y + region.h {
+                    let row = (y - band_y) as usize * stride;
+                    band[row + x0..row + x1].fill(0);
+                }
+                let Some(visible) = image.and_then(|image| image.intersect(&region)) else {
                     continue;
+                };
+                let sx = (visible.x as i32 - at.0) as usize;
+                for y in visible.y..visible.y + visible.h {
+                    let sy = (y as i32 - at.1) as usize;
+                    let src_off = (sy * part_w + sx) * 4;
+                    let src_row = &part.as_raw()[src_off..src_off + visible.w as usize * 4];
+                    let dst_off = (y - band_y) as usize * stride + visible.x as usize * 4;
+                    let dst_row = &mut band[dst_off..dst_off + visible.w as usize * 4];
+                    rgba_to_argb(dst_row, src_row);
                 }
-                let Rgba([r, g, b, a]) = *rotated.get_pixel(sx, sy);
-                let dst_idx = (dy as u32 * dst_w + dx as u32) as usize * 4;
-                // wl_shm ARGB8888 in little-endian memory: [B, G, R, A]
-                buf[dst_idx] = b;
-                buf[dst_idx + 1] = g;
-                buf[dst_idx + 2] = r;
-                buf[dst_idx + 3] = a;
             }
-        }
+        },
+    );
+}
 
-        buf
+/// Convert RGBA8 pixels to wl_shm ARGB8888, which is `[B, G, R, A]` in
+/// little-endian memory, by swapping the red and blue bytes of each pixel.
+#[cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
+fn rgba_to_argb(dst: &mut [u8], src: &[u8]) {
+    for (d, s) in dst.chunks_exact_mut(4).zip(src.chunks_exact(4)) {
+        let v = u32::from_le_bytes([s[0], s[1], s[2], s[3]]);
+        let v = (v & 0xFF00_FF00) | ((v >> 16) & 0xFF) | ((v & 0xFF) << 16);
+        d.copy_from_slice(&v.to_le_bytes());
+    }
+}
+
+/// Map a rectangle of the displayed image (after rotation and flips) back to
+/// the `scaled_w × scaled_h` image before them.
+///
+/// Flips are applied after rotation, so they are undone first: mirroring the
+/// rectangle across the rotated image yields the one that would be shown
+/// without flips. Undoing the rotation then follows the rotate.wgsl /
+/// image-rs conventions noted per arm.
+fn displayed_to_scaled(
+    rotation: u16,
+    flip_h: bool,
+    flip_v: bool,
+    window: Rect,
+    (scaled_w, scaled_h): (u32, u32),
+) -> Rect {
+    let (rot_w, rot_h) = match rotation % 360 {
+        90 | 270 => (scaled_h, scaled_w),
+        _ => (scaled_w, scaled_h),
+    };
+    let Rect { w, h, .. } = window;
+    let x = if flip_h {
+        rot_w - window.x - w
+    } else {
+        window.x
+    };
+    let y = if flip_v {
+        rot_h - window.y - h
+    } else {
+        window.y
+    };
+
+    match rotation % 360 {
+        // output(rx, ry) ← src(rx, ry)
+        0 => Rect { x, y, w, h },
+        // output(rx, ry) ← src(ry, scaled_h-1-rx)
+        90 => Rect {
+            x: y,
+            y: scaled_h - x - w,
+            w: h,
+            h: w,
+        },
+        // output(rx, ry) ← src(scaled_w-1-rx, scaled_h-1-ry)
+        180 => Rect {
+            x: scaled_w - x - w,
+            y: scaled_h - y - h,
+            w,
+            h,
+        },
+        // output(rx, ry) ← src(scaled_w-1-ry, rx)
+        _ => Rect {
+            x: scaled_w - y - h,
+            y: x,
+            w: h,
+            h: w,
+        },
+    }
+}
+
+/// Size of `src` after scaling by `viewport.scale`, at least 1×1.
+fn scaled_size(src: &DynamicImage, viewport: &ViewportState) -> (u32, u32) {
+    let scaled_w = ((src.width() as f32) * viewport.scale).max(1.0) as u32;
+    let scaled_h = ((src.height() as f32) * viewport.scale).max(1.0) as u32;
+    (scaled_w, scaled_h)
+}
+
+/// Size of `src` on screen: scaled, with width and height swapped by a
+/// quarter-turn rotation.
+fn rotated_size(src: &DynamicImage, viewport: &ViewportState) -> (u32, u32) {
+    let (scaled_w, scaled_h) = scaled_size(src, viewport);
+    match viewport.rotation {
+        90 | 270 => (scaled_h, scaled_w),
+        _ => (scaled_w, scaled_h),
     }
 }
 
+/// Top-left corner of a `rot_w × rot_h` image centred in the destination
+/// and shifted by `viewport.offset`. May lie outside the buffer.
+fn blit_origin(
+    (rot_w, rot_h): (u32, u32),
+    viewport: &ViewportState,
+    dst_w: u32,
+    dst_h: u32,
+) -> (i32, i32) {
+    (
+        (dst_w as i32 / 2) - (rot_w as i32 / 2) + viewport.offset.0 as i32,
+        (dst_h as i32 / 2) - (rot_h as i32 / 2) + viewport.offset.1 as i32,
+    )
+}
+
 /// GPU render path with viewport culling.
 ///
 /// When the scaled image fits inside the window, the full image is uploaded
@@ -138,7 +552,7 @@ pub fn render(
 #[cfg(any(feature = "gpu-vulkan", feature = "gpu-gles"))]
 #[allow(clippy::too_many_arguments)]
 fn gpu_render(
-    src: &DynamicImage,
+    src: Source<'_>,
     viewport: &ViewportState,
     dst_w: u32,
     dst_h: u32,
@@ -180,7 +594,7 @@ fn gpu_render(
 #[cfg(any(feature = "gpu-vulkan", feature = "gpu-gles"))]
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn gpu_render_inner(
-    src: &DynamicImage,
+    src: Source<'_>,
     viewport: &ViewportState,
     dst_w: u32,
     dst_h: u32,
@@ -189,7 +603,13 @@ pub(crate) fn gpu_render_inner(
     filter: FilterMethod,
     gpu: &gpu::GpuContext,
 ) -> Option<(wgpu::Texture, u32, u32)> {
-    let scale = viewport.scale;
+    // A mip level stands in for the image at its own, larger scale.
+    let (level, pixels) = src.level((scaled_w, scaled_h), filter);
+    let scale = if level == 0 {
+        viewport.scale
+    } else {
+        scaled_w as f32 / pixels.width() as f32
+    };
     let rotation = viewport.rotation;
 
     let (rot_w, rot_h) = match rotation {
@@ -197,8 +617,7 @@ pub(crate) fn gpu_render_inner(
         _ => (scaled_w, scaled_h),
     };
 
-    let blit_x = dst_w as i32 / 2 - rot_w as i32 / 2 + viewport.offset.0 as i32;
-    let blit_y = dst_h as i32 / 2 - rot_h as i32 / 2 + viewport.offset.1 as i32;
+    let (blit_x, blit_y) = blit_origin((rot_w, rot_h), viewport, dst_w, dst_h);
 
     let vis_sx0 = (-blit_x).max(0) as u32;
     let vis_sy0 = (-blit_y).max(0) as u32;
@@ -209,108 +628,98 @@ pub(crate) fn gpu_render_inner(
         return None;
     }
 
-    let vis_w = vis_sx1 - vis_sx0;
-    let vis_h = vis_sy1 - vis_sy0;
     let win_x = blit_x.max(0) as u32;
     let win_y = blit_y.max(0) as u32;
 
     let (crop_x, crop_y, crop_w, crop_h, resize_w, resize_h) = visible_source_crop(
         rotation,
+        viewport.flip_h,
+        viewport.flip_v,
         vis_sx0,
         vis_sy0,
         vis_sx1,
         vis_sy1,
-        vis_w,
-        vis_h,
         scale,
-        src.width(),
-        src.height(),
+        pixels.width(),
+        pixels.height(),
         scaled_w,
         scaled_h,
     );
 
-    let crop = src.crop_imm(crop_x, crop_y, crop_w, crop_h);
-    let tex = gpu::upload_texture(&gpu.device, &gpu.queue, &crop);
-    let out = gpu::resize_blit(gpu, &tex, resize_w, resize_h, filter, rotation);
+    let crop = (crop_x, crop_y, crop_w, crop_h);
+    let max = gpu.device.limits().max_texture_dimension_2d;
+    let out = if crop_w > max || crop_h > max {
+        gpu::resize_blit_tiled(
+            gpu,
+            pixels,
+            crop,
+            resize_w,
+            resize_h,
+            filter,
+            rotation,
+            viewport.flip_h,
+            viewport.flip_v,
+        )
+    } else {
+        let tex = gpu::source_texture(gpu, src.id, level, pixels, crop);
+        gpu::resize_blit(
+            gpu,
+            &tex,
+            resize_w,
+            resize_h,
+            filter,
+            rotation,
+            viewport.flip_h,
+            viewport.flip_v,
+        )
+    };
     Some((out, win_x, win_y))
 }
 
 /// Compute the source image crop and GPU resize dimensions needed to produce
-/// exactly the visible `vis_w × vis_h` window region after `rotation`.
+/// exactly the visible `[vis_sx0, vis_sx1) × [vis_sy0, vis_sy1)` window
+/// region after `rotation` and the optional flips.
 ///
-/// The rotation shader (rotate.wgsl) maps each output pixel back to a source
-/// pixel. Inverting that mapping for the visible rectangle gives a rectangular
-/// source crop that, when resized to `(resize_w, resize_h)` and rotated,
-/// produces a `vis_w × vis_h` output — matching the window region exactly.
+/// [`displayed_to_scaled`] maps the visible rectangle back to the scaled
+/// image before rotation and flips; dividing by `scale` gives the source
+/// crop. Resizing that crop to the scaled rectangle's size and rotating and
+/// mirroring it in the shader reproduces the visible region exactly.
 ///
 /// Returns `(crop_x, crop_y, crop_w, crop_h, resize_w, resize_h)`.
 #[cfg(any(feature = "gpu-vulkan", feature = "gpu-gles"))]
 #[allow(clippy::too_many_arguments)]
 fn visible_source_crop(
     rotation: u16,
+    flip_h: bool,
+    flip_v: bool,
     vis_sx0: u32,
     vis_sy0: u32,
     vis_sx1: u32,
     vis_sy1: u32,
-    vis_w: u32,
-    vis_h: u32,
     scale: f32,
     src_w: u32,
     src_h: u32,
     scaled_w: u32,
     scaled_h: u32,
 ) -> (u32, u32, u32, u32, u32, u32) {
-    // Helpers: map a range in scaled (resized-source) coordinates back to image coords.
-    let sx = |v: u32| (v as f32 / scale) as u32;
-    let sx_ceil = |v: u32| ((v as f32 / scale).ceil() as u32).min(src_w);
-    let sy = |v: u32| (v as f32 / scale) as u32;
-    let sy_ceil = |v: u32| ((v as f32 / scale).ceil() as u32).min(src_h);
-    // Map from the flipped side: (scaled_dim - v) / scale, clamped.
-    let fx = |v: u32| ((scaled_w as f32 - v as f32) / scale).max(0.0) as u32;
-    let fx_ceil = |v: u32| {
-        ((scaled_w as f32 - v as f32) / scale)
-            .max(0.0)
-            .ceil()
-            .min(src_w as f32) as u32
-    };
-    let fy = |v: u32| ((scaled_h as f32 - v as f32) / scale).max(0.0) as u32;
-    let fy_ceil = |v: u32| {
-        ((scaled_h as f32 - v as f32) / scale)
-            .max(0.0)
-            .ceil()
-            .min(src_h as f32) as u32
+    let visible = Rect {
+        x: vis_sx0,
+        y: vis_sy0,
+        w: vis_sx1 - vis_sx0,
+        h: vis_sy1 - vis_sy0,
     };
+    let window = displayed_to_scaled(rotation, flip_h, flip_v, visible, (scaled_w, scaled_h));
 
-    match rotation % 360 {
-        // Shader: output(rx,ry) ← src(rx,ry)
-        // Visible src x: [vis_sx0, vis_sx1), y: [vis_sy0, vis_sy1)
-        0 => {
-            let (x0, x1) = (sx(vis_sx0), sx_ceil(vis_sx1));
-            let (y0, y1) = (sy(vis_sy0), sy_ceil(vis_sy1));
-            (x0, y0, (x1 - x0).max(1), (y1 - y0).max(1), vis_w, vis_h)
-        }
-        // Shader: output(rx,ry) ← src(ry, scaled_h-1-rx); output size = scaled_h × scaled_w
-        // Visible src x: [vis_sy0, vis_sy1), y: [scaled_h-vis_sx1, scaled_h-vis_sx0)
-        // Resize to (vis_h, vis_w) then rotate 90 → vis_w × vis_h
-        90 => {
-            let (x0, x1) = (sx(vis_sy0), sx_ceil(vis_sy1));
-            let (y0, y1) = (fy(vis_sx1), fy_ceil(vis_sx0));
-            (x0, y0, (x1 - x0).max(1), (y1 - y0).max(1), vis_h, vis_w)
-        }
-        // Shader: output(rx,ry) ← src(scaled_w-1-rx, scaled_h-1-ry)
-        // Visible src x: [scaled_w-vis_sx1, scaled_w-vis_sx0), y: [scaled_h-vis_sy1, scaled_h-vis_sy0)
-        180 => {
-            let (x0, x1) = (fx(vis_sx1), fx_ceil(vis_sx0));
-            let (y0, y1) = (fy(vis_sy1), fy_ceil(vis_sy0));
-            (x0, y0, (x1 - x0).max(1), (y1 - y0).max(1), vis_w, vis_h)
-        }
-        // Shader: output(rx,ry) ← src(scaled_w-1-ry, rx); output size = scaled_h × scaled_w
-        // Visible src x: [scaled_w-vis_sy1, scaled_w-vis_sy0), y: [vis_sx0, vis_sx1)
-        // Resize to (vis_h, vis_w) then rotate 270 → vis_w × vis_h
-        _ => {
-            let (x0, x1) = (fx(vis_sy1), fx_ceil(vis_sy0));
-            let (y0, y1) = (sy(vis_sx0), sy_ceil(vis_sx1));
-            (x0, y0, (x1 - x0).max(1), (y1 - y0).max(1), vis_h, vis_w)
-        }
-    }
+    let x0 = (window.x as f32 / scale) as u32;
+    let y0 = (window.y as f32 / scale) as u32;
+    let x1 = (((window.x + window.w) as f32 / scale).ceil() as u32).min(src_w);
+    let y1 = (((window.y + window.h) as f32 / scale).ceil() as u32).min(src_h);
+    (
+        x0,
+        y0,
+        (x1 - x0).max(1),
+        (y1 - y0).max(1),
+        window.w,
+        window.h,
+    )
 }
@@ -0,0 +1,63 @@
+//! Row-band parallelism for the CPU render path.
+//!
+//! Resampling and the RGBA → ARGB8888 blit work row by row with no
+//! dependencies between rows, so the output is split into bands of whole
+//! rows and each band is filled on its own scoped thread. Every pixel is
+//! computed by the same code whatever the band layout, so the result does
+//! not depend on the thread count.
+
+use std::num::NonZero;
+use std::sync::OnceLock;
+
+/// Least work, in bytes or samples touched, worth handing to another thread.
+const MIN_BAND_WORK: usize = 64 * 1024;
+
+/// Thread count used when none is configured: one per available CPU.
+pub(crate) fn available_threads() -> usize {
+    static THREADS: OnceLock<usize> = OnceLock::new();
+    *THREADS.get_or_init(|| std::thread::available_parallelism().map_or(1, NonZero::get))
+}
+
+/// Resolve a configured thread count, where `0` means
+/// [`available_threads`].
+pub(crate) fn resolve_threads(threads: usize) -> usize {
+    if threads == 0 {
+        available_threads()
+    } else {
+        threads
+    }
+}
+
+/// Split `buf`, made of rows of `row_len` elements, into bands of whole rows
+/// and call `f(first_row, band)` on each.
+///
+/// At most `threads` bands are used, and no more than one per
+/// [`MIN_BAND_WORK`] units of `work`, so small jobs stay on the calling
+/// thread.
+pub(crate) fn for_each_band<T: Send>(
+    buf: &mut [T],
+    row_len: usize,
+    threads: usize,
+    work: usize,
+    f: impl Fn(usize, &mut [T]) + Sync,
+) {
+    let rows = buf.len().checked_div(row_len).unwrap_or(0);
+    let bands = threads.min(work / MIN_BAND_WORK).min(rows);
+    if bands <= 1 {
+        f(0, buf);
+        return;
+    }
+    let band_rows = rows.div_ceil(bands);
+    std::thread::scope(|scope| {
+        let mut chunks = buf.chunks_mut(band_rows * row_len).enumerate();
+        // The calling thread takes the first band itself.
+        let first = chunks.next();
+        for (i, band) in chunks {
+            let f = &f;
+            scope.spawn(move || f(i * band_rows, band));
+        }
+        if let Some((_, band)) = first {
+            f(0, band);
+        }
+    });
+}
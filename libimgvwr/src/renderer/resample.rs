@@ -0,0 +1,303 @@
+//! Separable resampling of a window of the scaled image.
+//!
+//! [`resize_window`] computes any rectangle of `imageops::resize(src, w, h)`
+//! without producing the rest: the kernel taps of each output row and column
+//! depend only on its own coordinate, so a window needs just the source
+//! pixels under its taps. The kernels, tap positions and summation order
+//! follow image-rs exactly, making every window bit-identical to the same
+//! pixels of a full resize; filters image-rs lacks use the same scheme.
+//!
+//! Both passes fill whole output rows independently, so they are split into
+//! row bands across worker threads without affecting the result.
+//!
+//! With `linear` set the colour channels are filtered in linear light (see
+//! `srgb`) instead of on their encoded values.
+
+use std::borrow::Cow;
+
+use image::{DynamicImage, GenericImageView, RgbaImage};
+
+use super::{FilterMethod, frame::Rect, parallel, srgb};
+
+/// A resampling kernel and the radius outside which it is zero.
+struct Kernel {
+    weights: Weights,
+    support: f32,
+}
+
+/// How a kernel weighs the source pixels under its taps.
+#[derive(Clone, Copy)]
+enum Weights {
+    /// By distance from the output pixel's centre, in source pixels scaled
+    /// down by the downscaling ratio.
+    Distance(fn(f32) -> f32),
+    /// By the overlap of each source pixel with the output pixel's
+    /// footprint.
+    Area,
+    /// Bilinear around the output pixel's centre moved as by
+    /// [`sharp_bilinear_centre`].
+    SharpBilinear,
+}
+
+impl From<FilterMethod> for Kernel {
+    fn from(filter: FilterMethod) -> Kernel {
+        let weights = match filter {
+            FilterMethod::Nearest => Weights::Distance(box_kernel),
+            FilterMethod::Box => Weights::Area,
+            FilterMethod::Triangle => Weights::Distance(triangle_kernel),
+            FilterMethod::SharpBilinear => Weights::SharpBilinear,
+            FilterMethod::Hermite => Weights::Distance(hermite_kernel),
+            FilterMethod::CatmullRom => Weights::Distance(catmullrom_kernel),
+            FilterMethod::Mitchell => Weights::Distance(mitchell_kernel),
+            FilterMethod::Gaussian => Weights::Distance(gaussian_kernel),
+            FilterMethod::Lanczos2 => Weights::Distance(lanczos2_kernel),
+            FilterMethod::Lanczos3 => Weights::Distance(lanczos3_kernel),
+            FilterMethod::Lanczos4 => Weights::Distance(lanczos4_kernel),
+        };
+        Kernel {
+            weights,
+            support: filter.support(),
+        }
+    }
+}
+
+fn sinc(t: f32) -> f32 {
+    let a = t * std::f32::consts::PI;
+    if t == 0.0 { 1.0 } else { a.sin() / a }
+}
+
+fn lanczos_kernel(x: f32, a: f32) -> f32 {
+    if x.abs() < a {
+        sinc(x) * sinc(x / a)
+    } else {
+        0.0
+    }
+}
+
+fn lanczos2_kernel(x: f32) -> f32 {
+    lanczos_kernel(x, 2.0)
+}
+
+fn lanczos3_kernel(x: f32) -> f32 {
+    lanczos_kernel(x, 3.0)
+}
+
+fn lanczos4_kernel(x: f32) -> f32 {
+    lanczos_kernel(x, 4.0)
+}
+
+/// Gaussian with a standard deviation of 0.5.
+fn gaussian_kernel(x: f32) -> f32 {
+    let r = 0.5f32;
+    ((2.0 * std::f32::consts::PI).sqrt() * r).recip() * (-x.powi(2) / (2.0 * r.powi(2))).exp()
+}
+
+/// Mitchell–Netravali cubic with `b = 0`, `c = 0.5`.
+fn catmullrom_kernel(x: f32) -> f32 {
+    bc_cubic_kernel(x, 0.0, 0.5)
+}
+
+/// Mitchell–Netravali cubic with the recommended `b = c = 1/3`.
+fn mitchell_kernel(x: f32) -> f32 {
+    bc_cubic_kernel(x, 1.0 / 3.0, 1.0 / 3.0)
+}
+
+/// Hermite cubic: Mitchell–Netravali with `b = c = 0`, zero from 1 on.
+fn hermite_kernel(x: f32) -> f32 {
+    bc_cubic_kernel(x, 0.0, 0.0)
+}
+
+fn bc_cubic_kernel(x: f32, b: f32, c: f32) -> f32 {
+    let a = x.abs();
+    let k = if a < 1.0 {
+        (12.0 - 9.0 * b - 6.0 * c) * a.powi(3)
+            + (-18.0 + 12.0 * b + 6.0 * c) * a.powi(2)
+            + (6.0 - 2.0 * b)
+    } else if a < 2.0 {
+        (-b - 6.0 * c) * a.powi(3)
+            + (6.0 * b + 30.0 * c) * a.powi(2)
+            + (-12.0 * b - 48.0 * c) * a
+            + (8.0 * b + 24.0 * c)
+    } else {
+        0.0
+    };
+    k / 6.0
+}
+
+fn triangle_kernel(x: f32) -> f32 {
+    if x.abs() < 1.0 { 1.0 - x.abs() } else { 0.0 }
+}
+
+fn box_kernel(_x: f32) -> f32 {
+    1.0
+}
+
+/// Overlap of the source pixel starting `offset` pixels after an output
+/// pixel's centre with that pixel's `ratio` pixels wide footprint.
+fn area_weight(offset: f32, ratio: f32) -> f32 {
+    let half = ratio / 2.0;
+    ((offset + 1.0).min(half) - offset.max(-half)).max(0.0)
+}
+
+/// Where sharp-bilinear sampling reads around source position `centre`:
+/// upscaling by the largest whole factor with nearest neighbour and then
+/// bilinearly moves every sample inside a source pixel towards its centre,
+/// except within half an output pixel of its edges.
+fn sharp_bilinear_centre(centre: f32, ratio: f32) -> f32 {
+    // Slack for ratios such as 1/3 that f32 cannot represent exactly.
+    let k = (1.0 / ratio + 1e-3).floor().max(1.0);
+    let pixel = centre.floor();
+    let d = centre - pixel - 0.5;
+    let r = 0.5 - 0.5 / k;
+    pixel + 0.5 + (d - d.clamp(-r, r)) * k
+}
+
+/// The source pixels contributing to one output pixel along one axis.
+struct Taps {
+    /// First contributing source index.
+    left: u32,
+    /// Normalised weights of `left..left + weights.len()`.
+    weights: Vec<f32>,
+}
+
+impl Taps {
+    fn right(&self) -> u32 {
+        self.left + self.weights.len() as u32
+    }
+}
+
+/// Taps of output index `out` when resampling `src_len` pixels to `dst_len`.
+fn taps(out: u32, src_len: u32, dst_len: u32, kernel: &Kernel) -> Taps {
+    let ratio = src_len as f32 / dst_len as f32;
+    let sratio = if ratio < 1.0 { 1.0 } else { ratio };
+    let src_support = kernel.support * sratio;
+
+    // Centre of the output pixel in source coordinates.
+    let input = (out as f32 + 0.5) * ratio;
+    let left = ((input - src_support).floor() as i64).clamp(0, i64::from(src_len) - 1) as u32;
+    let right =
+        ((input + src_support).ceil() as i64).clamp(i64::from(left) + 1, i64::from(src_len)) as u32;
+
+    let centre = match kernel.weights {
+        Weights::SharpBilinear => sharp_bilinear_centre(input, ratio),
+        _ => input,
+    };
+    // The kernel treats the centre of a pixel as 0.
+    let input = centre - 0.5;
+    let mut weights: Vec<f32> = (left..right)
+        .map(|i| match kernel.weights {
+            Weights::Distance(f) => f((i as f32 - input) / sratio),
+            Weights::Area => area_weight(i as f32 - centre, ratio),
+            Weights::SharpBilinear => triangle_kernel(i as f32 - input),
+        })
+        .collect();
+    let sum: f32 = weights.iter().sum();
+    for w in &mut weights {
+        *w /= sum;
+    }
+    Taps { left, weights }
+}
+
+/// The `window` of `src` resized to `dst_w × dst_h` with `filter`, as RGBA8.
+///
+/// Equal to cropping `imageops::resize(src, dst_w, dst_h, filter)` to
+/// `window`, but touches only the source pixels the window depends on.
+/// `window` must lie inside `dst_w × dst_h` and not be empty. With `linear`
+/// the colour channels are resampled in linear light. Runs on `threads`
+/// worker threads (`0` for one per CPU).
+pub(crate) fn resize_window(
+    src: &DynamicImage,
+    (dst_w, dst_h): (u32, u32),
+    window: Rect,
+    filter: FilterMethod,
+    linear: bool,
+    threads: usize,
+) -> RgbaImage {
+    let (src_w, src_h) = src.dimensions();
+    if (dst_w, dst_h) == (src_w, src_h) {
+        return src.view(window.x, window.y, window.w, window.h).to_image();
+    }
+
+    let kernel = Kernel::from(filter);
+    let cols: Vec<Taps> = (window.x..window.x + window.w)
+        .map(|x| taps(x, src_w, dst_w, &kernel))
+        .collect();
+    let rows: Vec<Taps> = (window.y..window.y + window.h)
+        .map(|y| taps(y, src_h, dst_h, &kernel))
+        .collect();
+
+    // Source rectangle under the taps; tap ranges grow monotonically.
+    let sx0 = cols[0].left;
+    let sx1 = cols.iter().map(Taps::right).max().unwrap_or(sx0 + 1);
+    let sy0 = rows[0].left;
+    let sy1 = rows.iter().map(Taps::right).max().unwrap_or(sy0 + 1);
+    let (source, (ox, oy)): (Cow<'_, RgbaImage>, _) = match src.as_rgba8() {
+        Some(rgba) => (Cow::Borrowed(rgba), (0, 0)),
+        None => (
+            Cow::Owned(src.view(sx0, sy0, sx1 - sx0, sy1 - sy0).to_image()),
+            (sx0, sy0),
+        ),
+    };
+    let source_w = source.width() as usize;
+    let source = source.as_raw();
+
+    let threads = parallel::resolve_threads(threads);
+
+    // Value filtered for each byte of each channel, on the 0–255 scale.
+    let bytes: [f32; 256] = std::array::from_fn(|i| i as f32);
+    let colour: [f32; 256] = if linear {
+        std::array::from_fn(|i| srgb::to_linear(i as u8) * 255.0)
+    } else {
+        bytes
+    };
+    let channel_levels = [&colour, &colour, &colour, &bytes];
+
+    // Vertical pass into f32, only over the columns the horizontal pass reads.
+    let mid_w = (sx1 - sx0) as usize;
+    let mut mid = vec![0f32; mid_w * rows.len() * 4];
+    let work = mid.len() * rows[0].weights.len();
+    parallel::for_each_band(&mut mid, mid_w * 4, threads, work, |first_row, band| {
+        for (out, taps) in band.chunks_exact_mut(mid_w * 4).zip(&rows[first_row..]) {
+            for (x, acc) in out.chunks_exact_mut(4).enumerate() {
+                let sx = (sx0 - ox) as usize + x;
+                let mut t = [0f32; 4];
+                for (i, w) in taps.weights.iter().enumerate() {
+                    let sy = (taps.left - oy) as usize + i;
+                    let p = &source[(sy * source_w + sx) * 4..][..4];
+                    for ((t, &p), levels) in t.iter_mut().zip(p).zip(channel_levels) {
+                        *t += levels[usize::from(p)] * w;
+                    }
+                }
+                acc.copy_from_slice(&t);
+            }
+        }
+    });
+
+    // Horizontal pass, rounding to the nearest integer like image-rs.
+    let mut out = RgbaImage::new(window.w, window.h);
+    let line_len = window.w as usize * 4;
+    let work = out.len() * cols[0].weights.len();
+    parallel::for_each_band(&mut out, line_len, threads, work, |first_row, band| {
+        for (row, line) in band.chunks_exact_mut(line_len).enumerate() {
+            let row = first_row + row;
+            let mid_row = &mid[row * mid_w * 4..(row + 1) * mid_w * 4];
+            for (taps, px) in cols.iter().zip(line.chunks_exact_mut(4)) {
+                let mut t = [0f32; 4];
+                for (i, w) in taps.weights.iter().enumerate() {
+                    let m = &mid_row[((taps.left - sx0) as usize + i) * 4..][..4];
+                    for (t, &m) in t.iter_mut().zip(m) {
+                        *t += m * w;
+                    }
+                }
+                for (c, (px, t)) in px.iter_mut().zip(t).enumerate() {
+                    *px = if linear && c < 3 {
+                        srgb::from_linear(t / 255.0)
+                    } else {
+                        t.clamp(0.0, 255.0).round() as u8
+                    };
+                }
+            }
+        }
+    });
+    out
+}
@@ -1,6 +1,15 @@
 @group(0) @binding(0) var src_texture: texture_2d<f32>;
 @group(0) @binding(1) var src_sampler: sampler;
 
+struct Uniforms {
+    // Texture coordinates of the destination's top-left corner and its size
+    // in texture coordinates; (0, 0) and (1, 1) stretch the whole source.
+    origin: vec2<f32>,
+    extent: vec2<f32>,
+}
+
+@group(0) @binding(2) var<uniform> uni: Uniforms;
+
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) uv: vec2<f32>,
@@ -24,7 +33,7 @@ fn vs_main(@builtin(vertex_index) vi: u32) -> VertexOutput {
     );
     var out: VertexOutput;
     out.position = vec4<f32>(positions[vi], 0.0, 1.0);
-    out.uv = uvs[vi];
+    out.uv = uni.origin + uvs[vi] * uni.extent;
     return out;
 }
 
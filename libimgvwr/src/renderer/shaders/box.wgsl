@@ -0,0 +1,10 @@
+// Box filter averaging the area under each output pixel, appended to
+// separable.wgsl. A source pixel weighs as much as it overlaps the output
+// pixel's footprint, `ratio` source pixels wide.
+
+fn kernel(x: f32, span: Taps) -> f32 {
+    // Distance between the pixel centres in source pixels.
+    let d = x / span.scale;
+    let h = span.ratio * 0.5;
+    return max(0.0, min(d + 0.5, h) - max(d - 0.5, -h));
+}
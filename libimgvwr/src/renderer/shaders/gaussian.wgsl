@@ -0,0 +1,6 @@
+// Gaussian kernel with a standard deviation of 0.5, appended to
+// separable.wgsl. The taps are normalised, so the constant factor is omitted.
+
+fn kernel(x: f32, span: Taps) -> f32 {
+    return exp(-2.0 * x * x);
+}
@@ -0,0 +1,8 @@
+// Hermite cubic kernel (Mitchell-Netravali B=0, C=0), appended to
+// separable.wgsl.
+
+fn kernel(x: f32, span: Taps) -> f32 {
+    let t = abs(x);
+    if t >= 1.0 { return 0.0; }
+    return 2.0*t*t*t - 3.0*t*t + 1.0;
+}
@@ -0,0 +1,10 @@
+// Lanczos kernel with window a=SUPPORT, appended to separable.wgsl.
+
+fn kernel(x: f32, span: Taps) -> f32 {
+    let a = SUPPORT;
+    let t = abs(x);
+    if t >= a    { return 0.0; }
+    if t < 0.001 { return 1.0; }
+    let pt = 3.14159265358979 * t;
+    return (sin(pt) / pt) * (sin(pt / a) / (pt / a));
+}
@@ -0,0 +1,14 @@
+// Mitchell-Netravali cubic kernel with B=C=1/3, appended to separable.wgsl.
+
+fn kernel(x: f32, span: Taps) -> f32 {
+    let b = 1.0 / 3.0;
+    let c = 1.0 / 3.0;
+    let t = abs(x);
+    var k = 0.0;
+    if t < 1.0 {
+        k = (12.0 - 9.0*b - 6.0*c)*t*t*t + (-18.0 + 12.0*b + 6.0*c)*t*t + (6.0 - 2.0*b);
+    } else if t < 2.0 {
+        k = (-b - 6.0*c)*t*t*t + (6.0*b + 30.0*c)*t*t + (-12.0*b - 48.0*c)*t + (8.0*b + 24.0*c);
+    }
+    return k / 6.0;
+}
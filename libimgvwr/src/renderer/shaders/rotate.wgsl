@@ -1,11 +1,13 @@
 // Pixel-perfect 90 / 180 / 270° rotation via textureLoad (no sampler).
 // Matches image-rs rotate90 / rotate180 / rotate270 conventions.
+// Optional horizontal / vertical flips are applied after the rotation,
+// matching image-rs flip_horizontal / flip_vertical on the rotated image.
 
 struct Uniforms {
     src_w:    u32,
     src_h:    u32,
     rotation: u32,  // 0=0°  1=90°  2=180°  3=270°
-    _pad:     u32,
+    flip:     u32,  // bit 0 = horizontal, bit 1 = vertical
 }
 
 @group(0) @binding(0) var src: texture_2d<f32>;
@@ -26,10 +28,24 @@ fn vs_main(@builtin(vertex_index) vi: u32) -> VertOut {
 
 @fragment
 fn fs_main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
-    let px = i32(pos.x);
-    let py = i32(pos.y);
+    var px = i32(pos.x);
+    var py = i32(pos.y);
     let sw = i32(uni.src_w);
     let sh = i32(uni.src_h);
+    // Output size: (sh, sw) for 90° / 270°, (sw, sh) otherwise.
+    var ow = sw;
+    var oh = sh;
+    if uni.rotation == 1u || uni.rotation == 3u {
+        ow = sh;
+        oh = sw;
+    }
+    // Undo the flips first: they act on the rotated output.
+    if (uni.flip & 1u) != 0u {
+        px = ow - 1 - px;
+    }
+    if (uni.flip & 2u) != 0u {
+        py = oh - 1 - py;
+    }
     var sx: i32;
     var sy: i32;
     // image-rs rotate90 maps src(x,y) → out(sh-1-y, x); output size (sh, sw).
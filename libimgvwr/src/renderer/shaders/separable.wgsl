@@ -0,0 +1,116 @@
+// Two-pass separable resize shared by every kernel filter.
+// Pass 1 (horizontal): entry point fs_horizontal → Rgba16Float
+// Pass 2 (vertical):   entry point fs_vertical   → Rgba8Unorm
+//
+// With `linear` set, pass 1 decodes the sRGB source to linear light and
+// pass 2 encodes its result again; alpha is filtered as is.
+//
+// `SUPPORT`, the kernel's radius at 1:1 scale, is prepended by the renderer,
+// and a kernel file appended to this one defines
+// `fn kernel(x: f32, span: Taps) -> f32`, weighing a source pixel `x` kernel
+// units from the output pixel's centre. Tap positions, the widening of the
+// kernel when downscaling and the dropping of taps past the image edge
+// follow image-rs, so the result matches the CPU path.
+
+struct Uniforms {
+    src_size: vec2<u32>,
+    dst_size: vec2<u32>,
+    // Source pixels per destination pixel.
+    ratio:    vec2<f32>,
+    // Source position of the destination's top-left corner, so that a tile
+    // of a larger resize samples exactly where the whole resize would.
+    origin:   vec2<f32>,
+    // Non-zero to filter colours in linear light.
+    linear:   u32,
+}
+
+@group(0) @binding(0) var src: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> uni: Uniforms;
+
+struct VertOut {
+    @builtin(position) pos: vec4<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vi: u32) -> VertOut {
+    var xs = array<f32, 4>(-1.0,  1.0, -1.0,  1.0);
+    var ys = array<f32, 4>(-1.0, -1.0,  1.0,  1.0);
+    var out: VertOut;
+    out.pos = vec4<f32>(xs[vi], ys[vi], 0.0, 1.0);
+    return out;
+}
+
+// Source taps `lo..=hi` of the output pixel centred at `pos` along one axis,
+// the scale mapping source distances onto the kernel, and the resize ratio.
+struct Taps {
+    lo:     i32,
+    hi:     i32,
+    centre: f32,
+    scale:  f32,
+    ratio:  f32,
+}
+
+fn srgb_to_linear(c: vec4<f32>) -> vec4<f32> {
+    let rgb = select(
+        pow((c.rgb + 0.055) / 1.055, vec3<f32>(2.4)),
+        c.rgb / 12.92,
+        c.rgb <= vec3<f32>(0.04045),
+    );
+    return vec4<f32>(rgb, c.a);
+}
+
+fn linear_to_srgb(c: vec4<f32>) -> vec4<f32> {
+    let rgb = select(
+        1.055 * pow(c.rgb, vec3<f32>(1.0 / 2.4)) - 0.055,
+        c.rgb * 12.92,
+        c.rgb <= vec3<f32>(0.0031308),
+    );
+    return vec4<f32>(rgb, c.a);
+}
+
+fn taps(pos: f32, ratio: f32, origin: f32, len: u32) -> Taps {
+    // Compress the kernel when downscaling to avoid aliasing.
+    let scale   = min(1.0, 1.0 / ratio);
+    let centre  = origin + pos * ratio;
+    let support = SUPPORT / scale;
+    let last    = i32(len) - 1;
+    let lo = clamp(i32(floor(centre - support)), 0, last);
+    let hi = clamp(i32(ceil(centre + support)) - 1, lo, last);
+    return Taps(lo, hi, centre, scale, ratio);
+}
+
+@fragment
+fn fs_horizontal(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+    let t = taps(pos.x, uni.ratio.x, uni.origin.x, uni.src_size.x);
+    let y = i32(pos.y);
+    var col = vec4<f32>(0.0);
+    var ws  = 0.0;
+    for (var ix = t.lo; ix <= t.hi; ix++) {
+        let w = kernel((f32(ix) + 0.5 - t.centre) * t.scale, t);
+        var texel = textureLoad(src, vec2<i32>(ix, y), 0);
+        if uni.linear != 0u { texel = srgb_to_linear(texel); }
+        col += w * texel;
+        ws  += w;
+    }
+    if ws == 0.0 { return vec4<f32>(0.0); }
+    // No clamp: the Rgba16Float intermediate preserves negative lobes.
+    return col / ws;
+}
+
+@fragment
+fn fs_vertical(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+    let t = taps(pos.y, uni.ratio.y, uni.origin.y, uni.src_size.y);
+    let x = i32(pos.x);
+    var col = vec4<f32>(0.0);
+    var ws  = 0.0;
+    for (var iy = t.lo; iy <= t.hi; iy++) {
+        let w = kernel((f32(iy) + 0.5 - t.centre) * t.scale, t);
+        col += w * textureLoad(src, vec2<i32>(x, iy), 0);
+        ws  += w;
+    }
+    if ws == 0.0 { return vec4<f32>(0.0); }
+    // Clamp on final write to Rgba8Unorm.
+    let out = clamp(col / ws, vec4<f32>(0.0), vec4<f32>(1.0));
+    if uni.linear != 0u { return linear_to_srgb(out); }
+    return out;
+}
@@ -0,0 +1,17 @@
+// Sharp bilinear for pixel art, appended to separable.wgsl: nearest
+// neighbour upscaling by the largest whole factor, then bilinear to the
+// final size, so pixels stay square with one blended output pixel at their
+// edges. Downscaling is plain bilinear.
+
+fn kernel(x: f32, span: Taps) -> f32 {
+    // Slack for ratios such as 1/3 that f32 cannot represent exactly.
+    let k = max(1.0, floor(1.0 / span.ratio + 1e-3));
+    // Pull the sample towards the centre of the source pixel it lies in.
+    let pixel = floor(span.centre);
+    let d = span.centre - pixel - 0.5;
+    let r = 0.5 - 0.5 / k;
+    let pos = pixel + 0.5 + (d - clamp(d, -r, r)) * k;
+    // Centre of the weighed source pixel.
+    let p = span.centre + x / span.scale;
+    return max(0.0, 1.0 - abs(p - pos));
+}
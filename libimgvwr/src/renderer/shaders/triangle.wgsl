@@ -0,0 +1,6 @@
+// Triangle (tent) kernel, appended to separable.wgsl. Unlike the sampler's
+// bilinear filter it widens when downscaling, averaging every source pixel.
+
+fn kernel(x: f32, span: Taps) -> f32 {
+    return max(0.0, 1.0 - abs(x));
+}
@@ -0,0 +1,38 @@
+//! Conversion between sRGB-encoded bytes and linear light.
+//!
+//! Averaging sRGB-encoded values weighs dark pixels too heavily, so
+//! resampling in them darkens high-contrast detail such as fine text.
+//! Linear-light resampling decodes colour channels with [`to_linear`],
+//! filters, and encodes the result with [`from_linear`]. Alpha is linear
+//! already and passes through unchanged.
+
+use std::sync::LazyLock;
+
+/// Linear light of every encoded byte.
+static LINEAR: LazyLock<[f32; 256]> =
+    LazyLock::new(|| std::array::from_fn(|i| decode(i as f32 / 255.0)));
+
+/// Linear light halfway between the encodings of byte `i` and `i + 1`:
+/// the least value that encodes to `i + 1`.
+static THRESHOLDS: LazyLock<[f32; 255]> =
+    LazyLock::new(|| std::array::from_fn(|i| decode((i as f32 + 0.5) / 255.0)));
+
+/// The sRGB transfer function, from encoded to linear values in `0..=1`.
+fn decode(v: f32) -> f32 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Linear light of the sRGB-encoded byte `v`, in `0..=1`.
+pub(crate) fn to_linear(v: u8) -> f32 {
+    LINEAR[usize::from(v)]
+}
+
+/// The sRGB-encoded byte nearest to linear light `v`, saturating outside
+/// `0..=1`.
+pub(crate) fn from_linear(v: f32) -> u8 {
+    THRESHOLDS.partition_point(|&t| t <= v) as u8
+}
@@ -1,7 +1,14 @@
 #![cfg(not(any(feature = "gpu-vulkan", feature = "gpu-gles")))]
 
-use image::{DynamicImage, ImageBuffer, Rgba};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use image::{DynamicImage, GenericImageView, ImageBuffer, Rgba};
+
+use super::cache::RenderCache;
+use super::frame::{DEFAULT_CACHE_BYTES, FrameBuffer, Rect, scroll};
+use super::mip::{MipPyramid, PendingMips};
+use super::srgb::{from_linear, to_linear};
 use super::*;
 use crate::viewport::ViewportState;
 
@@ -10,6 +17,17 @@ fn red_4x4() -> DynamicImage {
     DynamicImage::ImageRgba8(buf)
 }
 
+/// `image` as a [`Source`] identified as still image `id`.
+fn identified(image: &DynamicImage, id: u64) -> Source<'_> {
+    Source {
+        id: Some(ImageId {
+            image: id,
+            frame: 0,
+        }),
+        ..image.into()
+    }
+}
+
 fn argb_at(buf: &[u8], dst_w: u32, x: u32, y: u32) -> (u8, u8, u8, u8) {
     let i = (y * dst_w + x) as usize * 4;
     // stored as [B, G, R, A]
@@ -61,3 +79,878 @@ fn rotation_90_swaps_dimensions() {
     let buf = render(&src, &vp, 8, 8, FilterMethod::Nearest);
     assert_eq!(buf.len(), 8 * 8 * 4);
 }
+
+/// A 3×2 image with a distinct colour per pixel: the red channel encodes x and
+/// the green channel encodes y.
+fn gradient_3x2() -> DynamicImage {
+    let buf = ImageBuffer::from_fn(3, 2, |x, y| Rgba([x as u8 * 100, y as u8 * 100, 0, 255]));
+    DynamicImage::ImageRgba8(buf)
+}
+
+fn render_exact(vp: &ViewportState, dst_w: u32, dst_h: u32) -> Vec<u8> {
+    render(&gradient_3x2(), vp, dst_w, dst_h, FilterMethod::Nearest)
+}
+
+#[test]
+fn flip_horizontal_mirrors_columns() {
+    let mut vp = ViewportState::default();
+    vp.flip_horizontal();
+    let buf = render_exact(&vp, 3, 2);
+    for y in 0..2 {
+        for x in 0..3 {
+            let (r, g, _, _) = argb_at(&buf, 3, x, y);
+            assert_eq!(
+                (r, g),
+                ((2 - x) as u8 * 100, y as u8 * 100),
+                "pixel ({x},{y})"
+            );
+        }
+    }
+}
+
+#[test]
+fn flip_vertical_mirrors_rows() {
+    let mut vp = ViewportState::default();
+    vp.flip_vertical();
+    let buf = render_exact(&vp, 3, 2);
+    for y in 0..2 {
+        for x in 0..3 {
+            let (r, g, _, _) = argb_at(&buf, 3, x, y);
+            assert_eq!(
+                (r, g),
+                (x as u8 * 100, (1 - y) as u8 * 100),
+                "pixel ({x},{y})"
+            );
+        }
+    }
+}
+
+#[test]
+fn both_flips_equal_rotation_180() {
+    let mut flipped = ViewportState::default();
+    flipped.flip_horizontal();
+    flipped.flip_vertical();
+    let mut rotated = ViewportState::default();
+    rotated.rotate_right();
+    rotated.rotate_right();
+    assert_eq!(render_exact(&flipped, 3, 2), render_exact(&rotated, 3, 2));
+}
+
+#[test]
+fn flips_apply_after_rotation() {
+    // rotate 90° + flip both == rotate 270°
+    let mut flipped = ViewportState::default();
+    flipped.rotate_right();
+    flipped.flip_horizontal();
+    flipped.flip_vertical();
+    let mut rotated = ViewportState::default();
+    rotated.rotate_left();
+    assert_eq!(render_exact(&flipped, 2, 3), render_exact(&rotated, 2, 3));
+
+    // After a 90° rotation the displayed image is 2 wide × 3 tall; a
+    // horizontal flip must swap those two on-screen columns.
+    let mut vp = ViewportState::default();
+    vp.rotate_right();
+    let plain = render_exact(&vp, 2, 3);
+    vp.flip_horizontal();
+    let mirrored = render_exact(&vp, 2, 3);
+    for y in 0..3 {
+        assert_eq!(argb_at(&plain, 2, 0, y), argb_at(&mirrored, 2, 1, y));
+        assert_eq!(argb_at(&plain, 2, 1, y), argb_at(&mirrored, 2, 0, y));
+    }
+}
+
+/// A 6×5 image with a distinct colour per pixel.
+fn gradient_6x5() -> DynamicImage {
+    let buf = ImageBuffer::from_fn(6, 5, |x, y| Rgba([x as u8 * 40, y as u8 * 50, 7, 255]));
+    DynamicImage::ImageRgba8(buf)
+}
+
+#[test]
+fn render_regions_matches_full_render_and_leaves_the_rest() {
+    let src = gradient_6x5();
+    let mut vp = ViewportState {
+        scale: 2.0,
+        offset: (3.0, -2.0),
+        ..ViewportState::default()
+    };
+    vp.rotate_right();
+    let full = render(&src, &vp, 16, 14, FilterMethod::Nearest);
+
+    let region = Rect {
+        x: 2,
+        y: 5,
+        w: 9,
+        h: 4,
+    };
+    let mut buf = vec![0xAAu8; 16 * 14 * 4];
+    render_regions(
+        &src,
+        &vp,
+        &mut buf,
+        16,
+        14,
+        &[region],
+        FilterMethod::Nearest,
+    );
+    for y in 0..14 {
+        for x in 0..16 {
+            let inside = region.intersect(&Rect { x, y, w: 1, h: 1 }).is_some();
+            let expected = if inside {
+                argb_at(&full, 16, x, y)
+            } else {
+                (0xAA, 0xAA, 0xAA, 0xAA)
+            };
+            assert_eq!(argb_at(&buf, 16, x, y), expected, "pixel ({x},{y})");
+        }
+    }
+}
+
+#[test]
+fn frame_buffer_pan_matches_full_render() {
+    let src = gradient_6x5();
+    let mut vp = ViewportState {
+        scale: 3.0,
+        ..ViewportState::default()
+    };
+    let mut frame = FrameBuffer::default();
+    let damage = frame.render(identified(&src, 1), &vp, 20, 12, FilterMethod::Nearest);
+    assert_eq!(damage, vec![Rect::full(20, 12)]);
+
+    for offset in [
+        (4.0, -3.0),
+        (-7.0, 2.0),
+        (0.0, 5.0),
+        (-30.0, 0.0),
+        (1.0, 1.0),
+    ] {
+        vp.offset = offset;
+        frame.render(identified(&src, 1), &vp, 20, 12, FilterMethod::Nearest);
+        let expected = render(&src, &vp, 20, 12, FilterMethod::Nearest);
+        assert!(frame.pixels() == expected.as_slice(), "offset {offset:?}");
+    }
+}
+
+#[test]
+fn frame_buffer_damages_old_and_new_image_area() {
+    // 4×4 image centred in 8×8 at (2, 2)
+    let src = red_4x4();
+    let mut vp = ViewportState::default();
+    let mut frame = FrameBuffer::default();
+    frame.render(identified(&src, 1), &vp, 8, 8, FilterMethod::Nearest);
+
+    assert!(
+        frame
+            .render(identified(&src, 1), &vp, 8, 8, FilterMethod::Nearest)
+            .is_empty()
+    );
+
+    vp.offset = (3.0, 0.0);
+    let damage = frame.render(identified(&src, 1), &vp, 8, 8, FilterMethod::Nearest);
+    assert_eq!(
+        damage,
+        vec![
+            Rect {
+                x: 2,
+                y: 2,
+                w: 4,
+                h: 4
+            },
+            Rect {
+                x: 5,
+                y: 2,
+                w: 3,
+                h: 4
+            },
+        ]
+    );
+
+    let damage = frame.render(identified(&src, 1), &vp, 10, 8, FilterMethod::Nearest);
+    assert_eq!(damage, vec![Rect::full(10, 8)]);
+
+    frame.invalidate();
+    let damage = frame.render(identified(&src, 1), &vp, 10, 8, FilterMethod::Nearest);
+    assert_eq!(damage, vec![Rect::full(10, 8)]);
+}
+
+#[test]
+fn frame_buffer_always_redraws_a_source_without_identity() {
+    let vp = ViewportState::default();
+    let mut frame = FrameBuffer::default();
+    frame.render(&gradient_6x5(), &vp, 8, 8, FilterMethod::Nearest);
+    let damage = frame.render(&red_4x4(), &vp, 8, 8, FilterMethod::Nearest);
+    assert!(!damage.is_empty());
+    assert_eq!(
+        frame.pixels(),
+        render(&red_4x4(), &vp, 8, 8, FilterMethod::Nearest).as_slice()
+    );
+}
+
+#[test]
+fn frame_buffer_reuses_the_previous_frame_and_cached_transforms() {
+    let src = gradient_6x5();
+    let mut vp = ViewportState {
+        scale: 2.0,
+        ..ViewportState::default()
+    };
+    let mut frame = FrameBuffer::default();
+    assert!(!frame.reuses(identified(&src, 1), &vp, FilterMethod::Lanczos3));
+    frame.render(identified(&src, 1), &vp, 8, 8, FilterMethod::Lanczos3);
+    vp.offset = (3.0, -2.0);
+    assert!(frame.reuses(identified(&src, 1), &vp, FilterMethod::Lanczos3));
+    assert!(!frame.reuses(identified(&src, 1), &vp, FilterMethod::Triangle));
+    assert!(!frame.reuses(&src, &vp, FilterMethod::Lanczos3));
+
+    // A transform drawn earlier stays cached after the scale changes.
+    vp.scale = 3.0;
+    frame.render(identified(&src, 1), &vp, 8, 8, FilterMethod::Lanczos3);
+    vp.scale = 2.0;
+    assert!(frame.reuses(identified(&src, 1), &vp, FilterMethod::Lanczos3));
+}
+
+#[test]
+fn frame_buffer_redraws_when_the_image_changes() {
+    let mut vp = ViewportState::default();
+    let mut frame = FrameBuffer::default();
+    frame.render(
+        identified(&gradient_6x5(), 1),
+        &vp,
+        8,
+        8,
+        FilterMethod::Nearest,
+    );
+    let damage = frame.render(identified(&red_4x4(), 2), &vp, 8, 8, FilterMethod::Nearest);
+    assert_eq!(
+        frame.pixels(),
+        render(&red_4x4(), &vp, 8, 8, FilterMethod::Nearest).as_slice()
+    );
+    assert_eq!(damage.len(), 2);
+
+    // Zoom changes the cached transform as well.
+    vp.scale = 2.0;
+    frame.render(identified(&red_4x4(), 2), &vp, 8, 8, FilterMethod::Nearest);
+    assert_eq!(
+        frame.pixels(),
+        render(&red_4x4(), &vp, 8, 8, FilterMethod::Nearest).as_slice()
+    );
+}
+
+/// A 13×9 image of pseudo-random pixels.
+fn noise_13x9() -> DynamicImage {
+    let buf = ImageBuffer::from_fn(13, 9, |x, y| {
+        let v = (x * 7919 + y * 104_729) ^ (x * y * 31);
+        Rgba([
+            v as u8,
+            (v >> 3) as u8,
+            (v >> 7) as u8,
+            128 + (v >> 2) as u8 % 128,
+        ])
+    });
+    DynamicImage::ImageRgba8(buf)
+}
+
+const FILTERS: [FilterMethod; 5] = [
+    FilterMethod::Nearest,
+    FilterMethod::Triangle,
+    FilterMethod::CatmullRom,
+    FilterMethod::Gaussian,
+    FilterMethod::Lanczos3,
+];
+
+#[test]
+fn resize_window_matches_imageops_resize() {
+    let src = noise_13x9();
+    let rgb = DynamicImage::ImageRgb8(src.to_rgb8());
+    for filter in FILTERS {
+        for size in [(5, 4), (13, 20), (40, 27), (13, 9)] {
+            for image in [&src, &rgb] {
+                let full = imageops::resize(image, size.0, size.1, filter.into());
+                let window = Rect::full(size.0, size.1);
+                assert!(
+                    resample::resize_window(image, size, window, filter, false, 1) == full,
+                    "{filter:?} {size:?}"
+                );
+
+                let window = Rect {
+                    x: size.0 / 3,
+                    y: size.1 / 2,
+                    w: size.0 / 2,
+                    h: size.1 - size.1 / 2,
+                };
+                let expected =
+                    imageops::crop_imm(&full, window.x, window.y, window.w, window.h).to_image();
+                assert!(
+                    resample::resize_window(image, size, window, filter, false, 1) == expected,
+                    "{filter:?} {size:?} {window:?}"
+                );
+            }
+        }
+    }
+}
+
+/// Filters image-rs lacks, checked against properties instead.
+const EXTRA_FILTERS: [FilterMethod; 6] = [
+    FilterMethod::Box,
+    FilterMethod::SharpBilinear,
+    FilterMethod::Hermite,
+    FilterMethod::Mitchell,
+    FilterMethod::Lanczos2,
+    FilterMethod::Lanczos4,
+];
+
+#[test]
+fn extra_filter_windows_match_the_full_resize_and_keep_flat_areas() {
+    let src = noise_13x9();
+    let flat = DynamicImage::ImageRgba8(ImageBuffer::from_pixel(13, 9, Rgba([9, 80, 200, 255])));
+    for filter in EXTRA_FILTERS {
+        for size in [(5, 4), (40, 27)] {
+            let full =
+                resample::resize_window(&src, size, Rect::full(size.0, size.1), filter, false, 1);
+            let window = Rect {
+                x: 1,
+                y: size.1 / 2,
+                w: size.0 - 2,
+                h: size.1 - size.1 / 2,
+            };
+            let expected =
+                imageops::crop_imm(&full, window.x, window.y, window.w, window.h).to_image();
+            assert!(
+                resample::resize_window(&src, size, window, filter, false, 1) == expected,
+                "{filter:?} {size:?}"
+            );
+
+            let out =
+                resample::resize_window(&flat, size, Rect::full(size.0, size.1), filter, false, 1);
+            assert!(
+                out.pixels().all(|p| p.0 == [9, 80, 200, 255]),
+                "{filter:?} {size:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn box_downscale_averages_whole_blocks() {
+    let src = noise_13x9().crop_imm(0, 0, 12, 8);
+    let out = resample::resize_window(&src, (4, 2), Rect::full(4, 2), FilterMethod::Box, false, 1);
+    let rgba = src.to_rgba8();
+    for (x, y, px) in out.enumerate_pixels() {
+        for c in 0..4 {
+            let sum: u32 = (0..3)
+                .flat_map(|dx| (0..4).map(move |dy| (x * 3 + dx, y * 4 + dy)))
+                .map(|(sx, sy)| u32::from(rgba.get_pixel(sx, sy)[c]))
+                .sum();
+            assert!(px[c].abs_diff((sum as f32 / 12.0).round() as u8) <= 1);
+        }
+    }
+}
+
+#[test]
+fn sharp_bilinear_is_nearest_at_whole_factors_and_blends_only_edges() {
+    let src = noise_13x9();
+    let out = resample::resize_window(
+        &src,
+        (39, 27),
+        Rect::full(39, 27),
+        FilterMethod::SharpBilinear,
+        false,
+        1,
+    );
+    let nearest = imageops::resize(&src, 39, 27, imageops::FilterType::Nearest);
+    assert!(out == nearest);
+
+    // At 3.5× the blend between two source pixels is 3.5 / 3 output
+    // pixels wide, so it touches at most two of them; bilinear blends
+    // nearly all.
+    let row = DynamicImage::ImageRgba8(ImageBuffer::from_fn(4, 1, |x, _| {
+        Rgba([if x % 2 == 0 { 0 } else { 255 }, 0, 0, 255])
+    }));
+    let out = resample::resize_window(
+        &row,
+        (14, 1),
+        Rect::full(14, 1),
+        FilterMethod::SharpBilinear,
+        false,
+        1,
+    );
+    let blended = out.pixels().filter(|p| p[0] != 0 && p[0] != 255).count();
+    assert!(blended <= 2 * 3, "{blended} blended pixels");
+}
+
+/// Columns alternating black and white, half transparent.
+fn stripes_8x4() -> DynamicImage {
+    DynamicImage::ImageRgba8(ImageBuffer::from_fn(8, 4, |x, _| {
+        let v = if x % 2 == 0 { 0 } else { 255 };
+        Rgba([v, v, v, 128])
+    }))
+}
+
+#[test]
+fn linear_light_downscale_keeps_the_mean_brightness() {
+    let src = stripes_8x4();
+    let full = Rect::full(4, 2);
+    let encoded = resample::resize_window(&src, (4, 2), full, FilterMethod::Box, false, 1);
+    let linear = resample::resize_window(&src, (4, 2), full, FilterMethod::Box, true, 1);
+    // Half of white's light encodes to 188, not 128; alpha is filtered
+    // as is.
+    assert!(encoded.pixels().all(|p| p.0 == [128, 128, 128, 128]));
+    assert!(linear.pixels().all(|p| p.0 == [188, 188, 188, 128]));
+
+    let noise = noise_13x9();
+    let flat = DynamicImage::ImageRgba8(ImageBuffer::from_pixel(13, 9, Rgba([9, 80, 200, 255])));
+    for filter in [FilterMethod::Triangle, FilterMethod::Lanczos3] {
+        for size in [(5, 4), (29, 20)] {
+            let full =
+                resample::resize_window(&noise, size, Rect::full(size.0, size.1), filter, true, 1);
+            let window = Rect {
+                x: 1,
+                y: 2,
+                w: size.0 - 2,
+                h: size.1 - 2,
+            };
+            let expected =
+                imageops::crop_imm(&full, window.x, window.y, window.w, window.h).to_image();
+            assert!(
+                resample::resize_window(&noise, size, window, filter, true, 1) == expected,
+                "{filter:?} {size:?}"
+            );
+
+            let out =
+                resample::resize_window(&flat, size, Rect::full(size.0, size.1), filter, true, 1);
+            assert!(
+                out.pixels().all(|p| p.0 == [9, 80, 200, 255]),
+                "{filter:?} {size:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn frame_buffer_resamples_in_linear_light_when_asked() {
+    let src = stripes_8x4();
+    let vp = ViewportState {
+        scale: 0.5,
+        ..ViewportState::default()
+    };
+    let mut frame = FrameBuffer::default();
+    frame.render(identified(&src, 1), &vp, 4, 2, FilterMethod::Box);
+    assert_eq!(argb_at(frame.pixels(), 4, 1, 1), (128, 128, 128, 128));
+
+    let mut frame = FrameBuffer::default().with_linear_light(true);
+    frame.render(identified(&src, 1), &vp, 4, 2, FilterMethod::Box);
+    assert_eq!(argb_at(frame.pixels(), 4, 1, 1), (188, 188, 188, 128));
+}
+
+#[test]
+fn transform_window_matches_full_transform() {
+    let src = noise_13x9();
+    for rotation in 0..4 {
+        for (flip_h, flip_v) in [(false, false), (true, false), (false, true), (true, true)] {
+            let mut vp = ViewportState {
+                scale: 2.5,
+                flip_h,
+                flip_v,
+                ..ViewportState::default()
+            };
+            for _ in 0..rotation {
+                vp.rotate_right();
+            }
+            let (rot_w, rot_h) = rotated_size(&src, &vp);
+            let full = transform(
+                (&src).into(),
+                &vp,
+                FilterMethod::CatmullRom,
+                false,
+                Rect::full(rot_w, rot_h),
+                1,
+            );
+            let window = Rect {
+                x: 3,
+                y: 5,
+                w: rot_w - 10,
+                h: rot_h - 9,
+            };
+            let expected =
+                imageops::crop_imm(&full, window.x, window.y, window.w, window.h).to_image();
+            assert!(
+                transform(
+                    (&src).into(),
+                    &vp,
+                    FilterMethod::CatmullRom,
+                    false,
+                    window,
+                    1
+                ) == expected,
+                "rotation {} flips {flip_h} {flip_v}",
+                vp.rotation
+            );
+        }
+    }
+}
+
+#[test]
+fn culled_frame_buffer_matches_full_render() {
+    let src = noise_13x9();
+    let mut vp = ViewportState {
+        scale: 6.0,
+        ..ViewportState::default()
+    };
+    vp.rotate_left();
+    // No cache: every redraw resamples only the visible regions.
+    let mut frame = FrameBuffer::default().with_cache_limit(0);
+    for offset in [(0.0, 0.0), (5.0, -9.0), (-20.0, 3.0), (-21.0, 4.0)] {
+        vp.offset = offset;
+        frame.render(identified(&src, 1), &vp, 24, 30, FilterMethod::Lanczos3);
+        // Reference: the whole image transformed, then placed.
+        let mut whole = FrameBuffer::default();
+        whole.render(identified(&src, 1), &vp, 24, 30, FilterMethod::Lanczos3);
+        assert!(frame.pixels() == whole.pixels(), "offset {offset:?}");
+    }
+}
+
+#[test]
+fn windowed_frame_buffer_matches_full_render() {
+    let src = noise_13x9();
+    let mut vp = ViewportState {
+        scale: 9.0,
+        flip_h: true,
+        ..ViewportState::default()
+    };
+    vp.rotate_right();
+    let mut frame = FrameBuffer::default();
+    // Pans within the cached window, beyond it, and back.
+    for offset in [
+        (0.0, 0.0),
+        (4.0, -3.0),
+        (-30.0, 25.0),
+        (-31.0, 24.0),
+        (2.0, 1.0),
+    ] {
+        vp.offset = offset;
+        frame.render(identified(&src, 1), &vp, 24, 30, FilterMethod::Lanczos3);
+        let expected = render(&src, &vp, 24, 30, FilterMethod::Lanczos3);
+        assert!(frame.pixels() == expected.as_slice(), "offset {offset:?}");
+    }
+}
+
+#[test]
+fn frame_buffer_caches_only_the_visible_window() {
+    let src = noise_13x9();
+    let zoomed = ViewportState {
+        scale: 100.0,
+        ..ViewportState::default()
+    };
+    // The whole 1300×900 transform is far beyond the ceiling; the 24×30
+    // window and its margins are not.
+    let mut frame = FrameBuffer::default().with_cache_limit(64 * 1024);
+    frame.render(identified(&src, 1), &zoomed, 24, 30, FilterMethod::Lanczos3);
+    let other = ViewportState {
+        scale: 50.0,
+        ..zoomed
+    };
+    frame.render(identified(&src, 1), &other, 24, 30, FilterMethod::Lanczos3);
+    assert!(frame.reuses(identified(&src, 1), &zoomed, FilterMethod::Lanczos3));
+    // A pan far outside the window needs resampling again.
+    let panned = ViewportState {
+        offset: (400.0, 0.0),
+        ..zoomed
+    };
+    assert!(!frame.reuses(identified(&src, 1), &panned, FilterMethod::Lanczos3));
+}
+
+#[test]
+fn thread_count_does_not_change_the_output() {
+    let src = noise_13x9();
+    for cache in [0, DEFAULT_CACHE_BYTES] {
+        let render = |threads| {
+            let mut vp = ViewportState {
+                scale: 23.0,
+                offset: (7.0, -5.0),
+                ..ViewportState::default()
+            };
+            vp.rotate_right();
+            let mut frame = FrameBuffer::default()
+                .with_cache_limit(cache)
+                .with_threads(threads);
+            frame.render(identified(&src, 1), &vp, 250, 230, FilterMethod::Lanczos3);
+            // A pan redraws the exposed strips only.
+            vp.offset = (-40.0, 31.0);
+            frame.render(identified(&src, 1), &vp, 250, 230, FilterMethod::Lanczos3);
+            frame.pixels().to_vec()
+        };
+        let single = render(1);
+        for threads in [2, 3, 8] {
+            assert!(
+                render(threads) == single,
+                "{threads} threads, cache {cache}"
+            );
+        }
+    }
+}
+
+#[test]
+fn zoom_out_resamples_from_the_mip_level() {
+    let src = noise_13x9().resize_exact(64, 32, imageops::FilterType::Nearest);
+    let mips = MipPyramid::build(&src, false);
+    let source = Source {
+        mips: Some(&mips),
+        ..identified(&src, 1)
+    };
+    let vp = ViewportState {
+        scale: 0.25,
+        ..ViewportState::default()
+    };
+    // 16×8 is exactly level 2, which is then copied unscaled.
+    let (_, level) = mips.level_for((16, 8)).unwrap();
+    let expected = render(
+        level,
+        &ViewportState::default(),
+        20,
+        10,
+        FilterMethod::Lanczos3,
+    );
+    assert!(render(source, &vp, 20, 10, FilterMethod::Lanczos3) == expected);
+
+    let mut frame = FrameBuffer::default();
+    frame.render(source, &vp, 20, 10, FilterMethod::Lanczos3);
+    assert!(frame.pixels() == expected);
+    // The pyramid replaces the full image once it arrives.
+    let mut frame = FrameBuffer::default();
+    frame.render(identified(&src, 1), &vp, 20, 10, FilterMethod::Lanczos3);
+    assert!(frame.pixels() != expected);
+    assert!(
+        !frame
+            .render(source, &vp, 20, 10, FilterMethod::Lanczos3)
+            .is_empty()
+    );
+    assert!(frame.pixels() == expected);
+
+    // Nearest-neighbour keeps sampling the full image.
+    assert!(
+        render(source, &vp, 20, 10, FilterMethod::Nearest)
+            == render(&src, &vp, 20, 10, FilterMethod::Nearest)
+    );
+}
+
+#[test]
+fn render_cache_reuses_entries_and_evicts_least_recently_used() {
+    let image = |w| ImageBuffer::from_pixel(w, 1, Rgba([0u8; 4]));
+    let made = std::cell::Cell::new(0);
+    // Room for 4 pixels' worth of images.
+    let mut cache = RenderCache::new(16);
+    let get = |cache: &mut RenderCache<u32>, key: u32, w: u32| {
+        if cache.find(|&k| k == key).is_none() {
+            made.set(made.get() + 1);
+            cache.insert(key, image(w));
+        }
+    };
+
+    get(&mut cache, 1, 2);
+    get(&mut cache, 2, 2);
+    get(&mut cache, 1, 2);
+    assert_eq!(cache.bytes(), 16);
+
+    // Key 2 is least recently used and makes room for key 3.
+    get(&mut cache, 3, 2);
+    get(&mut cache, 1, 2);
+    get(&mut cache, 2, 2);
+    assert_eq!(cache.bytes(), 16);
+    assert!(cache.contains(|&k| k == 1) && !cache.contains(|&k| k == 3));
+
+    // Larger than the ceiling: returned but not cached.
+    get(&mut cache, 4, 5);
+    assert_eq!(cache.bytes(), 16);
+    assert_eq!(made.get(), 5);
+}
+
+#[test]
+fn render_cache_returns_the_inserted_image() {
+    let mut cache = RenderCache::new(0);
+    let image = cache.insert(7, ImageBuffer::from_pixel(3, 2, Rgba([9u8; 4])));
+    assert_eq!(image.dimensions(), (3, 2));
+    assert_eq!(cache.bytes(), 0);
+}
+
+/// A `w × h` buffer whose pixel `(x, y)` holds `[x, y, 0, 1]`.
+fn numbered(w: u32, h: u32) -> Vec<u8> {
+    (0..h)
+        .flat_map(|y| (0..w).flat_map(move |x| [x as u8, y as u8, 0, 1]))
+        .collect()
+}
+
+fn pixel(buf: &[u8], w: u32, x: u32, y: u32) -> [u8; 4] {
+    let i = (y * w + x) as usize * 4;
+    buf[i..i + 4].try_into().unwrap()
+}
+
+#[test]
+fn scroll_moves_pixels_and_reports_exposed_strips() {
+    for (dx, dy) in [(2, 1), (-2, 1), (2, -1), (-1, -2), (0, 3), (-4, 0)] {
+        let (w, h) = (7u32, 5u32);
+        let mut buf = numbered(w, h);
+        let exposed = scroll(&mut buf, w, h, dx, dy);
+        for y in 0..h {
+            for x in 0..w {
+                let (ox, oy) = (x as i32 - dx, y as i32 - dy);
+                let stale = exposed
+                    .iter()
+                    .any(|r| r.intersect(&Rect { x, y, w: 1, h: 1 }).is_some());
+                let moved = (0..w as i32).contains(&ox) && (0..h as i32).contains(&oy);
+                assert_eq!(stale, !moved, "({x},{y}) shift ({dx},{dy})");
+                if moved {
+                    assert_eq!(
+                        pixel(&buf, w, x, y),
+                        [ox as u8, oy as u8, 0, 1],
+                        "({x},{y}) shift ({dx},{dy})"
+                    );
+                }
+            }
+        }
+        let area: u32 = exposed.iter().map(|r| r.w * r.h).sum();
+        let kept = (w - dx.unsigned_abs()) * (h - dy.unsigned_abs());
+        assert_eq!(area, w * h - kept, "strips overlap for ({dx},{dy})");
+    }
+}
+
+#[test]
+fn scroll_past_the_edge_exposes_everything() {
+    let mut buf = numbered(4, 3);
+    assert_eq!(scroll(&mut buf, 4, 3, 4, 0), vec![Rect::full(4, 3)]);
+    assert_eq!(scroll(&mut buf, 4, 3, 0, -3), vec![Rect::full(4, 3)]);
+    assert!(scroll(&mut buf, 4, 3, 0, 0).is_empty());
+    assert_eq!(buf, numbered(4, 3));
+}
+
+#[test]
+fn rect_intersect_and_clip() {
+    let a = Rect {
+        x: 1,
+        y: 1,
+        w: 4,
+        h: 4,
+    };
+    let b = Rect {
+        x: 3,
+        y: 0,
+        w: 5,
+        h: 2,
+    };
+    assert_eq!(
+        a.intersect(&b),
+        Some(Rect {
+            x: 3,
+            y: 1,
+            w: 2,
+            h: 1
+        })
+    );
+    assert_eq!(a.intersect(&Rect::full(1, 1)), None);
+    assert_eq!(
+        Rect::clipped((-2, 3), (5, 9), 10, 8),
+        Some(Rect {
+            x: 0,
+            y: 3,
+            w: 3,
+            h: 5
+        })
+    );
+    assert_eq!(Rect::clipped((10, 0), (5, 5), 10, 8), None);
+    assert!(Rect::full(0, 4).is_empty());
+}
+
+#[test]
+fn levels_average_blocks_down_to_one_pixel() {
+    // Columns alternate 0 and 200, rows add 0 and 4; odd edges drop.
+    let src = DynamicImage::ImageRgba8(ImageBuffer::from_fn(5, 3, |x, y| {
+        let v = (x % 2) as u8 * 200 + (y % 2) as u8 * 4;
+        Rgba([v, v, v, 255])
+    }));
+    let mips = MipPyramid::build(&src, false);
+    assert_eq!(mips.len(), 2);
+
+    let (n, half) = mips.level_for((2, 1)).unwrap();
+    assert_eq!((n, half.dimensions()), (1, (2, 1)));
+    // (0 + 200 + 4 + 204 + 2) / 4
+    assert_eq!(half.get_pixel(1, 0), Rgba([102, 102, 102, 255]));
+    let (n, last) = mips.level_for((1, 1)).unwrap();
+    assert_eq!((n, last.dimensions()), (2, (1, 1)));
+    assert!(mips.level_for((3, 1)).is_none());
+}
+
+#[test]
+fn linear_levels_average_light() {
+    let src = DynamicImage::ImageRgba8(ImageBuffer::from_fn(2, 2, |x, _| {
+        let v = if x == 0 { 0 } else { 255 };
+        Rgba([v, v, v, 255])
+    }));
+    let level = |linear| {
+        let mips = MipPyramid::build(&src, linear);
+        mips.level_for((1, 1)).unwrap().1.get_pixel(0, 0)
+    };
+    assert_eq!(level(false), Rgba([128, 128, 128, 255]));
+    assert_eq!(level(true), Rgba([188, 188, 188, 255]));
+}
+
+#[test]
+fn level_for_picks_the_smallest_level_covering_the_size() {
+    let src = DynamicImage::new_rgba8(64, 16);
+    let mips = MipPyramid::build(&src, false);
+    // 32×8, 16×4, 8×2, 4×1, 2×1, 1×1
+    assert_eq!(mips.len(), 6);
+    let size = |wh| mips.level_for(wh).map(|(n, l)| (n, l.dimensions()));
+    assert_eq!(size((16, 4)), Some((2, (16, 4))));
+    assert_eq!(size((15, 3)), Some((2, (16, 4))));
+    assert_eq!(size((17, 3)), Some((1, (32, 8))));
+    assert_eq!(size((33, 3)), None);
+}
+
+#[test]
+fn pending_mips_finish_in_the_background() {
+    let pending = PendingMips::spawn(Arc::new(DynamicImage::new_rgba8(8, 8)), false);
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while pending.get().is_none() {
+        assert!(Instant::now() < deadline, "pyramid never finished");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(pending.get().unwrap().len(), 3);
+}
+
+#[test]
+fn pending_mips_report_once_so_a_zoomed_out_frame_redraws() {
+    let src = Arc::new(noise_13x9().resize_exact(64, 32, imageops::FilterType::Nearest));
+    let mut pending = PendingMips::spawn(Arc::clone(&src), false);
+    let vp = ViewportState {
+        scale: 0.25,
+        ..ViewportState::default()
+    };
+    let deadline = Instant::now() + Duration::from_secs(10);
+    // The caller keeps checking back while the pyramid is being built...
+    while pending.is_building() {
+        assert!(Instant::now() < deadline, "pyramid never finished");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    // ...and is told once that it can redraw from a mip level.
+    assert!(pending.take_ready());
+    assert!(!pending.take_ready());
+    let source = Source {
+        mips: pending.get(),
+        ..Source::from(&*src)
+    };
+    assert_eq!(source.mip_level(&vp, FilterMethod::Lanczos3), 2);
+    assert_eq!(source.mip_level(&vp, FilterMethod::Nearest), 0);
+    assert_eq!(
+        source.mip_level(&ViewportState::default(), FilterMethod::Lanczos3),
+        0
+    );
+}
+
+#[test]
+fn bytes_survive_a_round_trip_through_linear_light() {
+    for v in 0..=255 {
+        assert_eq!(from_linear(to_linear(v)), v);
+    }
+    assert_eq!((to_linear(0), to_linear(255)), (0.0, 1.0));
+    assert!((to_linear(128) - 0.2159).abs() < 1e-4);
+    assert_eq!(from_linear(0.5), 188);
+    assert_eq!((from_linear(-0.5), from_linear(2.0)), (0, 255));
+}
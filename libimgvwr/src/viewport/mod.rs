@@ -1,16 +1,82 @@
-//! Viewer-side transform state: scale, pan offset, and rotation.
+//! Viewer-side transform state: scale, pan offset, rotation, and mirroring.
 //!
 //! [`ViewportState`] is pure data with no I/O or rendering. All mutating
 //! methods take explicit min/max parameters so the caller (the binary crate)
 //! drives policy from config values without `libimgvwr` knowing about config.
+//! [`FitMode`] describes how an image is sized to the window and [`Edge`]
+//! names the image edges the view can jump to.
 
 #[cfg(test)]
 mod tests;
 
-/// The current pan, zoom, and rotation state for the displayed image.
+/// How the image scale is chosen relative to the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FitMode {
+    /// Scale up or down so the whole image is visible.
+    #[default]
+    Fit,
+    /// Like [`FitMode::Fit`], but never enlarge images smaller than the window.
+    ShrinkToFit,
+    /// Cover the whole window; the overflowing axis is cropped.
+    Fill,
+    /// Match the window width; tall images start at their top edge.
+    FitWidth,
+    /// Match the window height; wide images start at their left edge.
+    FitHeight,
+    /// One image pixel per display pixel.
+    ActualSize,
+}
+
+impl FitMode {
+    /// Every mode, in the order [`FitMode::next`] cycles through them.
+    pub const ALL: [FitMode; 6] = [
+        FitMode::Fit,
+        FitMode::ShrinkToFit,
+        FitMode::Fill,
+        FitMode::FitWidth,
+        FitMode::FitHeight,
+        FitMode::ActualSize,
+    ];
+
+    /// The mode after `self` in [`FitMode::ALL`], wrapping around.
+    pub fn next(self) -> FitMode {
+        let i = FitMode::ALL.iter().position(|&m| m == self).unwrap_or(0);
+        FitMode::ALL[(i + 1) % FitMode::ALL.len()]
+    }
+
+    /// Unclamped scale that applies this mode to an `image`-sized source
+    /// shown in a `window`-sized surface. Both sizes are `(width, height)`
+    /// in pixels, with `image` already in display orientation.
+    pub fn scale(self, image: (u32, u32), window: (u32, u32)) -> f32 {
+        let sw = window.0 as f32 / image.0.max(1) as f32;
+        let sh = window.1 as f32 / image.1.max(1) as f32;
+        match self {
+            FitMode::Fit => sw.min(sh),
+            FitMode::ShrinkToFit => sw.min(sh).min(1.0),
+            FitMode::Fill => sw.max(sh),
+            FitMode::FitWidth => sw,
+            FitMode::FitHeight => sh,
+            FitMode::ActualSize => 1.0,
+        }
+    }
+}
+
+/// An edge of the displayed image, used by [`ViewportState::align_edge`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// The current pan, zoom, rotation, and mirroring state for the displayed
+/// image.
 ///
 /// Rotation is restricted to multiples of 90°, stored as `0`, `90`, `180`,
-/// or `270`. Offset is unconstrained — the image may be panned fully
+/// or `270`. Flips mirror the image *as displayed*, i.e. they are applied
+/// after rotation, so a horizontal flip always swaps the on-screen left and
+/// right edges. Offset is unconstrained — the image may be panned fully
 /// off-screen.
 pub struct ViewportState {
     /// Current zoom factor. `1.0` means one image pixel per display pixel.
@@ -19,6 +85,10 @@ pub struct ViewportState {
     pub offset: (f32, f32),
     /// Clockwise rotation in degrees: `0`, `90`, `180`, or `270`.
     pub rotation: u16,
+    /// Mirror the rotated image left-to-right.
+    pub flip_h: bool,
+    /// Mirror the rotated image top-to-bottom.
+    pub flip_v: bool,
 }
 
 impl Default for ViewportState {
@@ -27,6 +97,8 @@ impl Default for ViewportState {
             scale: 1.0,
             offset: (0.0, 0.0),
             rotation: 0,
+            flip_h: false,
+            flip_v: false,
         }
     }
 }
@@ -71,6 +143,66 @@ impl ViewportState {
         self.rotation = (self.rotation + 90) % 360;
     }
 
+    /// Toggle the horizontal (left-to-right) mirror.
+    pub fn flip_horizontal(&mut self) {
+        self.flip_h = !self.flip_h;
+    }
+
+    /// Toggle the vertical (top-to-bottom) mirror.
+    pub fn flip_vertical(&mut self) {
+        self.flip_v = !self.flip_v;
+    }
+
+    /// Apply `mode` to an `image`-sized source (`(width, height)` before
+    /// rotation) in a `window`-sized surface.
+    ///
+    /// Sets the scale, clamped to `[min_scale, max_scale]`, and recentres the
+    /// image. [`FitMode::FitWidth`] and [`FitMode::FitHeight`] instead align the
+    /// overflowing axis to its start so reading begins at the top or left.
+    /// Rotation and flips are kept; 90° and 270° swap the fitted dimensions.
+    pub fn fit(
+        &mut self,
+        mode: FitMode,
+        image: (u32, u32),
+        window: (u32, u32),
+        min_scale: f32,
+        max_scale: f32,
+    ) {
+        let shown = self.oriented(image);
+        self.scale = mode.scale(shown, window).clamp(min_scale, max_scale);
+        self.offset = (0.0, 0.0);
+        match mode {
+            FitMode::FitWidth => self.align_edge(Edge::Top, image, window),
+            FitMode::FitHeight => self.align_edge(Edge::Left, image, window),
+            _ => {}
+        }
+    }
+
+    /// Pan so that `edge` of an `image`-sized source (`(width, height)`
+    /// before rotation) touches the same edge of the window.
+    ///
+    /// Only the axis of `edge` changes. When the scaled image fits inside the
+    /// window on that axis it is centred instead, since there is nothing
+    /// beyond the edge to reveal.
+    pub fn align_edge(&mut self, edge: Edge, image: (u32, u32), window: (u32, u32)) {
+        let shown = self.oriented(image);
+        let overflow = |len: u32, win: u32| ((len as f32 * self.scale - win as f32) / 2.0).max(0.0);
+        match edge {
+            Edge::Left => self.offset.0 = overflow(shown.0, window.0),
+            Edge::Right => self.offset.0 = -overflow(shown.0, window.0),
+            Edge::Top => self.offset.1 = overflow(shown.1, window.1),
+            Edge::Bottom => self.offset.1 = -overflow(shown.1, window.1),
+        }
+    }
+
+    /// `(width, height)` of `image` after the current rotation.
+    fn oriented(&self, image: (u32, u32)) -> (u32, u32) {
+        match self.rotation {
+            90 | 270 => (image.1, image.0),
+            _ => image,
+        }
+    }
+
     /// Translate the image by `(dx, dy)` pixels. No clamping — the image can
     /// be dragged fully outside the window area.
     pub fn pan(&mut self, dx: f32, dy: f32) {
@@ -78,10 +210,13 @@ impl ViewportState {
         self.offset.1 += dy;
     }
 
-    /// Reset to the default state: scale `1.0`, zero offset, no rotation.
+    /// Reset to the default state: scale `1.0`, zero offset, no rotation,
+    /// no flips.
     pub fn reset(&mut self) {
         self.scale = 1.0;
         self.offset = (0.0, 0.0);
         self.rotation = 0;
+        self.flip_h = false;
+        self.flip_v = false;
     }
 }
@@ -37,6 +37,8 @@ fn rotate_right_cycles() {
     assert_eq!(vp.rotation, 270);
     vp.rotate_right();
     assert_eq!(vp.rotation, 0);
+    assert!(!vp.flip_h);
+    assert!(!vp.flip_v);
 }
 
 #[test]
@@ -50,6 +52,20 @@ fn rotate_left_cycles() {
     assert_eq!(vp.rotation, 90);
     vp.rotate_left();
     assert_eq!(vp.rotation, 0);
+    assert!(!vp.flip_h);
+    assert!(!vp.flip_v);
+}
+
+#[test]
+fn flips_toggle() {
+    let mut vp = ViewportState::default();
+    vp.flip_horizontal();
+    assert!(vp.flip_h);
+    assert!(!vp.flip_v);
+    vp.flip_vertical();
+    assert!(vp.flip_v);
+    vp.flip_horizontal();
+    assert!(!vp.flip_h);
 }
 
 #[test]
@@ -67,8 +83,110 @@ fn reset_restores_defaults() {
     vp.zoom_by(5.0, 0.1, 100.0);
     vp.pan(20.0, 30.0);
     vp.rotate_right();
+    vp.flip_horizontal();
+    vp.flip_vertical();
     vp.reset();
     assert!((vp.scale - 1.0).abs() < f32::EPSILON);
     assert_eq!(vp.offset, (0.0, 0.0));
     assert_eq!(vp.rotation, 0);
+    assert!(!vp.flip_h);
+    assert!(!vp.flip_v);
+}
+
+#[test]
+fn fit_mode_next_cycles_through_all() {
+    let mut mode = FitMode::default();
+    for expected in FitMode::ALL.iter().cycle().skip(1).take(FitMode::ALL.len()) {
+        mode = mode.next();
+        assert_eq!(mode, *expected);
+    }
+    assert_eq!(mode, FitMode::Fit);
+}
+
+#[test]
+fn fit_mode_scales() {
+    // 200×100 image in a 400×400 window.
+    let (img, win) = ((200, 100), (400, 400));
+    assert!((FitMode::Fit.scale(img, win) - 2.0).abs() < f32::EPSILON);
+    assert!((FitMode::ShrinkToFit.scale(img, win) - 1.0).abs() < f32::EPSILON);
+    assert!((FitMode::Fill.scale(img, win) - 4.0).abs() < f32::EPSILON);
+    assert!((FitMode::FitWidth.scale(img, win) - 2.0).abs() < f32::EPSILON);
+    assert!((FitMode::FitHeight.scale(img, win) - 4.0).abs() < f32::EPSILON);
+    assert!((FitMode::ActualSize.scale(img, win) - 1.0).abs() < f32::EPSILON);
+}
+
+#[test]
+fn shrink_to_fit_shrinks_large_images() {
+    let scale = FitMode::ShrinkToFit.scale((800, 400), (400, 400));
+    assert!((scale - 0.5).abs() < f32::EPSILON);
+}
+
+#[test]
+fn fit_clamps_and_recentres() {
+    let mut vp = ViewportState::default();
+    vp.pan(10.0, 10.0);
+    vp.fit(FitMode::Fit, (10, 10), (1000, 1000), 0.1, 50.0);
+    assert!((vp.scale - 50.0).abs() < f32::EPSILON);
+    assert_eq!(vp.offset, (0.0, 0.0));
+}
+
+#[test]
+fn fit_honours_rotation() {
+    let mut vp = ViewportState::default();
+    vp.rotate_right();
+    // 200×100 rotated is 100×200; fitting width 400 gives 4×.
+    vp.fit(FitMode::FitWidth, (200, 100), (400, 400), 0.1, 100.0);
+    assert!((vp.scale - 4.0).abs() < f32::EPSILON);
+    assert_eq!(vp.rotation, 90);
+}
+
+#[test]
+fn fit_width_aligns_top_edge() {
+    let mut vp = ViewportState::default();
+    // 100×300 at 2× is 200×600 in a 200×200 window: 400 px overflow.
+    vp.fit(FitMode::FitWidth, (100, 300), (200, 200), 0.1, 100.0);
+    assert!((vp.scale - 2.0).abs() < f32::EPSILON);
+    assert_eq!(vp.offset, (0.0, 200.0));
+}
+
+#[test]
+fn fit_height_aligns_left_edge() {
+    let mut vp = ViewportState::default();
+    vp.fit(FitMode::FitHeight, (300, 100), (200, 200), 0.1, 100.0);
+    assert_eq!(vp.offset, (200.0, 0.0));
+}
+
+#[test]
+fn align_edge_reveals_each_edge() {
+    let mut vp = ViewportState {
+        scale: 2.0,
+        ..Default::default()
+    };
+    // 200×100 at 2× is 400×200 in a 100×100 window.
+    vp.align_edge(Edge::Left, (200, 100), (100, 100));
+    assert_eq!(vp.offset, (150.0, 0.0));
+    vp.align_edge(Edge::Bottom, (200, 100), (100, 100));
+    assert_eq!(vp.offset, (150.0, -50.0));
+    vp.align_edge(Edge::Right, (200, 100), (100, 100));
+    assert_eq!(vp.offset, (-150.0, -50.0));
+    vp.align_edge(Edge::Top, (200, 100), (100, 100));
+    assert_eq!(vp.offset, (-150.0, 50.0));
+}
+
+#[test]
+fn align_edge_centres_axis_that_fits() {
+    let mut vp = ViewportState::default();
+    vp.pan(30.0, 30.0);
+    vp.align_edge(Edge::Left, (50, 50), (100, 100));
+    assert_eq!(vp.offset, (0.0, 30.0));
+}
+
+#[test]
+fn align_edge_honours_rotation() {
+    let mut vp = ViewportState::default();
+    vp.rotate_right();
+    // 100×300 rotated is 300×100: only the horizontal axis overflows.
+    vp.align_edge(Edge::Left, (100, 300), (100, 100));
+    vp.align_edge(Edge::Top, (100, 300), (100, 100));
+    assert_eq!(vp.offset, (100.0, 0.0));
 }
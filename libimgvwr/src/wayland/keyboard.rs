@@ -1,23 +1,92 @@
 //! xkbcommon keymap and key-event state wrapper.
 //!
 //! [`KeyboardState`] is created from the keymap fd delivered by the
-//! `wl_keyboard::keymap` event and updated on every key press/release.
-//! Only key-press events produce a [`Keysym`]; releases return `None`.
+//! `wl_keyboard::keymap` event and updated by every `wl_keyboard::modifiers`
+//! event; the compositor's mask is authoritative, so key events only read the
+//! state. Only key-press events produce a [`Keysym`]; releases return `None`.
+//!
+//! Wayland leaves key repeat to the client: [`KeyRepeat`] tracks the held key
+//! and the compositor's `wl_keyboard::repeat_info` rate and delay, and tells
+//! the event loop when the next synthetic press is due.
 
-use std::{io, os::fd::OwnedFd};
+use std::{
+    io,
+    os::fd::OwnedFd,
+    time::{Duration, Instant},
+};
 
 use wayland_client::protocol::wl_keyboard;
 use xkbcommon::xkb::{
-    self, CONTEXT_NO_FLAGS, KEYMAP_COMPILE_NO_FLAGS, KEYMAP_FORMAT_TEXT_V1, KeyDirection, Keycode,
-    Keysym,
+    self, CONTEXT_NO_FLAGS, KEYMAP_COMPILE_NO_FLAGS, KEYMAP_FORMAT_TEXT_V1, Keycode, Keysym,
+    ModIndex, STATE_MODS_EFFECTIVE,
 };
 
+use crate::keybinds::Modifiers;
+
 /// Live xkbcommon context, keymap, and state for the seat keyboard.
 pub struct KeyboardState {
-    // Fields kept to satisfy the borrow requirements of xkb::State.
+    // Kept to satisfy the borrow requirements of xkb::State.
     _context: xkb::Context,
-    _keymap: xkb::Keymap,
+    keymap: xkb::Keymap,
     state: xkb::State,
+    mod_indices: ModIndices,
+}
+
+/// Keymap indices of the modifiers tracked in [`Modifiers`].
+struct ModIndices {
+    ctrl: ModIndex,
+    shift: ModIndex,
+    alt: ModIndex,
+    logo: ModIndex,
+}
+
+impl KeyboardState {
+    pub(super) fn from_keymap(context: xkb::Context, keymap: xkb::Keymap) -> Self {
+        let mod_indices = ModIndices {
+            ctrl: keymap.mod_get_index(xkb::MOD_NAME_CTRL),
+            shift: keymap.mod_get_index(xkb::MOD_NAME_SHIFT),
+            alt: keymap.mod_get_index(xkb::MOD_NAME_ALT),
+            logo: keymap.mod_get_index(xkb::MOD_NAME_LOGO),
+        };
+        let state = xkb::State::new(&keymap);
+        KeyboardState {
+            _context: context,
+            keymap,
+            state,
+            mod_indices,
+        }
+    }
+
+    /// Whether the keymap marks the evdev scancode `key` as repeating.
+    /// Modifier keys, for example, do not repeat.
+    pub fn key_repeats(&self, key: u32) -> bool {
+        self.keymap.key_repeats(Keycode::new(key + 8))
+    }
+
+    /// The modifiers currently held, regardless of any key.
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers_where(|_| true)
+    }
+
+    /// The held modifiers that were not consumed translating `keycode`.
+    fn unconsumed_modifiers(&self, keycode: Keycode) -> Modifiers {
+        self.modifiers_where(|idx| !self.state.mod_index_is_consumed(keycode, idx))
+    }
+
+    fn modifiers_where(&self, keep: impl Fn(ModIndex) -> bool) -> Modifiers {
+        let on = |idx| {
+            idx != xkb::MOD_INVALID
+                && self.state.mod_index_is_active(idx, STATE_MODS_EFFECTIVE)
+                && keep(idx)
+        };
+        let m = &self.mod_indices;
+        Modifiers {
+            ctrl: on(m.ctrl),
+            shift: on(m.shift),
+            alt: on(m.alt),
+            logo: on(m.logo),
+        }
+    }
 }
 
 /// Compile a new [`KeyboardState`] from the keymap `fd` and byte `size`
@@ -44,44 +113,133 @@ pub fn update_keymap(fd: OwnedFd, size: u32) -> io::Result<KeyboardState> {
             "xkbcommon keymap compilation failed",
         )
     })?;
-    let state = xkb::State::new(&keymap);
-    Ok(KeyboardState {
-        _context: context,
-        _keymap: keymap,
-        state,
-    })
+    Ok(KeyboardState::from_keymap(context, keymap))
+}
+
+/// Apply a `wl_keyboard::modifiers` event to `keyboard_state`.
+pub fn update_modifiers(
+    keyboard_state: &mut KeyboardState,
+    depressed: u32,
+    latched: u32,
+    locked: u32,
+    group: u32,
+) {
+    keyboard_state
+        .state
+        .update_mask(depressed, latched, locked, 0, 0, group);
 }
 
-/// Feed a key event into `keyboard_state` and return the [`Keysym`] for
-/// key-press events, or `None` for releases.
+/// Translate a key event against `keyboard_state` and return the [`Keysym`]
+/// and active [`Modifiers`] for key-press events, or `None` for releases.
+///
+/// The state is driven by [`update_modifiers`] alone, so the translation uses
+/// the modifiers the compositor last reported.
+///
+/// Modifiers consumed by the translation (e.g. Shift for `A` or `plus`) are
+/// left out, so the pair can be looked up directly in a
+/// [`KeybindMap`](crate::keybinds::KeybindMap).
 ///
 /// `key` is the raw Linux evdev scancode from the `wl_keyboard::key` event.
 /// XKB keycodes are evdev + 8.
 pub fn key_event(
-    keyboard_state: &mut KeyboardState,
+    keyboard_state: &KeyboardState,
     key: u32,
     key_state: wl_keyboard::KeyState,
-) -> Option<Keysym> {
-    let pressed = match key_state {
-        wl_keyboard::KeyState::Pressed => true,
-        wl_keyboard::KeyState::Released => false,
-        _ => return None,
-    };
+) -> Option<(Keysym, Modifiers)> {
+    if key_state != wl_keyboard::KeyState::Pressed {
+        return None;
+    }
     let keycode = Keycode::new(key + 8);
-    let direction = if pressed {
-        KeyDirection::Down
-    } else {
-        KeyDirection::Up
-    };
-    keyboard_state.state.update_key(keycode, direction);
-    if pressed {
-        let sym = keyboard_state.state.key_get_one_sym(keycode);
-        if sym != Keysym::NoSymbol {
-            Some(sym)
-        } else {
-            None
-        }
+    let sym = keyboard_state.state.key_get_one_sym(keycode);
+    if sym != Keysym::NoSymbol {
+        Some((sym, keyboard_state.unconsumed_modifiers(keycode)))
     } else {
         None
     }
 }
+
+/// Client-side key repeat state.
+///
+/// Defaults to 25 repeats per second after 600 ms — the common compositor
+/// default — until a `wl_keyboard::repeat_info` event (seat v4+) overrides it.
+pub struct KeyRepeat {
+    /// Interval between repeats; `None` when the compositor disabled repeat.
+    interval: Option<Duration>,
+    delay: Duration,
+    held: Option<HeldKey>,
+}
+
+struct HeldKey {
+    key: u32,
+    sym: Keysym,
+    mods: Modifiers,
+    next: Instant,
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        KeyRepeat {
+            interval: Some(Duration::from_millis(40)),
+            delay: Duration::from_millis(600),
+            held: None,
+        }
+    }
+}
+
+impl KeyRepeat {
+    /// Apply a `wl_keyboard::repeat_info` event. A `rate` of zero (or less)
+    /// disables repeat.
+    pub fn set_info(&mut self, rate: i32, delay: i32) {
+        self.interval = u64::try_from(rate)
+            .ok()
+            .filter(|&r| r > 0)
+            .map(|r| Duration::from_micros(1_000_000 / r));
+        self.delay = Duration::from_millis(delay.max(0) as u64);
+        if self.interval.is_none() {
+            self.held = None;
+        }
+    }
+
+    /// Start repeating `key` (evdev scancode), replacing any held key.
+    pub fn press(&mut self, key: u32, sym: Keysym, mods: Modifiers, now: Instant) {
+        self.held = self.interval.map(|_| HeldKey {
+            key,
+            sym,
+            mods,
+            next: now + self.delay,
+        });
+    }
+
+    /// Stop repeating if `key` is the held key.
+    pub fn release(&mut self, key: u32) {
+        if self.held.as_ref().is_some_and(|h| h.key == key) {
+            self.held = None;
+        }
+    }
+
+    /// Stop repeating unconditionally (e.g. on keyboard focus loss).
+    pub fn stop(&mut self) {
+        self.held = None;
+    }
+
+    /// When the next repeat is due, if a key is held.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.held.as_ref().map(|h| h.next)
+    }
+
+    /// Return the held key if a repeat is due at `now` and schedule the next
+    /// one.
+    ///
+    /// At most one repeat is produced per call: if the event loop fell behind
+    /// (e.g. a slow render), the schedule restarts from `now` instead of
+    /// replaying a burst of missed repeats.
+    pub fn due(&mut self, now: Instant) -> Option<(Keysym, Modifiers)> {
+        let interval = self.interval?;
+        let held = self.held.as_mut().filter(|h| h.next <= now)?;
+        held.next += interval;
+        if held.next <= now {
+            held.next = now + interval;
+        }
+        Some((held.sym, held.mods))
+    }
+}
@@ -7,49 +7,156 @@
 //! Submodules:
 //! - [`shm`]: SHM pool backed by a `memfd` (Phase 5.1)
 //! - [`keyboard`]: xkbcommon keymap and key-event handling (Phase 5.2)
+//! - [`pointer`]: per-frame scroll accumulation and touchpad gestures
+//! - [`touch`]: touchscreen gesture recognition
+//! - [`scale`]: output scale tracking for HiDPI buffers
 
 pub mod keyboard;
+pub mod pointer;
+pub mod scale;
 pub mod shm;
+pub mod touch;
 
-use std::io;
+#[cfg(test)]
+mod tests;
+
+use std::{
+    io,
+    time::{Duration, Instant},
+};
 
 #[cfg(feature = "decorations")]
 use tracing::warn;
 use tracing::{debug, info};
 
 use wayland_client::{
-    Connection, Dispatch, EventQueue, QueueHandle, WEnum,
+    Connection, Dispatch, EventQueue, Proxy, QueueHandle, WEnum,
     protocol::{
-        wl_buffer, wl_compositor, wl_keyboard, wl_pointer, wl_registry, wl_seat, wl_shm,
-        wl_shm_pool, wl_surface,
+        wl_buffer, wl_compositor, wl_keyboard, wl_output, wl_pointer, wl_registry, wl_seat, wl_shm,
+        wl_shm_pool, wl_surface, wl_touch,
+    },
+};
+use wayland_protocols::{
+    wp::{
+        fractional_scale::v1::client::{wp_fractional_scale_manager_v1, wp_fractional_scale_v1},
+        pointer_gestures::zv1::client::{
+            zwp_pointer_gesture_pinch_v1, zwp_pointer_gesture_swipe_v1, zwp_pointer_gestures_v1,
+        },
+        viewporter::client::{wp_viewport, wp_viewporter},
     },
+    xdg::shell::client::{xdg_surface, xdg_toplevel, xdg_wm_base},
 };
-use wayland_protocols::xdg::shell::client::{xdg_surface, xdg_toplevel, xdg_wm_base};
 use xkbcommon::xkb::Keysym;
 
+use crate::keybinds::Modifiers;
+use crate::renderer::frame::Rect;
+
 #[cfg(feature = "decorations")]
 use wayland_protocols::xdg::decoration::zv1::client::{
     zxdg_decoration_manager_v1, zxdg_toplevel_decoration_v1,
 };
 
 use crate::wayland::{
-    keyboard::{KeyboardState, key_event, update_keymap},
-    shm::ShmPool,
+    keyboard::{KeyRepeat, KeyboardState, key_event, update_keymap, update_modifiers},
+    pointer::{AxisFrame, PinchTracker, ScrollKind, SwipeTracker},
+    scale::{SCALE_DENOMINATOR, SurfaceScale},
+    shm::{BufferSlots, ShmBuffer},
+    touch::{TouchGesture, TouchRecognizer},
 };
 
 // ── Input events ────────────────────────────────────────────────────────────
 
 /// Input events collected during dispatch and consumed by the event loop.
+///
+/// Positions and distances are in buffer pixels: surface coordinates
+/// multiplied by [`WaylandState::scale_factor`]. Thresholds defined in
+/// surface pixels must be scaled by the same factor before comparing.
 pub enum InputEvent {
-    /// A key was pressed; carries the resolved XKB keysym.
-    Key(Keysym),
-    /// Vertical scroll wheel tick. Positive = scroll down.
-    /// Carries the pointer position at the time of the event (surface coords).
-    Scroll { delta: f32, cursor: (f32, f32) },
-    /// Left mouse button pressed (`true`) or released (`false`).
-    PointerButton { pressed: bool },
-    /// Mouse moved while the button was held; delta in surface pixels.
+    /// A key was pressed; carries the resolved XKB keysym and the modifiers
+    /// held that were not consumed producing it. `repeat` is `true` for
+    /// synthetic presses generated while the key is held.
+    Key {
+        sym: Keysym,
+        mods: Modifiers,
+        repeat: bool,
+    },
+    /// One `wl_pointer` frame of scrolling as a `(horizontal, vertical)`
+    /// delta; positive scrolls right and down (towards the user). The unit
+    /// depends on `kind`: notches for wheels, buffer pixels for touchpads.
+    /// Carries the pointer position and the keyboard modifiers held.
+    Scroll {
+        delta: (f32, f32),
+        kind: ScrollKind,
+        cursor: (f32, f32),
+        mods: Modifiers,
+    },
+    /// Mouse button `button` (Linux evdev code, e.g. `BTN_LEFT`) pressed or
+    /// released, with the keyboard modifiers held. `double` marks the second
+    /// press of the same button within [`DOUBLE_CLICK_MS`].
+    PointerButton {
+        button: u32,
+        pressed: bool,
+        mods: Modifiers,
+        double: bool,
+    },
+    /// Mouse moved while a button was held; delta in buffer pixels.
     PointerMotion { dx: f32, dy: f32 },
+    /// The pointer left the surface. Buttons released elsewhere, e.g. after
+    /// a compositor grab, are never reported, so treat them as released.
+    PointerLeave,
+    /// Touchpad pinch in progress. `scale` is the zoom factor since the
+    /// previous `Pinch`, `rotation` the degrees turned clockwise since the
+    /// gesture began, and `centre` the gesture centre.
+    Pinch {
+        scale: f32,
+        rotation: f32,
+        centre: (f32, f32),
+    },
+    /// The touchpad pinch ended or was cancelled.
+    PinchEnd,
+    /// A completed touchpad swipe with `fingers` fingers; `dx`/`dy` is the
+    /// total motion in buffer pixels.
+    Swipe { fingers: u32, dx: f32, dy: f32 },
+    /// A touchscreen gesture, recognised in surface pixels and then scaled
+    /// to buffer pixels like the other events.
+    Touch(TouchGesture),
+}
+
+/// Maximum gap between two presses of the same button that counts as a
+/// double click.
+pub const DOUBLE_CLICK_MS: u32 = 400;
+
+/// Window states granted by the compositor in the latest
+/// `xdg_toplevel::configure`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ToplevelState {
+    pub fullscreen: bool,
+    pub maximized: bool,
+    pub activated: bool,
+}
+
+impl ToplevelState {
+    /// Decode the `states` array of an `xdg_toplevel::configure` event: a
+    /// packed list of native-endian `u32` state values. Unknown values are
+    /// ignored.
+    pub fn from_states(states: &[u8]) -> ToplevelState {
+        let mut toplevel = ToplevelState::default();
+        for chunk in states.chunks_exact(4) {
+            let raw = u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+            match xdg_toplevel::State::try_from(raw) {
+                Ok(xdg_toplevel::State::Fullscreen) => toplevel.fullscreen = true,
+                Ok(xdg_toplevel::State::Maximized) => toplevel.maximized = true,
+                Ok(xdg_toplevel::State::Activated) => toplevel.activated = true,
+                _ => {}
+            }
+        }
+        toplevel
+    }
+
+    /// `true` when the compositor, not the client, chooses the window size.
+    pub fn is_constrained(&self) -> bool {
+        self.fullscreen || self.maximized
+    }
 }
 
 // ── WaylandState ────────────────────────────────────────────────────────────
@@ -66,6 +173,11 @@ pub struct WaylandState {
     wl_shm: Option<wl_shm::WlShm>,
     xdg_wm_base: Option<xdg_wm_base::XdgWmBase>,
     seat: Option<wl_seat::WlSeat>,
+    pointer_gestures: Option<zwp_pointer_gestures_v1::ZwpPointerGesturesV1>,
+    fractional_scale_manager: Option<wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1>,
+    viewporter: Option<wp_viewporter::WpViewporter>,
+    /// Every bound output with its `wl_output::scale`.
+    outputs: Vec<(wl_output::WlOutput, i32)>,
 
     #[cfg(feature = "decorations")]
     decoration_manager: Option<zxdg_decoration_manager_v1::ZxdgDecorationManagerV1>,
@@ -76,13 +188,30 @@ pub struct WaylandState {
     surface: Option<wl_surface::WlSurface>,
     xdg_surface: Option<xdg_surface::XdgSurface>,
     xdg_toplevel: Option<xdg_toplevel::XdgToplevel>,
+    fractional_scale: Option<wp_fractional_scale_v1::WpFractionalScaleV1>,
+    viewport: Option<wp_viewport::WpViewport>,
+    /// Outputs the surface is currently shown on.
+    entered_outputs: Vec<wl_output::WlOutput>,
+    surface_scale: SurfaceScale,
+    /// Persistent SHM buffers, reused once the compositor releases them.
+    buffers: BufferSlots<ShmBuffer>,
 
     // Input objects and state
     keyboard: Option<wl_keyboard::WlKeyboard>,
     keyboard_state: Option<KeyboardState>,
+    key_repeat: KeyRepeat,
     pointer: Option<wl_pointer::WlPointer>,
     pointer_pos: (f64, f64),
-    pointer_pressed: bool,
+    buttons_held: u32,
+    /// Button and timestamp (ms) of the last press, for double clicks.
+    last_press: Option<(u32, u32)>,
+    axis_frame: AxisFrame,
+    pinch_gesture: Option<zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1>,
+    swipe_gesture: Option<zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1>,
+    pinch: PinchTracker,
+    swipe: SwipeTracker,
+    touch: Option<wl_touch::WlTouch>,
+    touch_gestures: TouchRecognizer,
 
     /// Set to `true` when the compositor requests the window be closed.
     pub closed: bool,
@@ -90,6 +219,11 @@ pub struct WaylandState {
     pub needs_redraw: bool,
     /// Current window size in pixels as reported by the compositor.
     pub window_size: (u32, u32),
+    /// Last size of the floating window, restored when leaving fullscreen or
+    /// maximized if the compositor leaves the size to the client.
+    windowed_size: (u32, u32),
+    /// Fullscreen / maximized state granted by the compositor.
+    pub toplevel_state: ToplevelState,
     /// Events accumulated since the last drain by the event loop.
     pub pending_events: Vec<InputEvent>,
 }
@@ -102,6 +236,10 @@ impl WaylandState {
             wl_shm: None,
             xdg_wm_base: None,
             seat: None,
+            pointer_gestures: None,
+            fractional_scale_manager: None,
+            viewporter: None,
+            outputs: Vec::new(),
             #[cfg(feature = "decorations")]
             decoration_manager: None,
             #[cfg(feature = "decorations")]
@@ -109,18 +247,173 @@ impl WaylandState {
             surface: None,
             xdg_surface: None,
             xdg_toplevel: None,
+            fractional_scale: None,
+            viewport: None,
+            entered_outputs: Vec::new(),
+            surface_scale: SurfaceScale::default(),
+            buffers: BufferSlots::default(),
             keyboard: None,
             keyboard_state: None,
+            key_repeat: KeyRepeat::default(),
             pointer: None,
             pointer_pos: (0.0, 0.0),
-            pointer_pressed: false,
+            buttons_held: 0,
+            last_press: None,
+            axis_frame: AxisFrame::default(),
+            pinch_gesture: None,
+            swipe_gesture: None,
+            pinch: PinchTracker::default(),
+            swipe: SwipeTracker::default(),
+            touch: None,
+            touch_gestures: TouchRecognizer::default(),
             closed: false,
             needs_redraw: false,
             window_size: initial_size,
+            windowed_size: initial_size,
+            toplevel_state: ToplevelState::default(),
             pending_events: Vec::new(),
         }
     }
 
+    /// Keyboard modifiers currently held, for qualifying pointer events.
+    fn modifiers(&self) -> Modifiers {
+        self.keyboard_state
+            .as_ref()
+            .map(KeyboardState::modifiers)
+            .unwrap_or_default()
+    }
+
+    /// Create the pinch and swipe gesture objects once both the pointer and
+    /// `zwp_pointer_gestures_v1` are available.
+    fn bind_gestures(&mut self) {
+        let (Some(gestures), Some(pointer)) = (&self.pointer_gestures, &self.pointer) else {
+            return;
+        };
+        if self.pinch_gesture.is_none() {
+            self.pinch_gesture = Some(gestures.get_pinch_gesture(pointer, &self.qh, ()));
+        }
+        if self.swipe_gesture.is_none() {
+            self.swipe_gesture = Some(gestures.get_swipe_gesture(pointer, &self.qh, ()));
+        }
+    }
+
+    /// Size of the buffers to render, in physical pixels: the logical
+    /// [`window_size`](Self::window_size) times the output scale.
+    pub fn buffer_size(&self) -> (u32, u32) {
+        self.surface_scale.buffer_size(self.window_size)
+    }
+
+    /// Buffer pixels per surface pixel (`2.0` on a 200 % output), the factor
+    /// applied to [`InputEvent`] positions and distances.
+    pub fn scale_factor(&self) -> f64 {
+        self.surface_scale.factor()
+    }
+
+    /// Update the scale hints and request a redraw if the scale changed.
+    fn update_scale(&mut self, update: impl FnOnce(&mut SurfaceScale)) {
+        let before = self.surface_scale.scale120();
+        update(&mut self.surface_scale);
+        let after = self.surface_scale.scale120();
+        if after != before {
+            debug!(scale = self.surface_scale.factor(), "surface scale changed");
+            self.needs_redraw = true;
+        }
+    }
+
+    /// Recompute the largest scale among the outputs the surface is on.
+    fn update_output_scale(&mut self) {
+        let factor = self
+            .entered_outputs
+            .iter()
+            .filter_map(|o| self.outputs.iter().find(|(out, _)| out == o))
+            .map(|&(_, scale)| scale)
+            .max();
+        self.update_scale(|s| s.set_output(factor));
+    }
+
+    /// Queue `event`, converting surface coordinates to buffer pixels.
+    fn push_event(&mut self, event: InputEvent) {
+        let k = self.surface_scale.factor() as f32;
+        let at = |(x, y): (f32, f32)| (x * k, y * k);
+        let event = match event {
+            InputEvent::Scroll {
+                delta,
+                kind,
+                cursor,
+                mods,
+            } => InputEvent::Scroll {
+                delta: match kind {
+                    ScrollKind::Smooth => at(delta),
+                    ScrollKind::Wheel => delta,
+                },
+                kind,
+                cursor: at(cursor),
+                mods,
+            },
+            InputEvent::PointerMotion { dx, dy } => {
+                let (dx, dy) = at((dx, dy));
+                InputEvent::PointerMotion { dx, dy }
+            }
+            InputEvent::Pinch {
+                scale,
+                rotation,
+                centre,
+            } => InputEvent::Pinch {
+                scale,
+                rotation,
+                centre: at(centre),
+            },
+            InputEvent::Swipe { fingers, dx, dy } => {
+                let (dx, dy) = at((dx, dy));
+                InputEvent::Swipe { fingers, dx, dy }
+            }
+            InputEvent::Touch(gesture) => InputEvent::Touch(match gesture {
+                TouchGesture::Pan { dx, dy } => {
+                    let (dx, dy) = at((dx, dy));
+                    TouchGesture::Pan { dx, dy }
+                }
+                TouchGesture::Pinch {
+                    scale,
+                    centre,
+                    dx,
+                    dy,
+                } => {
+                    let (dx, dy) = at((dx, dy));
+                    TouchGesture::Pinch {
+                        scale,
+                        centre: at(centre),
+                        dx,
+                        dy,
+                    }
+                }
+                TouchGesture::Tap { pos, double } => TouchGesture::Tap {
+                    pos: at(pos),
+                    double,
+                },
+                TouchGesture::Swipe { dx, dy } => {
+                    let (dx, dy) = at((dx, dy));
+                    TouchGesture::Swipe { dx, dy }
+                }
+            }),
+            event => event,
+        };
+        self.pending_events.push(event);
+    }
+
+    /// Emit the scroll accumulated since the last pointer frame, if any.
+    fn flush_axis_frame(&mut self) {
+        if let Some((delta, kind)) = self.axis_frame.take() {
+            let cursor = (self.pointer_pos.0 as f32, self.pointer_pos.1 as f32);
+            let mods = self.modifiers();
+            self.push_event(InputEvent::Scroll {
+                delta,
+                kind,
+                cursor,
+                mods,
+            });
+        }
+    }
+
     /// Expose `wl_shm` for Phase 6 buffer creation.
     pub fn wl_shm(&self) -> Option<&wl_shm::WlShm> {
         self.wl_shm.as_ref()
@@ -148,18 +441,19 @@ pub struct WaylandContext {
     /// Public Wayland state — surfaces, globals, pending events, flags.
     pub state: WaylandState,
     event_queue: EventQueue<WaylandState>,
-    /// SHM pool backing the pixel buffer. Created on first commit; resized as needed.
-    shm_pool: Option<ShmPool>,
-    /// The `wl_buffer` attached in the previous frame. Destroyed before each new commit.
-    prev_buffer: Option<wl_buffer::WlBuffer>,
 }
 
 impl WaylandContext {
     /// Connect to the Wayland compositor and initialise surfaces.
     ///
     /// Performs two roundtrips: one to enumerate globals, one to receive the
-    /// initial `xdg_toplevel::configure`.
-    pub fn connect(initial_size: (u32, u32), use_decorations: bool) -> io::Result<Self> {
+    /// initial `xdg_toplevel::configure`. With `fullscreen` the window asks
+    /// to be fullscreen before it is first mapped.
+    pub fn connect(
+        initial_size: (u32, u32),
+        use_decorations: bool,
+        fullscreen: bool,
+    ) -> io::Result<Self> {
         // Suppress unused-variable lint when the `decorations` feature is off.
         #[cfg(not(feature = "decorations"))]
         let _ = use_decorations;
@@ -197,6 +491,18 @@ impl WaylandContext {
             .get_xdg_surface(&surface, &qh, ());
         let toplevel = xdg_surf.get_toplevel(&qh, ());
         toplevel.set_app_id("imgvwr".to_string());
+        if fullscreen {
+            toplevel.set_fullscreen(None);
+        }
+        // Fractional scales need a viewport to map the larger buffer back
+        // onto the logical surface size.
+        if let (Some(manager), Some(viewporter)) =
+            (&state.fractional_scale_manager, &state.viewporter)
+        {
+            state.fractional_scale = Some(manager.get_fractional_scale(&surface, &qh, ()));
+            state.viewport = Some(viewporter.get_viewport(&surface, &qh, ()));
+            state.surface_scale.set_viewporter(true);
+        }
 
         #[cfg(feature = "decorations")]
         if use_decorations {
@@ -227,61 +533,73 @@ impl WaylandContext {
             conn,
             state,
             event_queue,
-            shm_pool: None,
-            prev_buffer: None,
         })
     }
 
-    /// Write `pixels` (ARGB8888, `w × h × 4` bytes) into a Wayland SHM buffer
-    /// and commit it to the surface.
-    pub fn commit_frame(&mut self, pixels: &[u8], w: u32, h: u32) -> io::Result<()> {
-        let size = (w * h * 4) as usize;
+    /// Write `pixels` (ARGB8888, `w × h × 4` bytes) into a released SHM
+    /// buffer and commit it to the surface, damaging only the `damage`
+    /// rectangles (buffer pixels) that changed since the previous frame.
+    ///
+    /// Returns `false` if the compositor still holds every buffer: the frame
+    /// is dropped and [`WaylandState::needs_redraw`] is set, so the caller
+    /// draws again once a `wl_buffer::release` arrives. Its damage was never
+    /// reported, so the next frame must damage everything.
+    pub fn commit_frame(
+        &mut self,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+        damage: &[Rect],
+    ) -> io::Result<bool> {
+        let size = w as usize * h as usize * 4;
+        self.apply_buffer_scale();
+        let state = &mut self.state;
+        let surface = state
+            .surface
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "surface not available"))?;
+        let wl_shm = state
+            .wl_shm
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "wl_shm not bound"))?;
+        let qh = &state.qh;
+        let Some(buffer) = state.buffers.acquire(
+            (w, h),
+            |id| ShmBuffer::create(wl_shm, (w, h), id, qh),
+            ShmBuffer::destroy,
+        )?
+        else {
+            debug!("all buffers busy; dropping frame");
+            state.needs_redraw = true;
+            return Ok(false);
+        };
 
-        // Create or grow the SHM backing store.
-        match &self.shm_pool {
-            None => self.shm_pool = Some(ShmPool::create(size)?),
-            Some(p) if p.size < size => self.shm_pool.as_mut().unwrap().resize(size)?,
-            _ => {}
+        buffer.pixels_mut()[..size].copy_from_slice(pixels);
+        surface.attach(Some(buffer.wl_buffer()), 0, 0);
+        for rect in damage {
+            surface.damage_buffer(rect.x as i32, rect.y as i32, rect.w as i32, rect.h as i32);
         }
-
-        self.shm_pool.as_mut().unwrap().as_mut_slice()[..size].copy_from_slice(pixels);
-
-        // Build a wl_shm_pool + wl_buffer for this frame.
-        let stride = w as i32 * 4;
-        let wl_pool = {
-            let fd = self.shm_pool.as_ref().unwrap().fd();
-            let wl_shm = self
-                .state
-                .wl_shm()
-                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "wl_shm not bound"))?;
-            wl_shm.create_pool(fd, size as i32, self.state.qh(), ())
-        };
-        let buffer = wl_pool.create_buffer(
-            0,
-            w as i32,
-            h as i32,
-            stride,
-            wl_shm::Format::Argb8888,
-            self.state.qh(),
-            (),
-        );
-        wl_pool.destroy();
-
-        // Destroy the previous frame's buffer before the new one takes the surface slot.
-        if let Some(prev) = self.prev_buffer.take() {
-            prev.destroy();
-        }
-
-        let surface = self
-            .state
-            .surface()
-            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "surface not available"))?;
-        surface.attach(Some(&buffer), 0, 0);
-        surface.damage_buffer(0, 0, w as i32, h as i32);
         surface.commit();
+        self.flush().map(|()| true)
+    }
 
-        self.prev_buffer = Some(buffer);
-        self.flush()
+    /// Tell the compositor how the next buffer maps onto the surface: through
+    /// the viewport at the logical window size, or by the integer buffer
+    /// scale. Applied on the next surface commit; [`commit_frame`] calls this
+    /// itself, GPU presentation must call it before presenting.
+    ///
+    /// [`commit_frame`]: Self::commit_frame
+    pub fn apply_buffer_scale(&self) {
+        let Some(surface) = self.state.surface() else {
+            return;
+        };
+        let (w, h) = self.state.window_size;
+        if let Some(viewport) = &self.state.viewport {
+            viewport.set_destination(w as i32, h as i32);
+        } else if surface.version() >= 3 {
+            let scale = self.state.surface_scale.scale120() / SCALE_DENOMINATOR;
+            surface.set_buffer_scale(scale as i32);
+        }
     }
 
     /// Flush the outgoing Wayland socket buffer.
@@ -291,75 +609,65 @@ impl WaylandContext {
             .map_err(|e| io::Error::new(io::ErrorKind::BrokenPipe, e))
     }
 
-    /// Dispatch pending Wayland events.
-    ///
-    /// Without any animated-format feature: uses `blocking_dispatch`, which
-    /// parks the thread until a compositor event arrives — correct and idle for
-    /// static images.
+    /// Dispatch pending Wayland events, waiting at most `timeout_ms`
+    /// milliseconds for new ones (negative = wait indefinitely).
     ///
-    /// With an animated-format feature (e.g. `gif`): uses a `poll` timeout so
-    /// the animation loop can call `tick()` on a regular cadence even without
-    /// user input.
+    /// Static images pass a negative timeout so the thread parks until a
+    /// compositor event arrives; animations, and images whose mip pyramid is
+    /// still being built, pass a short cadence so the loop can update
+    /// without user input. While a key is held the wait is further shortened
+    /// to the next key-repeat deadline, and a due repeat is queued as an
+    /// [`InputEvent::Key`] with `repeat: true`.
     pub fn dispatch(&mut self, timeout_ms: i32) -> io::Result<()> {
-        #[cfg(any(
-            feature = "gif",
-            feature = "avif-anim",
-            feature = "jxl-anim",
-            feature = "webp-anim",
-            feature = "apng"
-        ))]
-        {
-            use std::os::fd::AsFd;
-
-            self.flush()?;
-
-            if let Some(guard) = self.event_queue.prepare_read() {
-                let fd = self.conn.as_fd();
-                let mut pfd = [rustix::event::PollFd::new(
-                    &fd,
-                    rustix::event::PollFlags::IN,
-                )];
-                let timeout_spec;
-                let timeout = if timeout_ms < 0 {
-                    None
-                } else {
-                    timeout_spec = rustix::time::Timespec {
-                        tv_sec: (timeout_ms / 1000) as i64,
-                        tv_nsec: ((timeout_ms % 1000) * 1_000_000) as i64,
-                    };
-                    Some(&timeout_spec)
-                };
-                let _ = rustix::event::poll(&mut pfd, timeout);
-                // Always attempt the read: WouldBlock means no data arrived
-                // (spurious wakeup or data already drained), which is fine.
-                match guard.read() {
-                    Ok(_) => {}
-                    Err(wayland_client::backend::WaylandError::Io(e))
-                        if e.kind() == io::ErrorKind::WouldBlock => {}
-                    Err(e) => return Err(io::Error::other(e)),
-                }
+        use std::os::fd::AsFd;
+
+        self.flush()?;
+
+        if let Some(guard) = self.event_queue.prepare_read() {
+            let now = Instant::now();
+            let timeout = u64::try_from(timeout_ms)
+                .ok()
+                .map(Duration::from_millis)
+                .into_iter()
+                .chain(
+                    self.state
+                        .key_repeat
+                        .next_deadline()
+                        .map(|d| d.saturating_duration_since(now)),
+                )
+                .min();
+            let fd = self.conn.as_fd();
+            let mut pfd = [rustix::event::PollFd::new(
+                &fd,
+                rustix::event::PollFlags::IN,
+            )];
+            let timeout_spec = timeout.map(|t| rustix::time::Timespec {
+                tv_sec: t.as_secs() as i64,
+                tv_nsec: t.subsec_nanos() as i64,
+            });
+            let _ = rustix::event::poll(&mut pfd, timeout_spec.as_ref());
+            // Always attempt the read: WouldBlock means no data arrived
+            // (timeout, spurious wakeup, or data already drained), which is fine.
+            match guard.read() {
+                Ok(_) => {}
+                Err(wayland_client::backend::WaylandError::Io(e))
+                    if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(io::Error::other(e)),
             }
-
-            self.event_queue
-                .dispatch_pending(&mut self.state)
-                .map(|_| ())
-                .map_err(io::Error::other)
         }
 
-        #[cfg(not(any(
-            feature = "gif",
-            feature = "avif-anim",
-            feature = "jxl-anim",
-            feature = "webp-anim",
-            feature = "apng"
-        )))]
-        {
-            let _ = timeout_ms;
-            self.event_queue
-                .blocking_dispatch(&mut self.state)
-                .map(|_| ())
-                .map_err(io::Error::other)
+        self.event_queue
+            .dispatch_pending(&mut self.state)
+            .map_err(io::Error::other)?;
+
+        if let Some((sym, mods)) = self.state.key_repeat.due(Instant::now()) {
+            self.state.push_event(InputEvent::Key {
+                sym,
+                mods,
+                repeat: true,
+            });
         }
+        Ok(())
     }
 
     /// Raw `wl_display *` pointer for wgpu surface creation.
@@ -390,6 +698,34 @@ impl WaylandContext {
         std::ptr::NonNull::new(ptr).expect("wl_surface must not be null")
     }
 
+    /// Ask the compositor to enter or leave fullscreen. The change takes
+    /// effect when the next configure arrives; see
+    /// [`WaylandState::toplevel_state`].
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        let Some(toplevel) = &self.state.xdg_toplevel else {
+            return;
+        };
+        debug!(fullscreen, "requesting fullscreen change");
+        if fullscreen {
+            toplevel.set_fullscreen(None);
+        } else {
+            toplevel.unset_fullscreen();
+        }
+    }
+
+    /// Ask the compositor to maximize or restore the window.
+    pub fn set_maximized(&mut self, maximized: bool) {
+        let Some(toplevel) = &self.state.xdg_toplevel else {
+            return;
+        };
+        debug!(maximized, "requesting maximized change");
+        if maximized {
+            toplevel.set_maximized();
+        } else {
+            toplevel.unset_maximized();
+        }
+    }
+
     /// Set the XDG toplevel window title.
     ///
     /// Only available when the `decorations` feature is enabled. The title is
@@ -438,6 +774,22 @@ impl Dispatch<wl_registry::WlRegistry, ()> for WaylandState {
             "wl_seat" => {
                 state.seat = Some(registry.bind(name, version.min(9), qh, ()));
             }
+            "wl_output" => {
+                let output = registry.bind(name, version.min(2), qh, ());
+                state.outputs.push((output, 1));
+            }
+            "wp_fractional_scale_manager_v1" => {
+                info!("compositor supports fractional scaling");
+                state.fractional_scale_manager = Some(registry.bind(name, version.min(1), qh, ()));
+            }
+            "wp_viewporter" => {
+                state.viewporter = Some(registry.bind(name, version.min(1), qh, ()));
+            }
+            "zwp_pointer_gestures_v1" => {
+                info!("compositor supports touchpad gestures");
+                state.pointer_gestures = Some(registry.bind(name, version.min(1), qh, ()));
+                state.bind_gestures();
+            }
             #[cfg(feature = "decorations")]
             "zxdg_decoration_manager_v1" => {
                 info!("compositor supports server-side decorations");
@@ -474,9 +826,92 @@ impl Dispatch<wl_shm::WlShm, ()> for WaylandState {
 
 impl Dispatch<wl_surface::WlSurface, ()> for WaylandState {
     fn event(
-        _: &mut Self,
+        state: &mut Self,
         _: &wl_surface::WlSurface,
-        _: wl_surface::Event,
+        event: wl_surface::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_surface::Event::Enter { output } => {
+                state.entered_outputs.push(output);
+                state.update_output_scale();
+            }
+            wl_surface::Event::Leave { output } => {
+                state.entered_outputs.retain(|o| *o != output);
+                state.update_output_scale();
+            }
+            wl_surface::Event::PreferredBufferScale { factor } => {
+                state.update_scale(|s| s.set_preferred(factor));
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<wl_output::WlOutput, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        output: &wl_output::WlOutput,
+        event: wl_output::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let wl_output::Event::Scale { factor } = event {
+            if let Some(entry) = state.outputs.iter_mut().find(|(o, _)| o == output) {
+                entry.1 = factor;
+            }
+            state.update_output_scale();
+        }
+    }
+}
+
+impl Dispatch<wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1, ()> for WaylandState {
+    fn event(
+        _: &mut Self,
+        _: &wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1,
+        _: wp_fractional_scale_manager_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<wp_fractional_scale_v1::WpFractionalScaleV1, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        _: &wp_fractional_scale_v1::WpFractionalScaleV1,
+        event: wp_fractional_scale_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let wp_fractional_scale_v1::Event::PreferredScale { scale } = event {
+            state.update_scale(|s| s.set_fractional(scale));
+        }
+    }
+}
+
+impl Dispatch<wp_viewporter::WpViewporter, ()> for WaylandState {
+    fn event(
+        _: &mut Self,
+        _: &wp_viewporter::WpViewporter,
+        _: wp_viewporter::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<wp_viewport::WpViewport, ()> for WaylandState {
+    fn event(
+        _: &mut Self,
+        _: &wp_viewport::WpViewport,
+        _: wp_viewport::Event,
         _: &(),
         _: &Connection,
         _: &QueueHandle<Self>,
@@ -517,6 +952,10 @@ impl Dispatch<wl_seat::WlSeat, ()> for WaylandState {
             }
             if caps.contains(wl_seat::Capability::Pointer) && state.pointer.is_none() {
                 state.pointer = Some(seat.get_pointer(qh, ()));
+                state.bind_gestures();
+            }
+            if caps.contains(wl_seat::Capability::Touch) && state.touch.is_none() {
+                state.touch = Some(seat.get_touch(qh, ()));
             }
         }
     }
@@ -543,20 +982,39 @@ impl Dispatch<wl_keyboard::WlKeyboard, ()> for WaylandState {
             }
             wl_keyboard::Event::Key {
                 key,
-                state: key_state,
+                state: WEnum::Value(key_state),
                 ..
             } => {
-                let sym = if let Some(ks) = state.keyboard_state.as_mut() {
-                    if let WEnum::Value(ks_val) = key_state {
-                        key_event(ks, key, ks_val)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
+                let Some(ks) = state.keyboard_state.as_ref() else {
+                    return;
                 };
-                if let Some(sym) = sym {
-                    state.pending_events.push(InputEvent::Key(sym));
+                if key_state == wl_keyboard::KeyState::Released {
+                    state.key_repeat.release(key);
+                }
+                if let Some((sym, mods)) = key_event(ks, key, key_state) {
+                    if ks.key_repeats(key) {
+                        state.key_repeat.press(key, sym, mods, Instant::now());
+                    }
+                    state.push_event(InputEvent::Key {
+                        sym,
+                        mods,
+                        repeat: false,
+                    });
+                }
+            }
+            wl_keyboard::Event::RepeatInfo { rate, delay } => {
+                state.key_repeat.set_info(rate, delay);
+            }
+            wl_keyboard::Event::Leave { .. } => state.key_repeat.stop(),
+            wl_keyboard::Event::Modifiers {
+                mods_depressed,
+                mods_latched,
+                mods_locked,
+                group,
+                ..
+            } => {
+                if let Some(ks) = state.keyboard_state.as_mut() {
+                    update_modifiers(ks, mods_depressed, mods_latched, mods_locked, group);
                 }
             }
             _ => {}
@@ -567,7 +1025,7 @@ impl Dispatch<wl_keyboard::WlKeyboard, ()> for WaylandState {
 impl Dispatch<wl_pointer::WlPointer, ()> for WaylandState {
     fn event(
         state: &mut Self,
-        _: &wl_pointer::WlPointer,
+        pointer: &wl_pointer::WlPointer,
         event: wl_pointer::Event,
         _: &(),
         _: &Connection,
@@ -575,43 +1033,171 @@ impl Dispatch<wl_pointer::WlPointer, ()> for WaylandState {
     ) {
         match event {
             wl_pointer::Event::Button {
+                time,
+                button,
                 state: WEnum::Value(btn),
                 ..
             } => {
                 let pressed = btn == wl_pointer::ButtonState::Pressed;
-                state.pointer_pressed = pressed;
-                state
-                    .pending_events
-                    .push(InputEvent::PointerButton { pressed });
+                let mut double = false;
+                if pressed {
+                    state.buttons_held += 1;
+                    double = state.last_press.is_some_and(|(b, t)| {
+                        b == button && time.wrapping_sub(t) <= DOUBLE_CLICK_MS
+                    });
+                    // A third press starts a new click sequence.
+                    state.last_press = (!double).then_some((button, time));
+                } else {
+                    state.buttons_held = state.buttons_held.saturating_sub(1);
+                }
+                let mods = state.modifiers();
+                state.push_event(InputEvent::PointerButton {
+                    button,
+                    pressed,
+                    mods,
+                    double,
+                });
+            }
+            wl_pointer::Event::Leave { .. } => {
+                state.buttons_held = 0;
+                state.push_event(InputEvent::PointerLeave);
             }
             wl_pointer::Event::Motion {
                 surface_x,
                 surface_y,
                 ..
             } => {
-                if state.pointer_pressed {
+                if state.buttons_held > 0 {
                     let dx = (surface_x - state.pointer_pos.0) as f32;
                     let dy = (surface_y - state.pointer_pos.1) as f32;
-                    state
-                        .pending_events
-                        .push(InputEvent::PointerMotion { dx, dy });
+                    state.push_event(InputEvent::PointerMotion { dx, dy });
                 }
                 state.pointer_pos = (surface_x, surface_y);
             }
+            wl_pointer::Event::AxisSource {
+                axis_source: WEnum::Value(source),
+            } => state.axis_frame.source(source),
             wl_pointer::Event::Axis {
-                axis: WEnum::Value(wl_pointer::Axis::VerticalScroll),
+                axis: WEnum::Value(axis),
                 value,
                 ..
             } => {
-                // Normalise: raw axis value is ~10–15 units per wheel notch on most
-                // compositors (wlroots/libinput default is 15). Dividing by 10.0 maps
-                // one notch to ≈1.0, so `scale_step` in config means "zoom % per notch".
-                let cursor = (state.pointer_pos.0 as f32, state.pointer_pos.1 as f32);
-                state.pending_events.push(InputEvent::Scroll {
-                    delta: -value as f32 / 10.0,
-                    cursor,
+                state.axis_frame.axis(axis, value);
+                // Before seat v5 there are no frame events to group by.
+                if pointer.version() < 5 {
+                    state.flush_axis_frame();
+                }
+            }
+            wl_pointer::Event::AxisDiscrete {
+                axis: WEnum::Value(axis),
+                discrete,
+            } => state.axis_frame.discrete(axis, discrete),
+            wl_pointer::Event::AxisValue120 {
+                axis: WEnum::Value(axis),
+                value120,
+            } => state.axis_frame.value120(axis, value120),
+            wl_pointer::Event::Frame => state.flush_axis_frame(),
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<wl_touch::WlTouch, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        _: &wl_touch::WlTouch,
+        event: wl_touch::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let gesture = match event {
+            wl_touch::Event::Down { time, id, x, y, .. } => {
+                state.touch_gestures.down(id, time, (x, y));
+                None
+            }
+            wl_touch::Event::Motion { id, x, y, .. } => state.touch_gestures.motion(id, (x, y)),
+            wl_touch::Event::Up { time, id, .. } => state.touch_gestures.up(id, time),
+            wl_touch::Event::Cancel => {
+                state.touch_gestures.cancel();
+                None
+            }
+            _ => None,
+        };
+        if let Some(gesture) = gesture {
+            state.push_event(InputEvent::Touch(gesture));
+        }
+    }
+}
+
+impl Dispatch<zwp_pointer_gestures_v1::ZwpPointerGesturesV1, ()> for WaylandState {
+    fn event(
+        _: &mut Self,
+        _: &zwp_pointer_gestures_v1::ZwpPointerGesturesV1,
+        _: zwp_pointer_gestures_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        _: &zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1,
+        event: zwp_pointer_gesture_pinch_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwp_pointer_gesture_pinch_v1::Event::Begin { .. } => {
+                state.pinch.begin(state.pointer_pos);
+            }
+            zwp_pointer_gesture_pinch_v1::Event::Update {
+                dx,
+                dy,
+                scale,
+                rotation,
+                ..
+            } => {
+                let step = state.pinch.update(dx, dy, scale, rotation);
+                state.push_event(InputEvent::Pinch {
+                    scale: step.scale,
+                    rotation: step.rotation,
+                    centre: step.centre,
                 });
             }
+            zwp_pointer_gesture_pinch_v1::Event::End { .. } => {
+                state.push_event(InputEvent::PinchEnd);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        _: &zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1,
+        event: zwp_pointer_gesture_swipe_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwp_pointer_gesture_swipe_v1::Event::Begin { fingers, .. } => {
+                state.swipe.begin(fingers);
+            }
+            zwp_pointer_gesture_swipe_v1::Event::Update { dx, dy, .. } => {
+                state.swipe.update(dx, dy);
+            }
+            zwp_pointer_gesture_swipe_v1::Event::End { cancelled, .. } => {
+                if let Some((fingers, (dx, dy))) = state.swipe.end(cancelled != 0) {
+                    state.push_event(InputEvent::Swipe { fingers, dx, dy });
+                }
+            }
             _ => {}
         }
     }
@@ -646,18 +1232,33 @@ impl Dispatch<xdg_toplevel::XdgToplevel, ()> for WaylandState {
         _: &QueueHandle<Self>,
     ) {
         match event {
-            xdg_toplevel::Event::Configure { width, height, .. } => {
-                let w = if width == 0 {
-                    state.window_size.0 as i32
+            xdg_toplevel::Event::Configure {
+                width,
+                height,
+                states,
+            } => {
+                let toplevel_state = ToplevelState::from_states(&states);
+                if toplevel_state != state.toplevel_state {
+                    debug!(?toplevel_state, "window state changed");
+                    state.toplevel_state = toplevel_state;
+                }
+                // A zero dimension leaves the size to us: keep the current
+                // size, or restore the floating size after fullscreen.
+                let fallback = if toplevel_state.is_constrained() {
+                    state.window_size
                 } else {
-                    width
+                    state.windowed_size
                 };
+                let w = if width == 0 { fallback.0 } else { width as u32 };
                 let h = if height == 0 {
-                    state.window_size.1 as i32
+                    fallback.1
                 } else {
-                    height
+                    height as u32
                 };
-                let new_size = (w as u32, h as u32);
+                let new_size = (w, h);
+                if !toplevel_state.is_constrained() {
+                    state.windowed_size = new_size;
+                }
                 if new_size != state.window_size {
                     debug!(width = new_size.0, height = new_size.1, "window resized");
                     state.window_size = new_size;
@@ -685,15 +1286,18 @@ impl Dispatch<wl_shm_pool::WlShmPool, ()> for WaylandState {
     }
 }
 
-impl Dispatch<wl_buffer::WlBuffer, ()> for WaylandState {
+impl Dispatch<wl_buffer::WlBuffer, u64> for WaylandState {
     fn event(
-        _: &mut Self,
+        state: &mut Self,
         _: &wl_buffer::WlBuffer,
-        _: wl_buffer::Event,
-        _: &(),
+        event: wl_buffer::Event,
+        id: &u64,
         _: &Connection,
         _: &QueueHandle<Self>,
     ) {
+        if let wl_buffer::Event::Release = event {
+            state.buffers.release(*id);
+        }
     }
 }
 
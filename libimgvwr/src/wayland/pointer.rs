@@ -0,0 +1,182 @@
+//! Pointer axis (scroll) accumulation and touchpad gesture tracking.
+//!
+//! `wl_pointer` reports one scroll gesture as several events — `axis_source`,
+//! `axis`, `axis_discrete` / `axis_value120` per axis — terminated by
+//! `wl_pointer::frame`. [`AxisFrame`] collects them and turns each frame into
+//! a single two-axis delta tagged with a [`ScrollKind`], so wheels and
+//! touchpads can be handled differently.
+//!
+//! [`PinchTracker`] and [`SwipeTracker`] follow `zwp_pointer_gestures_v1`
+//! pinch and swipe gestures from `begin` to `end`.
+
+use wayland_client::protocol::wl_pointer::{Axis, AxisSource};
+
+/// Raw axis units per wheel notch when the compositor sends neither
+/// `axis_value120` nor `axis_discrete`. Most compositors report 10–15 per
+/// notch (wlroots/libinput default is 15); 10.0 maps one notch to ≈1.0.
+const LEGACY_NOTCH: f64 = 10.0;
+
+/// `axis_value120` units per wheel notch.
+const VALUE120_NOTCH: f64 = 120.0;
+
+/// How a scroll delta was produced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollKind {
+    /// A mouse wheel or tilt wheel; the delta is in notches (fractional on
+    /// high-resolution wheels).
+    Wheel,
+    /// Touchpad fingers or another continuous source; the delta is in
+    /// surface pixels here and in buffer pixels once queued as an
+    /// [`InputEvent`](super::InputEvent).
+    Smooth,
+}
+
+/// Axis events received since the last `wl_pointer::frame`.
+#[derive(Debug, Default)]
+pub struct AxisFrame {
+    source: Option<AxisSource>,
+    /// Continuous values, indexed `[horizontal, vertical]`.
+    value: [f64; 2],
+    /// High-resolution wheel steps (seat v8+).
+    value120: [i32; 2],
+    /// Whole wheel notches (seat v5–v7).
+    discrete: [i32; 2],
+}
+
+impl AxisFrame {
+    /// Record a `wl_pointer::axis_source` event.
+    pub fn source(&mut self, source: AxisSource) {
+        self.source = Some(source);
+    }
+
+    /// Record a `wl_pointer::axis` event.
+    pub fn axis(&mut self, axis: Axis, value: f64) {
+        self.value[index(axis)] += value;
+    }
+
+    /// Record a `wl_pointer::axis_discrete` event.
+    pub fn discrete(&mut self, axis: Axis, discrete: i32) {
+        self.discrete[index(axis)] += discrete;
+    }
+
+    /// Record a `wl_pointer::axis_value120` event.
+    pub fn value120(&mut self, axis: Axis, value120: i32) {
+        self.value120[index(axis)] += value120;
+    }
+
+    /// Finish the frame: return the `(horizontal, vertical)` delta and its
+    /// kind, or `None` if nothing scrolled. Positive values scroll right and
+    /// down (towards the user). The accumulator is reset either way.
+    pub fn take(&mut self) -> Option<((f32, f32), ScrollKind)> {
+        let frame = std::mem::take(self);
+        let kind = match frame.source {
+            Some(AxisSource::Finger | AxisSource::Continuous) => ScrollKind::Smooth,
+            _ => ScrollKind::Wheel,
+        };
+        let delta = |i: usize| -> f32 {
+            let v = match kind {
+                ScrollKind::Smooth => frame.value[i],
+                ScrollKind::Wheel if frame.value120[i] != 0 => {
+                    f64::from(frame.value120[i]) / VALUE120_NOTCH
+                }
+                ScrollKind::Wheel if frame.discrete[i] != 0 => f64::from(frame.discrete[i]),
+                ScrollKind::Wheel => frame.value[i] / LEGACY_NOTCH,
+            };
+            v as f32
+        };
+        let delta = (delta(0), delta(1));
+        (delta != (0.0, 0.0)).then_some((delta, kind))
+    }
+}
+
+fn index(axis: Axis) -> usize {
+    match axis {
+        Axis::HorizontalScroll => 0,
+        _ => 1,
+    }
+}
+
+/// One step of an active pinch gesture, as reported by
+/// [`PinchTracker::update`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PinchStep {
+    /// Zoom factor since the previous step (`> 1.0` spreads the fingers).
+    pub scale: f32,
+    /// Degrees turned clockwise since the gesture began.
+    pub rotation: f32,
+    /// Gesture centre in surface coordinates; queued events carry it in
+    /// buffer pixels.
+    pub centre: (f32, f32),
+}
+
+/// State of the active touchpad pinch.
+#[derive(Debug, Default)]
+pub struct PinchTracker {
+    /// Absolute scale of the previous update, relative to the gesture start.
+    scale: f64,
+    rotation: f64,
+    centre: (f64, f64),
+}
+
+impl PinchTracker {
+    /// Start a gesture centred on the pointer position.
+    pub fn begin(&mut self, centre: (f64, f64)) {
+        *self = PinchTracker {
+            scale: 1.0,
+            rotation: 0.0,
+            centre,
+        };
+    }
+
+    /// Apply a `zwp_pointer_gesture_pinch_v1::update`: `dx`/`dy` move the
+    /// centre, `scale` is absolute since `begin` and `rotation` is the
+    /// clockwise delta in degrees since the previous update.
+    pub fn update(&mut self, dx: f64, dy: f64, scale: f64, rotation: f64) -> PinchStep {
+        let ratio = if self.scale > 0.0 {
+            scale / self.scale
+        } else {
+            1.0
+        };
+        self.scale = scale;
+        self.rotation += rotation;
+        self.centre.0 += dx;
+        self.centre.1 += dy;
+        PinchStep {
+            scale: ratio as f32,
+            rotation: self.rotation as f32,
+            centre: (self.centre.0 as f32, self.centre.1 as f32),
+        }
+    }
+}
+
+/// State of the active touchpad swipe.
+#[derive(Debug, Default)]
+pub struct SwipeTracker {
+    fingers: u32,
+    delta: (f64, f64),
+}
+
+impl SwipeTracker {
+    /// Start a swipe with `fingers` fingers.
+    pub fn begin(&mut self, fingers: u32) {
+        *self = SwipeTracker {
+            fingers,
+            delta: (0.0, 0.0),
+        };
+    }
+
+    /// Add the motion from a `zwp_pointer_gesture_swipe_v1::update`.
+    pub fn update(&mut self, dx: f64, dy: f64) {
+        self.delta.0 += dx;
+        self.delta.1 += dy;
+    }
+
+    /// Finish the swipe: the finger count and total motion in surface
+    /// pixels (scaled to buffer pixels when queued), or `None` when the
+    /// compositor cancelled it.
+    pub fn end(&mut self, cancelled: bool) -> Option<(u32, (f32, f32))> {
+        let swipe = std::mem::take(self);
+        (!cancelled && swipe.fingers > 0)
+            .then_some((swipe.fingers, (swipe.delta.0 as f32, swipe.delta.1 as f32)))
+    }
+}
@@ -0,0 +1,71 @@
+//! Output scale tracking for HiDPI rendering.
+//!
+//! The compositor reports the preferred scale of a surface in up to three
+//! ways, best first: `wp_fractional_scale_v1::preferred_scale` (in 120ths,
+//! usable only together with `wp_viewporter`), `wl_surface::preferred_buffer_scale`
+//! (`wl_compositor` v6), and the `wl_output::scale` of the outputs the surface
+//! is shown on. [`SurfaceScale`] keeps the latest value of each and decides
+//! the scale and size of the buffers the client should render.
+
+/// Denominator of fractional scale values: `120` means a scale of 1.0.
+pub const SCALE_DENOMINATOR: u32 = 120;
+
+/// Scale hints received for one surface.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SurfaceScale {
+    fractional: Option<u32>,
+    preferred: Option<u32>,
+    output: Option<u32>,
+    viewporter: bool,
+}
+
+impl SurfaceScale {
+    /// Record `wp_fractional_scale_v1::preferred_scale` (in 120ths).
+    pub fn set_fractional(&mut self, scale: u32) {
+        self.fractional = Some(scale);
+    }
+
+    /// Record `wl_surface::preferred_buffer_scale`.
+    pub fn set_preferred(&mut self, factor: i32) {
+        self.preferred = u32::try_from(factor).ok().filter(|&f| f > 0);
+    }
+
+    /// Record the largest `wl_output::scale` among the outputs the surface
+    /// is on, or `None` when it is on no known output.
+    pub fn set_output(&mut self, factor: Option<i32>) {
+        self.output = factor
+            .and_then(|f| u32::try_from(f).ok())
+            .filter(|&f| f > 0);
+    }
+
+    /// Record whether a `wp_viewport` exists for the surface. Without one
+    /// only integer scales can be honoured.
+    pub fn set_viewporter(&mut self, viewporter: bool) {
+        self.viewporter = viewporter;
+    }
+
+    /// The scale to render at, in 120ths. Always a multiple of
+    /// [`SCALE_DENOMINATOR`] unless a viewport is available.
+    pub fn scale120(&self) -> u32 {
+        let integer = self.preferred.or(self.output).unwrap_or(1) * SCALE_DENOMINATOR;
+        match self.fractional {
+            Some(fractional) if self.viewporter => fractional,
+            _ => integer,
+        }
+    }
+
+    /// The scale to render at as a factor (`1.5` on a 150 % output).
+    pub fn factor(&self) -> f64 {
+        f64::from(self.scale120()) / f64::from(SCALE_DENOMINATOR)
+    }
+
+    /// Buffer size in physical pixels for a surface of `logical` size,
+    /// rounding half away from zero as `fractional-scale-v1` recommends.
+    pub fn buffer_size(&self, logical: (u32, u32)) -> (u32, u32) {
+        let scale = u64::from(self.scale120());
+        let half = u64::from(SCALE_DENOMINATOR / 2);
+        let physical =
+            |v: u32| ((u64::from(v) * scale + half) / u64::from(SCALE_DENOMINATOR)) as u32;
+        (physical(logical.0), physical(logical.1))
+    }
+}
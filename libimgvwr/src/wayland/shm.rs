@@ -1,8 +1,14 @@
-//! Wayland SHM pool backed by a `memfd`.
+//! Wayland SHM pool backed by a `memfd`, and the persistent frame buffers
+//! built on it.
 //!
 //! [`ShmPool`] owns the file descriptor and the memory mapping. Call
 //! [`ShmPool::fd`] to obtain the [`BorrowedFd`] needed by
 //! `wl_shm::create_pool`.
+//!
+//! [`ShmBuffer`] is one `wl_buffer` with its own pool. [`BufferSlots`] keeps
+//! up to [`MAX_BUFFERS`] of them: a buffer attached to the surface stays busy
+//! until the compositor sends `wl_buffer::release`, and is only written to
+//! again after that.
 
 use std::{
     io,
@@ -11,6 +17,14 @@ use std::{
 
 use memmap2::MmapMut;
 use rustix::fs::{MemfdFlags, ftruncate, memfd_create};
+use wayland_client::{
+    Dispatch, QueueHandle,
+    protocol::{wl_buffer, wl_shm, wl_shm_pool},
+};
+
+/// Most buffers kept alive at once: one on screen, one queued in the
+/// compositor, one being drawn.
+pub const MAX_BUFFERS: usize = 3;
 
 /// An anonymous shared-memory pool suitable for use as a Wayland SHM buffer.
 pub struct ShmPool {
@@ -56,3 +70,133 @@ impl ShmPool {
         self.fd.as_fd()
     }
 }
+
+/// A persistent ARGB8888 `wl_buffer` and the memory behind it.
+pub struct ShmBuffer {
+    pool: ShmPool,
+    buffer: wl_buffer::WlBuffer,
+}
+
+impl ShmBuffer {
+    /// Allocate a `width × height` buffer. `id` is the `wl_buffer` user
+    /// data, reported back to [`BufferSlots::release`] on release.
+    pub fn create<D>(
+        wl_shm: &wl_shm::WlShm,
+        (width, height): (u32, u32),
+        id: u64,
+        qh: &QueueHandle<D>,
+    ) -> io::Result<ShmBuffer>
+    where
+        D: Dispatch<wl_shm_pool::WlShmPool, ()> + Dispatch<wl_buffer::WlBuffer, u64> + 'static,
+    {
+        let size = width as usize * height as usize * 4;
+        let pool = ShmPool::create(size)?;
+        let wl_pool = wl_shm.create_pool(pool.fd(), size as i32, qh, ());
+        let buffer = wl_pool.create_buffer(
+            0,
+            width as i32,
+            height as i32,
+            width as i32 * 4,
+            wl_shm::Format::Argb8888,
+            qh,
+            id,
+        );
+        // The buffer keeps the memory alive; the pool is never resized.
+        wl_pool.destroy();
+        Ok(ShmBuffer { pool, buffer })
+    }
+
+    /// The pixel memory, `width × height × 4` bytes.
+    pub fn pixels_mut(&mut self) -> &mut [u8] {
+        self.pool.as_mut_slice()
+    }
+
+    /// The `wl_buffer` to attach to the surface.
+    pub fn wl_buffer(&self) -> &wl_buffer::WlBuffer {
+        &self.buffer
+    }
+
+    /// Destroy the `wl_buffer`. The memory is unmapped when `self` drops.
+    pub fn destroy(self) {
+        self.buffer.destroy();
+    }
+}
+
+struct Slot<T> {
+    id: u64,
+    size: (u32, u32),
+    busy: bool,
+    buffer: T,
+}
+
+/// Up to [`MAX_BUFFERS`] reusable buffers, tracked by size and by whether
+/// the compositor still holds them.
+pub struct BufferSlots<T> {
+    slots: Vec<Slot<T>>,
+    next_id: u64,
+}
+
+impl<T> Default for BufferSlots<T> {
+    fn default() -> Self {
+        BufferSlots {
+            slots: Vec::new(),
+            next_id: 0,
+        }
+    }
+}
+
+impl<T> BufferSlots<T> {
+    /// Find a released buffer of `size`, creating one with `create` (given
+    /// its id) if there is room. Released buffers of another size are
+    /// passed to `destroy` first. Returns `None` when every buffer is still
+    /// held by the compositor; try again after a release.
+    pub fn acquire(
+        &mut self,
+        size: (u32, u32),
+        create: impl FnOnce(u64) -> io::Result<T>,
+        mut destroy: impl FnMut(T),
+    ) -> io::Result<Option<&mut T>> {
+        let (stale, keep): (Vec<_>, Vec<_>) = std::mem::take(&mut self.slots)
+            .into_iter()
+            .partition(|s| !s.busy && s.size != size);
+        self.slots = keep;
+        for slot in stale {
+            destroy(slot.buffer);
+        }
+        let index = match self.slots.iter().position(|s| !s.busy) {
+            Some(index) => index,
+            None if self.slots.len() < MAX_BUFFERS => {
+                let id = self.next_id;
+                self.next_id += 1;
+                self.slots.push(Slot {
+                    id,
+                    size,
+                    busy: false,
+                    buffer: create(id)?,
+                });
+                self.slots.len() - 1
+            }
+            None => return Ok(None),
+        };
+        let slot = &mut self.slots[index];
+        slot.busy = true;
+        Ok(Some(&mut slot.buffer))
+    }
+
+    /// Mark the buffer with `id` as released by the compositor.
+    pub fn release(&mut self, id: u64) {
+        if let Some(slot) = self.slots.iter_mut().find(|s| s.id == id) {
+            slot.busy = false;
+        }
+    }
+
+    /// Number of buffers currently allocated.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// `true` when no buffer is allocated.
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+}
@@ -0,0 +1,452 @@
+use std::time::{Duration, Instant};
+
+use wayland_client::protocol::{
+    wl_keyboard::KeyState,
+    wl_pointer::{Axis, AxisSource},
+};
+use xkbcommon::xkb;
+
+use super::{
+    ToplevelState,
+    keyboard::{KeyRepeat, KeyboardState, key_event, update_modifiers},
+    pointer::{AxisFrame, PinchStep, PinchTracker, ScrollKind, SwipeTracker},
+    scale::SurfaceScale,
+    shm::{BufferSlots, MAX_BUFFERS},
+    touch::{DOUBLE_TAP_MS, TAP_MS, TouchGesture, TouchRecognizer},
+};
+use crate::keybinds::{Modifiers, keysym_from_str};
+
+// Linux evdev scancodes.
+const KEY_EQUAL: u32 = 13;
+const KEY_S: u32 = 31;
+const KEY_LEFTSHIFT: u32 = 42;
+const KEY_RIGHT: u32 = 106;
+
+/// A US keymap compiled from the system XKB data, or `None` when the data is
+/// not installed.
+fn us_keyboard() -> Option<KeyboardState> {
+    let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+    let keymap = xkb::Keymap::new_from_names(
+        &context,
+        "evdev",
+        "pc105",
+        "us",
+        "",
+        None,
+        xkb::KEYMAP_COMPILE_NO_FLAGS,
+    )?;
+    Some(KeyboardState::from_keymap(context, keymap))
+}
+
+fn press(ks: &KeyboardState, key: u32) -> Option<(xkb::Keysym, Modifiers)> {
+    key_event(ks, key, KeyState::Pressed)
+}
+
+// Shift is modifier index 0 and Control index 2 in every XKB keymap.
+const SHIFT_MASK: u32 = 0b001;
+const CTRL_MASK: u32 = 0b100;
+
+#[test]
+fn key_release_yields_nothing() {
+    let Some(ks) = us_keyboard() else { return };
+    assert!(key_event(&ks, KEY_S, KeyState::Released).is_none());
+}
+
+#[test]
+fn plain_key_has_no_modifiers() {
+    let Some(ks) = us_keyboard() else { return };
+    let (sym, mods) = press(&ks, KEY_S).unwrap();
+    assert_eq!(sym, keysym_from_str("s").unwrap());
+    assert!(mods.is_empty());
+}
+
+#[test]
+fn shift_consumed_by_translation_is_dropped() {
+    let Some(mut ks) = us_keyboard() else { return };
+    update_modifiers(&mut ks, SHIFT_MASK, 0, 0, 0);
+    let (sym, mods) = press(&ks, KEY_EQUAL).unwrap();
+    assert_eq!(sym, keysym_from_str("plus").unwrap());
+    assert!(mods.is_empty());
+}
+
+#[test]
+fn ctrl_shift_letter_keeps_only_ctrl() {
+    let Some(mut ks) = us_keyboard() else { return };
+    update_modifiers(&mut ks, CTRL_MASK | SHIFT_MASK, 0, 0, 0);
+    let (sym, mods) = press(&ks, KEY_S).unwrap();
+    assert_eq!(sym, keysym_from_str("S").unwrap());
+    assert_eq!(
+        mods,
+        Modifiers {
+            ctrl: true,
+            ..Modifiers::NONE
+        }
+    );
+}
+
+#[test]
+fn modifiers_event_updates_state() {
+    let Some(mut ks) = us_keyboard() else { return };
+    update_modifiers(&mut ks, CTRL_MASK | SHIFT_MASK, 0, 0, 0);
+    assert_eq!(
+        ks.modifiers(),
+        Modifiers {
+            ctrl: true,
+            shift: true,
+            ..Modifiers::NONE
+        }
+    );
+    let (sym, mods) = press(&ks, KEY_RIGHT).unwrap();
+    assert_eq!(sym, keysym_from_str("Right").unwrap());
+    assert!(mods.ctrl);
+}
+
+#[test]
+fn modifier_keys_do_not_repeat() {
+    let Some(ks) = us_keyboard() else { return };
+    assert!(ks.key_repeats(KEY_S));
+    assert!(!ks.key_repeats(KEY_LEFTSHIFT));
+}
+
+fn held_s(repeat: &mut KeyRepeat, t0: Instant) {
+    let s = keysym_from_str("s").unwrap();
+    repeat.press(KEY_S, s, Modifiers::NONE, t0);
+}
+
+#[test]
+fn repeat_waits_for_delay_then_follows_rate() {
+    let t0 = Instant::now();
+    let mut repeat = KeyRepeat::default();
+    repeat.set_info(10, 300);
+    held_s(&mut repeat, t0);
+    assert_eq!(
+        repeat.next_deadline(),
+        Some(t0 + Duration::from_millis(300))
+    );
+    assert!(repeat.due(t0 + Duration::from_millis(299)).is_none());
+    assert!(repeat.due(t0 + Duration::from_millis(300)).is_some());
+    assert_eq!(
+        repeat.next_deadline(),
+        Some(t0 + Duration::from_millis(400))
+    );
+    assert!(repeat.due(t0 + Duration::from_millis(350)).is_none());
+    assert!(repeat.due(t0 + Duration::from_millis(400)).is_some());
+}
+
+#[test]
+fn repeat_does_not_burst_after_stall() {
+    let t0 = Instant::now();
+    let mut repeat = KeyRepeat::default();
+    repeat.set_info(10, 300);
+    held_s(&mut repeat, t0);
+    let late = t0 + Duration::from_secs(5);
+    assert!(repeat.due(late).is_some());
+    assert!(repeat.due(late).is_none());
+    assert_eq!(
+        repeat.next_deadline(),
+        Some(late + Duration::from_millis(100))
+    );
+}
+
+#[test]
+fn release_of_held_key_stops_repeat() {
+    let t0 = Instant::now();
+    let mut repeat = KeyRepeat::default();
+    held_s(&mut repeat, t0);
+    repeat.release(KEY_EQUAL);
+    assert!(repeat.next_deadline().is_some());
+    repeat.release(KEY_S);
+    assert!(repeat.next_deadline().is_none());
+}
+
+#[test]
+fn zero_rate_disables_repeat() {
+    let t0 = Instant::now();
+    let mut repeat = KeyRepeat::default();
+    held_s(&mut repeat, t0);
+    repeat.set_info(0, 300);
+    assert!(repeat.next_deadline().is_none());
+    held_s(&mut repeat, t0);
+    assert!(repeat.due(t0 + Duration::from_secs(1)).is_none());
+}
+
+#[test]
+fn wheel_frame_prefers_value120() {
+    let mut frame = AxisFrame::default();
+    frame.source(AxisSource::Wheel);
+    frame.axis(Axis::VerticalScroll, 7.5);
+    frame.value120(Axis::VerticalScroll, 60);
+    frame.discrete(Axis::VerticalScroll, 1);
+    assert_eq!(frame.take(), Some(((0.0, 0.5), ScrollKind::Wheel)));
+    assert_eq!(frame.take(), None);
+}
+
+#[test]
+fn wheel_frame_falls_back_to_discrete_then_value() {
+    let mut frame = AxisFrame::default();
+    frame.axis(Axis::HorizontalScroll, -15.0);
+    frame.discrete(Axis::HorizontalScroll, -1);
+    frame.axis(Axis::VerticalScroll, 20.0);
+    assert_eq!(frame.take(), Some(((-1.0, 2.0), ScrollKind::Wheel)));
+}
+
+#[test]
+fn finger_frame_is_smooth_pixels() {
+    let mut frame = AxisFrame::default();
+    frame.source(AxisSource::Finger);
+    frame.axis(Axis::VerticalScroll, 3.0);
+    frame.axis(Axis::HorizontalScroll, -1.5);
+    assert_eq!(frame.take(), Some(((-1.5, 3.0), ScrollKind::Smooth)));
+}
+
+#[test]
+fn source_only_frame_is_empty() {
+    let mut frame = AxisFrame::default();
+    frame.source(AxisSource::Finger);
+    assert_eq!(frame.take(), None);
+}
+
+#[test]
+fn pinch_reports_relative_scale_and_total_rotation() {
+    let mut pinch = PinchTracker::default();
+    pinch.begin((100.0, 50.0));
+    assert_eq!(
+        pinch.update(2.0, -1.0, 1.5, 10.0),
+        PinchStep {
+            scale: 1.5,
+            rotation: 10.0,
+            centre: (102.0, 49.0),
+        }
+    );
+    let step = pinch.update(0.0, 0.0, 0.75, 20.0);
+    assert_eq!(step.scale, 0.5);
+    assert_eq!(step.rotation, 30.0);
+    pinch.begin((0.0, 0.0));
+    assert_eq!(pinch.update(0.0, 0.0, 1.0, 0.0).rotation, 0.0);
+}
+
+#[test]
+fn swipe_sums_motion_until_end() {
+    let mut swipe = SwipeTracker::default();
+    swipe.begin(3);
+    swipe.update(-40.0, 5.0);
+    swipe.update(-80.0, -2.0);
+    assert_eq!(swipe.end(false), Some((3, (-120.0, 3.0))));
+    assert_eq!(swipe.end(false), None);
+}
+
+#[test]
+fn cancelled_swipe_is_dropped() {
+    let mut swipe = SwipeTracker::default();
+    swipe.begin(3);
+    swipe.update(-200.0, 0.0);
+    assert_eq!(swipe.end(true), None);
+}
+
+#[test]
+fn touch_tap_and_double_tap() {
+    let mut touch = TouchRecognizer::default();
+    touch.down(0, 1000, (50.0, 50.0));
+    assert_eq!(
+        touch.up(0, 1100),
+        Some(TouchGesture::Tap {
+            pos: (50.0, 50.0),
+            double: false
+        })
+    );
+    touch.down(0, 1200, (55.0, 48.0));
+    touch.motion(0, (57.0, 48.0));
+    assert_eq!(
+        touch.up(0, 1250),
+        Some(TouchGesture::Tap {
+            pos: (55.0, 48.0),
+            double: true
+        })
+    );
+    // A third tap starts over.
+    touch.down(0, 1300, (55.0, 48.0));
+    assert_eq!(
+        touch.up(0, 1350),
+        Some(TouchGesture::Tap {
+            pos: (55.0, 48.0),
+            double: false
+        })
+    );
+}
+
+#[test]
+fn touch_slow_taps_are_not_double() {
+    let mut touch = TouchRecognizer::default();
+    touch.down(0, 0, (50.0, 50.0));
+    touch.up(0, 10);
+    touch.down(0, 20 + DOUBLE_TAP_MS, (50.0, 50.0));
+    assert!(matches!(
+        touch.up(0, 30 + DOUBLE_TAP_MS),
+        Some(TouchGesture::Tap { double: false, .. })
+    ));
+    // A long press is not a tap at all.
+    touch.down(0, 5000, (50.0, 50.0));
+    assert_eq!(touch.up(0, 5001 + TAP_MS), None);
+}
+
+#[test]
+fn touch_drag_pans_after_slop() {
+    let mut touch = TouchRecognizer::default();
+    touch.down(3, 0, (100.0, 100.0));
+    assert_eq!(touch.motion(3, (104.0, 100.0)), None);
+    assert_eq!(
+        touch.motion(3, (120.0, 110.0)),
+        Some(TouchGesture::Pan { dx: 20.0, dy: 10.0 })
+    );
+    assert_eq!(
+        touch.motion(3, (125.0, 110.0)),
+        Some(TouchGesture::Pan { dx: 5.0, dy: 0.0 })
+    );
+    // Slow drag: no swipe on release.
+    assert_eq!(touch.up(3, 2000), None);
+}
+
+#[test]
+fn touch_flick_is_a_swipe() {
+    let mut touch = TouchRecognizer::default();
+    touch.down(0, 0, (400.0, 200.0));
+    touch.motion(0, (300.0, 210.0));
+    touch.motion(0, (250.0, 215.0));
+    assert_eq!(
+        touch.up(0, 150),
+        Some(TouchGesture::Swipe {
+            dx: -150.0,
+            dy: 15.0
+        })
+    );
+}
+
+#[test]
+fn touch_two_fingers_pinch() {
+    let mut touch = TouchRecognizer::default();
+    touch.down(0, 0, (100.0, 100.0));
+    touch.down(1, 10, (200.0, 100.0));
+    assert_eq!(
+        touch.motion(1, (300.0, 100.0)),
+        Some(TouchGesture::Pinch {
+            scale: 2.0,
+            centre: (200.0, 100.0),
+            dx: 50.0,
+            dy: 0.0,
+        })
+    );
+    // Lifting one finger ends the gesture without panning or tapping.
+    assert_eq!(touch.up(1, 100), None);
+    assert_eq!(touch.motion(0, (150.0, 150.0)), None);
+    assert_eq!(touch.up(0, 120), None);
+}
+
+#[test]
+fn touch_cancel_resets() {
+    let mut touch = TouchRecognizer::default();
+    touch.down(0, 0, (0.0, 0.0));
+    touch.cancel();
+    assert_eq!(touch.motion(0, (50.0, 0.0)), None);
+    assert_eq!(touch.up(0, 10), None);
+}
+
+#[test]
+fn toplevel_states_are_decoded() {
+    let states: Vec<u8> = [2u32, 1, 4, 99]
+        .iter()
+        .flat_map(|s| s.to_ne_bytes())
+        .collect();
+    assert_eq!(
+        ToplevelState::from_states(&states),
+        ToplevelState {
+            fullscreen: true,
+            maximized: true,
+            activated: true,
+        }
+    );
+    assert_eq!(ToplevelState::from_states(&[]), ToplevelState::default());
+    assert!(!ToplevelState::from_states(&4u32.to_ne_bytes()).is_constrained());
+}
+
+#[test]
+fn surface_scale_defaults_to_one() {
+    let scale = SurfaceScale::default();
+    assert_eq!(scale.scale120(), 120);
+    assert_eq!(scale.buffer_size((800, 600)), (800, 600));
+}
+
+#[test]
+fn fractional_scale_needs_a_viewport() {
+    let mut scale = SurfaceScale::default();
+    scale.set_fractional(180);
+    scale.set_preferred(2);
+    assert_eq!(scale.buffer_size((801, 600)), (1602, 1200));
+    scale.set_viewporter(true);
+    assert_eq!(scale.factor(), 1.5);
+    // 801 × 1.5 = 1201.5 rounds half away from zero.
+    assert_eq!(scale.buffer_size((801, 600)), (1202, 900));
+}
+
+#[test]
+fn preferred_buffer_scale_beats_output_scale() {
+    let mut scale = SurfaceScale::default();
+    scale.set_output(Some(3));
+    assert_eq!(scale.buffer_size((100, 50)), (300, 150));
+    scale.set_preferred(2);
+    assert_eq!(scale.buffer_size((100, 50)), (200, 100));
+    scale.set_preferred(0);
+    scale.set_output(None);
+    assert_eq!(scale.scale120(), 120);
+}
+
+/// Acquire a buffer of `size`, returning its id; `None` when all are busy.
+fn acquire(
+    slots: &mut BufferSlots<u64>,
+    size: (u32, u32),
+    destroyed: &mut Vec<u64>,
+) -> Option<u64> {
+    slots
+        .acquire(size, Ok, |id| destroyed.push(id))
+        .unwrap()
+        .copied()
+}
+
+#[test]
+fn released_buffers_are_reused() {
+    let mut slots = BufferSlots::default();
+    let mut destroyed = Vec::new();
+    let first = acquire(&mut slots, (4, 4), &mut destroyed).unwrap();
+    let second = acquire(&mut slots, (4, 4), &mut destroyed).unwrap();
+    assert_ne!(first, second);
+    slots.release(first);
+    assert_eq!(acquire(&mut slots, (4, 4), &mut destroyed), Some(first));
+    assert_eq!(slots.len(), 2);
+    assert!(destroyed.is_empty());
+}
+
+#[test]
+fn busy_buffers_are_never_handed_out() {
+    let mut slots = BufferSlots::default();
+    let mut destroyed = Vec::new();
+    for _ in 0..MAX_BUFFERS {
+        assert!(acquire(&mut slots, (4, 4), &mut destroyed).is_some());
+    }
+    assert_eq!(acquire(&mut slots, (4, 4), &mut destroyed), None);
+    slots.release(1);
+    assert_eq!(acquire(&mut slots, (4, 4), &mut destroyed), Some(1));
+}
+
+#[test]
+fn resize_recreates_buffers_once_released() {
+    let mut slots = BufferSlots::default();
+    let mut destroyed = Vec::new();
+    let old = acquire(&mut slots, (4, 4), &mut destroyed).unwrap();
+    let new = acquire(&mut slots, (8, 8), &mut destroyed).unwrap();
+    // The old buffer is still on screen, so it survives the resize.
+    assert!(destroyed.is_empty());
+    slots.release(old);
+    slots.release(new);
+    assert_eq!(acquire(&mut slots, (8, 8), &mut destroyed), Some(new));
+    assert_eq!(destroyed, [old]);
+    assert_eq!(slots.len(), 1);
+}
@@ -0,0 +1,211 @@
+//! Touchscreen gesture recognition.
+//!
+//! `wl_touch` only reports raw contact points. [`TouchRecognizer`] follows
+//! them from the first `down` to the last `up` and classifies the sequence:
+//! one finger drags ([`TouchGesture::Pan`]) or flicks
+//! ([`TouchGesture::Swipe`]), two fingers pinch ([`TouchGesture::Pinch`]),
+//! and short stationary touches are taps ([`TouchGesture::Tap`]). Times are
+//! the millisecond timestamps carried by the `wl_touch` events.
+//!
+//! Recognition runs on the surface coordinates `wl_touch` reports, so the
+//! distance thresholds below are in surface (logical) pixels and behave the
+//! same at any output scale. The gestures produced are scaled to buffer
+//! pixels when queued as [`InputEvent`](super::InputEvent)s.
+
+/// Distance in surface pixels a finger may move before a touch stops being
+/// a tap and starts panning.
+pub const TAP_SLOP_PX: f64 = 10.0;
+/// Longest touch, in milliseconds, that still counts as a tap.
+pub const TAP_MS: u32 = 250;
+/// Largest gap between two taps, in milliseconds, that forms a double tap.
+pub const DOUBLE_TAP_MS: u32 = 300;
+/// Largest distance between two taps, in surface pixels, that forms a
+/// double tap.
+pub const DOUBLE_TAP_SLOP_PX: f64 = 40.0;
+/// Longest one-finger drag, in milliseconds, that counts as a swipe.
+pub const SWIPE_MS: u32 = 300;
+/// Minimum horizontal travel of a swipe, in surface pixels.
+pub const SWIPE_MIN_PX: f64 = 100.0;
+
+/// A gesture recognised from a touch sequence, in surface pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TouchGesture {
+    /// One finger dragged by `(dx, dy)` surface pixels since the last event.
+    Pan { dx: f32, dy: f32 },
+    /// Two fingers moved: `scale` is the change in their distance since the
+    /// last event, `centre` their midpoint, and `dx`/`dy` the motion of the
+    /// midpoint.
+    Pinch {
+        scale: f32,
+        centre: (f32, f32),
+        dx: f32,
+        dy: f32,
+    },
+    /// A short touch without movement at `pos`; `double` marks the second
+    /// tap of a double tap.
+    Tap { pos: (f32, f32), double: bool },
+    /// A quick mostly-horizontal one-finger flick; total motion in surface
+    /// pixels.
+    Swipe { dx: f32, dy: f32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    /// No fingers down.
+    Idle,
+    /// One finger down that has not moved past [`TAP_SLOP_PX`].
+    Pending,
+    /// One finger dragging.
+    Pan,
+    /// Two fingers pinching.
+    Pinch,
+    /// The sequence can no longer form a gesture (a third finger, or a pinch
+    /// losing a finger); wait until every finger is lifted.
+    Ignored,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Point {
+    id: i32,
+    pos: (f64, f64),
+}
+
+/// Tracks the touch points of one seat and turns them into
+/// [`TouchGesture`]s.
+#[derive(Debug)]
+pub struct TouchRecognizer {
+    mode: Mode,
+    points: Vec<Point>,
+    /// Time and position of the first finger's `down`.
+    start: (u32, (f64, f64)),
+    /// Time and position of the last single tap, for double taps.
+    last_tap: Option<(u32, (f64, f64))>,
+}
+
+impl Default for TouchRecognizer {
+    fn default() -> Self {
+        TouchRecognizer {
+            mode: Mode::Idle,
+            points: Vec::new(),
+            start: (0, (0.0, 0.0)),
+            last_tap: None,
+        }
+    }
+}
+
+impl TouchRecognizer {
+    /// Handle `wl_touch::down` for contact `id` at `pos` (surface coords).
+    pub fn down(&mut self, id: i32, time: u32, pos: (f64, f64)) {
+        self.points.retain(|p| p.id != id);
+        self.points.push(Point { id, pos });
+        self.mode = match (self.mode, self.points.len()) {
+            (Mode::Idle, 1) => {
+                self.start = (time, pos);
+                Mode::Pending
+            }
+            (Mode::Pending | Mode::Pan, 2) => Mode::Pinch,
+            _ => Mode::Ignored,
+        };
+    }
+
+    /// Handle `wl_touch::motion` for contact `id`.
+    pub fn motion(&mut self, id: i32, pos: (f64, f64)) -> Option<TouchGesture> {
+        let before = self.points.clone();
+        let point = self.points.iter_mut().find(|p| p.id == id)?;
+        let prev = point.pos;
+        point.pos = pos;
+        match self.mode {
+            Mode::Pending => {
+                let origin = self.start.1;
+                if distance(origin, pos) <= TAP_SLOP_PX {
+                    return None;
+                }
+                self.mode = Mode::Pan;
+                Some(pan(origin, pos))
+            }
+            Mode::Pan => Some(pan(prev, pos)),
+            Mode::Pinch => {
+                let (old_centre, old_span) = spread(&before);
+                let (centre, span) = spread(&self.points);
+                Some(TouchGesture::Pinch {
+                    scale: if old_span > 0.0 {
+                        (span / old_span) as f32
+                    } else {
+                        1.0
+                    },
+                    centre: (centre.0 as f32, centre.1 as f32),
+                    dx: (centre.0 - old_centre.0) as f32,
+                    dy: (centre.1 - old_centre.1) as f32,
+                })
+            }
+            Mode::Idle | Mode::Ignored => None,
+        }
+    }
+
+    /// Handle `wl_touch::up` for contact `id`.
+    pub fn up(&mut self, id: i32, time: u32) -> Option<TouchGesture> {
+        let index = self.points.iter().position(|p| p.id == id)?;
+        let point = self.points.remove(index);
+        if !self.points.is_empty() {
+            // A pinch that loses a finger does not turn into a pan.
+            self.mode = Mode::Ignored;
+            return None;
+        }
+        let mode = std::mem::replace(&mut self.mode, Mode::Idle);
+        let (start_time, start_pos) = self.start;
+        let elapsed = time.wrapping_sub(start_time);
+        match mode {
+            Mode::Pending if elapsed <= TAP_MS => Some(self.tap(time, start_pos)),
+            Mode::Pan if elapsed <= SWIPE_MS => {
+                let dx = point.pos.0 - start_pos.0;
+                let dy = point.pos.1 - start_pos.1;
+                (dx.abs() >= SWIPE_MIN_PX && dx.abs() > dy.abs()).then_some(TouchGesture::Swipe {
+                    dx: dx as f32,
+                    dy: dy as f32,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Handle `wl_touch::cancel`: the compositor took over the sequence.
+    pub fn cancel(&mut self) {
+        self.mode = Mode::Idle;
+        self.points.clear();
+        self.last_tap = None;
+    }
+
+    fn tap(&mut self, time: u32, pos: (f64, f64)) -> TouchGesture {
+        let double = self.last_tap.is_some_and(|(t, p)| {
+            time.wrapping_sub(t) <= DOUBLE_TAP_MS && distance(p, pos) <= DOUBLE_TAP_SLOP_PX
+        });
+        // A third tap starts a new sequence.
+        self.last_tap = (!double).then_some((time, pos));
+        TouchGesture::Tap {
+            pos: (pos.0 as f32, pos.1 as f32),
+            double,
+        }
+    }
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (b.0 - a.0).hypot(b.1 - a.1)
+}
+
+fn pan(from: (f64, f64), to: (f64, f64)) -> TouchGesture {
+    TouchGesture::Pan {
+        dx: (to.0 - from.0) as f32,
+        dy: (to.1 - from.1) as f32,
+    }
+}
+
+/// Midpoint and distance of the first two points.
+fn spread(points: &[Point]) -> ((f64, f64), f64) {
+    match points {
+        [a, b, ..] => (
+            ((a.pos.0 + b.pos.0) / 2.0, (a.pos.1 + b.pos.1) / 2.0),
+            distance(a.pos, b.pos),
+        ),
+        _ => ((0.0, 0.0), 0.0),
+    }
+}